@@ -0,0 +1,59 @@
+//! Feeds arbitrary bytes to the serde deserialization of the stored
+//! entities, as the file store does for each JSON Lines record.
+//!
+//! Nothing may panic, whatever is accepted must survive a round trip
+//! unchanged, and the entity methods must cope with whatever history,
+//! dates and amounts the record carries.
+#![no_main]
+
+use accounting_spec::domain::entity::{Sales, Section, Term};
+use chrono::NaiveDate;
+use libfuzzer_sys::fuzz_target;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Deserializes `data`, then checks it serializes to something that reads
+/// back as the same record.
+fn round_trip<R: Serialize + DeserializeOwned>(data: &[u8]) -> Option<R> {
+    let record: R = serde_json::from_slice(data).ok()?;
+    let json = serde_json::to_string(&record).expect("a deserialized record serializes");
+    let again: R = serde_json::from_str(&json).expect("a serialized record deserializes");
+    assert_eq!(
+        json,
+        serde_json::to_string(&again).expect("a deserialized record serializes"),
+        "round trip changed the record"
+    );
+    Some(record)
+}
+
+fuzz_target!(|data: &[u8]| {
+    let dates = [NaiveDate::MIN, NaiveDate::MAX];
+
+    if let Some(mut section) = round_trip::<Section>(data) {
+        for date in dates.iter().chain(section.deactivated_on.as_ref()) {
+            section.is_active_on(*date);
+            section.name_on(*date);
+            section.parent_on(*date);
+        }
+        let _ = section
+            .clone()
+            .rename("Renamed".to_string(), NaiveDate::MAX);
+        let _ = section.clone().move_to(Some(Uuid::nil()), NaiveDate::MAX);
+        let _ = section.clone().merge_into(Uuid::nil(), NaiveDate::MAX);
+        let _ = section.deactivate(NaiveDate::MIN);
+    }
+
+    if let Some(mut term) = round_trip::<Term>(data) {
+        let _ = term.submit_section(Uuid::nil());
+        term.close();
+        assert!(term.submit_section(Uuid::nil()).is_err());
+    }
+
+    if let Some(sales) = round_trip::<Sales>(data) {
+        let _ = -sales.amount;
+        if sales.amount.is_postable() {
+            assert_eq!(sales.amount + sales.amount - sales.amount, sales.amount);
+        }
+    }
+});
@@ -0,0 +1,81 @@
+//! Feeds arbitrary bytes to the CSV sales import, in both modes, against a
+//! ledger with one section and one open term.
+//!
+//! Nothing may panic, every accepted row must be a registered sale, an
+//! all-or-nothing import that rejects a row must write nothing, and
+//! importing the same file again must add no entries.
+#![no_main]
+
+use accounting_spec::application::import::{import_sales, ImportMode};
+use accounting_spec::application::service::AccountingService;
+use accounting_spec::domain::entity::{SalesType, Section, SectionType, Term};
+use accounting_spec::domain::provider::SystemClock;
+use accounting_spec::domain::value_object::{ActorId, AuditInfo, SectionCode};
+use accounting_spec::infrastructure::in_memory::{
+    InMemoryAuditLogRepository, InMemorySalesRepository, InMemorySectionRepository,
+    InMemoryTermRepository,
+};
+use chrono::NaiveDate;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (bool, &[u8])| {
+    let (best_effort, data) = input;
+    let mode = if best_effort {
+        ImportMode::BestEffort
+    } else {
+        ImportMode::AllOrNothing
+    };
+    let audit = AuditInfo::new(
+        ActorId::new("fuzz".to_string()).unwrap(),
+        "fuzz",
+        &SystemClock,
+    );
+
+    let mut service = AccountingService::new(
+        InMemorySectionRepository::new(),
+        InMemoryTermRepository::new(),
+        InMemorySalesRepository::new(),
+        InMemoryAuditLogRepository::new(),
+    );
+    let section = Section::new(
+        service.ids().next_id(),
+        "Tokyo".to_string(),
+        SectionType::Section,
+        None,
+    )
+    .unwrap()
+    .with_code(SectionCode::new("JP-TKY".to_string()).unwrap());
+    service
+        .create_section(section, None, audit.clone())
+        .unwrap();
+    let term = Term::new(
+        service.ids().next_id(),
+        NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+    )
+    .unwrap();
+    let term_id = service.create_term(term, None, audit.clone()).unwrap();
+
+    let Ok(report) = import_sales(&mut service, data, mode, &audit) else {
+        return;
+    };
+    let entries = service.list_sales(&term_id).len();
+    if mode == ImportMode::AllOrNothing && report.rejected() > 0 {
+        assert_eq!(entries, 0, "an aborted import wrote");
+    }
+    for row in &report.rows {
+        if let Ok(id) = &row.outcome {
+            let sale = service.find_sales(id).expect("accepted row is registered");
+            assert_eq!(sale.sales_type, SalesType::Normal);
+        }
+    }
+    assert!(entries <= report.accepted());
+
+    let again = import_sales(&mut service, data, mode, &audit).expect("read once already");
+    assert_eq!(again.rows.len(), report.rows.len());
+    assert_eq!(
+        service.list_sales(&term_id).len(),
+        entries,
+        "re-import added entries"
+    );
+});
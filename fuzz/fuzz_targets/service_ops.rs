@@ -0,0 +1,583 @@
+//! Drives `AccountingService` with arbitrary sequences of operations: amounts
+//! up to the limits of `Decimal`, dates anywhere chrono can represent, any
+//! UTF-8 in names, codes, keys and reasons, and IDs that may not exist.
+//!
+//! No call may panic, a rejected call must leave the ledger as it was, and
+//! after every call the ledger must keep the invariants of
+//! docs/accounting_spec.md. The reports, exports and the trace are built from
+//! the final ledger so that they see the same odd data.
+#![no_main]
+
+use accounting_spec::application::approval::CorrectionWorkflow;
+use accounting_spec::application::checklist::CloseChecklist;
+use accounting_spec::application::export::{ExportFormat, ExportScope};
+use accounting_spec::application::render::{RenderFormat, ToTable};
+use accounting_spec::application::report::{IntegrityRule, Interval, Structure};
+use accounting_spec::application::service::AccountingService;
+use accounting_spec::domain::entity::{
+    CorrectionRequest, SalesType, Section, SectionType, Term, TermStatus,
+};
+use accounting_spec::domain::provider::SystemClock;
+use accounting_spec::domain::value_object::{
+    ActorId, AuditInfo, IdempotencyKey, Money, SectionCode,
+};
+use accounting_spec::infrastructure::in_memory::{
+    InMemoryAuditLogRepository, InMemoryPendingCorrectionRepository, InMemorySalesRepository,
+    InMemorySectionRepository, InMemoryTermRepository,
+};
+use arbitrary::Arbitrary;
+use chrono::{Days, NaiveDate, NaiveDateTime, NaiveTime};
+use libfuzzer_sys::fuzz_target;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+type Service = AccountingService<
+    InMemorySectionRepository,
+    InMemoryTermRepository,
+    InMemorySalesRepository,
+    InMemoryAuditLogRepository,
+>;
+
+/// A day within a few months of the start of 2025, so that terms and
+/// postings meet often, or anywhere at all.
+#[derive(Debug, Arbitrary)]
+enum Day {
+    Near(i8),
+    Far(i32),
+}
+
+impl Day {
+    fn date(&self) -> Option<NaiveDate> {
+        match *self {
+            Day::Near(offset) => {
+                let base = NaiveDate::from_ymd_opt(2025, 1, 1)?;
+                let days = Days::new(u64::from(offset.unsigned_abs()));
+                if offset < 0 {
+                    base.checked_sub_days(days)
+                } else {
+                    base.checked_add_days(days)
+                }
+            }
+            Day::Far(days) => NaiveDate::from_num_days_from_ce_opt(days),
+        }
+    }
+}
+
+#[derive(Debug, Arbitrary)]
+struct Moment {
+    day: Day,
+    seconds: u32,
+}
+
+impl Moment {
+    fn at(&self) -> Option<NaiveDateTime> {
+        let time = NaiveTime::from_num_seconds_from_midnight_opt(self.seconds % 86_400, 0)?;
+        Some(self.day.date()?.and_time(time))
+    }
+}
+
+/// Cents, the largest `Decimal` either way, or any `Decimal` by its 96-bit
+/// mantissa, sign and scale.
+#[derive(Debug, Arbitrary)]
+enum Amount {
+    Cents(i32),
+    Extreme {
+        negative: bool,
+    },
+    Raw {
+        lo: u32,
+        mid: u32,
+        hi: u32,
+        negative: bool,
+        scale: u8,
+    },
+}
+
+impl Amount {
+    fn money(&self) -> Option<Money> {
+        let amount = match *self {
+            Amount::Cents(cents) => Decimal::new(i64::from(cents), 2),
+            Amount::Extreme { negative: false } => Decimal::MAX,
+            Amount::Extreme { negative: true } => Decimal::MIN,
+            Amount::Raw {
+                lo,
+                mid,
+                hi,
+                negative,
+                scale,
+            } => Decimal::from_parts(lo, mid, hi, negative, u32::from(scale) % 29),
+        };
+        Some(Money::new(amount))
+    }
+}
+
+/// Indices pick an ID the service returned, or an unknown one past the end.
+#[derive(Debug, Arbitrary)]
+enum Op {
+    CreateSection {
+        name: String,
+        code: Option<String>,
+        division: bool,
+        parent: Option<u8>,
+        key: Option<String>,
+    },
+    CreateTerm {
+        start: Day,
+        end: Day,
+        key: Option<String>,
+    },
+    Register {
+        section: u8,
+        amount: Amount,
+        at: Moment,
+        key: Option<String>,
+    },
+    Transfer {
+        sale: u8,
+        target: u8,
+        at: Moment,
+        key: Option<String>,
+    },
+    Close {
+        term: u8,
+        override_reason: Option<String>,
+    },
+    Submit {
+        term: u8,
+        section: u8,
+    },
+    Correct {
+        term: u8,
+        section: u8,
+        original: Amount,
+        correct: Amount,
+        at: Moment,
+    },
+    Rebalance {
+        term: u8,
+        source: u8,
+        target: u8,
+        amount: Amount,
+        at: Moment,
+    },
+    Rename {
+        section: u8,
+        name: String,
+        effective: Day,
+    },
+    Move {
+        section: u8,
+        parent: Option<u8>,
+        effective: Day,
+    },
+    Deactivate {
+        section: u8,
+        effective: Day,
+    },
+    Merge {
+        source: u8,
+        target: u8,
+        effective: Day,
+    },
+}
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    actor: String,
+    reason: Option<String>,
+    ops: Vec<Op>,
+}
+
+/// IDs the service has handed out, to aim operations at.
+#[derive(Default)]
+struct Known {
+    sections: Vec<Uuid>,
+    terms: Vec<Uuid>,
+    sales: Vec<Uuid>,
+}
+
+fn pick(ids: &[Uuid], index: u8) -> Uuid {
+    ids.get(usize::from(index) % (ids.len() + 1))
+        .copied()
+        .unwrap_or_else(Uuid::nil)
+}
+
+fn key(key: &Option<String>) -> Option<IdempotencyKey> {
+    key.clone().and_then(|k| IdempotencyKey::new(k).ok())
+}
+
+/// Everything the service has written, comparable across calls.
+#[derive(Debug, PartialEq)]
+struct Ledger {
+    sections: String,
+    terms: String,
+    statuses: HashMap<Uuid, TermStatus>,
+    entries: HashMap<Uuid, (Uuid, Decimal, SalesType)>,
+    audit_records: usize,
+}
+
+fn capture(service: &Service) -> Ledger {
+    let mut sections = service.list_sections();
+    sections.sort_by_key(|s| s.id);
+    let mut terms = service.list_terms();
+    terms.sort_by_key(|t| t.id);
+    let entries = terms
+        .iter()
+        .flat_map(|t| service.list_sales(&t.id))
+        .map(|s| (s.id, (s.term_id, s.amount.amount(), s.sales_type)))
+        .collect();
+    Ledger {
+        sections: serde_json::to_string(&sections).expect("sections serialize"),
+        terms: serde_json::to_string(&terms).expect("terms serialize"),
+        statuses: terms.iter().map(|t| (t.id, t.status.clone())).collect(),
+        entries,
+        audit_records: service.audit_log().query(&Default::default()).len(),
+    }
+}
+
+/// Integrity rules the service must keep whatever it is asked to do. Posting
+/// to a section that later gains children is allowed, so `LeafPosting` is not
+/// among them.
+const KEPT_RULES: [IntegrityRule; 4] = [
+    IntegrityRule::TransferBalances,
+    IntegrityRule::AdjustmentReference,
+    IntegrityRule::DateInTerm,
+    IntegrityRule::SectionExists,
+];
+
+fn check(service: &Service, before: &Ledger, after: &Ledger, accepted: bool, op: &Op) {
+    if !accepted {
+        assert_eq!(before, after, "rejected {:?} wrote", op);
+        return;
+    }
+    for (id, entry) in &before.entries {
+        assert_eq!(
+            after.entries.get(id),
+            Some(entry),
+            "{:?} changed an entry",
+            op
+        );
+    }
+    for (id, status) in &before.statuses {
+        if *status == TermStatus::Closed {
+            assert_eq!(after.statuses[id], TermStatus::Closed, "{:?} reopened", op);
+        }
+    }
+    for (id, (term_id, _, sales_type)) in &after.entries {
+        if !before.entries.contains_key(id) && *sales_type == SalesType::Normal {
+            assert_eq!(
+                before.statuses.get(term_id),
+                Some(&TermStatus::Open),
+                "{:?} registered into a closed term",
+                op
+            );
+        }
+    }
+    // Only postable amounts reach the ledger, so every sum a report takes
+    // stays on the four-place grid and is exact
+    for (id, (_, amount, _)) in &after.entries {
+        if !before.entries.contains_key(id) {
+            assert!(
+                amount.normalize().scale() <= Money::MAX_SCALE,
+                "{:?} posted {} past the entry scale",
+                op,
+                amount
+            );
+        }
+    }
+    for term_id in after.statuses.keys() {
+        let adjustments: Decimal = after
+            .entries
+            .values()
+            .filter(|(t, _, sales_type)| t == term_id && *sales_type == SalesType::Adjustment)
+            .map(|(_, amount, _)| *amount)
+            .sum();
+        assert!(
+            adjustments.is_zero(),
+            "{:?} moved money out of thin air",
+            op
+        );
+
+        let report = service
+            .reports()
+            .term_integrity(*term_id)
+            .expect("known term has a report");
+        let broken: Vec<_> = report
+            .violations
+            .iter()
+            .filter(|v| KEPT_RULES.contains(&v.rule))
+            .collect();
+        assert!(broken.is_empty(), "{:?} broke {:?}", op, broken);
+    }
+}
+
+/// Files `request` as the audit's actor and approves it as another, the only
+/// way corrections reach the ledger.
+fn approved(service: &mut Service, request: CorrectionRequest, audit: &AuditInfo) -> bool {
+    let mut workflow = CorrectionWorkflow::new(InMemoryPendingCorrectionRepository::new());
+    let Ok(checker) = ActorId::new("checker".to_string()) else {
+        return false;
+    };
+    let maker = audit.actor.clone();
+    let reason = audit.reason.clone().unwrap_or_default();
+    workflow
+        .draft(service, request, maker.clone(), reason)
+        .and_then(|id| workflow.submit(id, &maker).map(|_| id))
+        .and_then(|id| workflow.approve(service, id, checker, "fuzz"))
+        .is_ok()
+}
+
+/// Applies `op`, returning whether the service accepted it. Ops whose inputs
+/// do not make a date or an amount are skipped with `None`.
+fn apply(service: &mut Service, known: &mut Known, op: &Op, audit: &AuditInfo) -> Option<bool> {
+    let audit = audit.clone();
+    let accepted = match op {
+        Op::CreateSection {
+            name,
+            code,
+            division,
+            parent,
+            key: k,
+        } => {
+            let section_type = if *division {
+                SectionType::Division
+            } else {
+                SectionType::Section
+            };
+            let parent = parent.map(|p| pick(&known.sections, p));
+            let mut section =
+                match Section::new(service.ids().next_id(), name.clone(), section_type, parent) {
+                    Ok(section) => section,
+                    Err(_) => return Some(false),
+                };
+            if let Some(code) = code.clone().and_then(|c| SectionCode::new(c).ok()) {
+                section = section.with_code(code);
+            }
+            service
+                .create_section(section, key(k), audit)
+                .map(|id| known.sections.push(id))
+                .is_ok()
+        }
+        Op::CreateTerm { start, end, key: k } => {
+            let term = match Term::new(service.ids().next_id(), start.date()?, end.date()?) {
+                Ok(term) => term,
+                Err(_) => return Some(false),
+            };
+            service
+                .create_term(term, key(k), audit)
+                .map(|id| known.terms.push(id))
+                .is_ok()
+        }
+        Op::Register {
+            section,
+            amount,
+            at,
+            key: k,
+        } => service
+            .register_sales(
+                amount.money()?,
+                at.at()?,
+                pick(&known.sections, *section),
+                key(k),
+                audit,
+            )
+            .map(|id| known.sales.push(id))
+            .is_ok(),
+        Op::Transfer {
+            sale,
+            target,
+            at,
+            key: k,
+        } => service
+            .transform_sales(
+                pick(&known.sales, *sale),
+                pick(&known.sections, *target),
+                at.at()?,
+                key(k),
+                audit,
+            )
+            .map(|id| known.sales.push(id))
+            .is_ok(),
+        Op::Close {
+            term,
+            override_reason,
+        } => service
+            .close_term(pick(&known.terms, *term), override_reason.clone(), audit)
+            .is_ok(),
+        Op::Submit { term, section } => service
+            .submit_section(
+                pick(&known.terms, *term),
+                pick(&known.sections, *section),
+                audit,
+            )
+            .is_ok(),
+        Op::Correct {
+            term,
+            section,
+            original,
+            correct,
+            at,
+        } => {
+            let request = CorrectionRequest::Correct {
+                term_id: pick(&known.terms, *term),
+                section_id: pick(&known.sections, *section),
+                original_amount: original.money()?,
+                correct_amount: correct.money()?,
+                date: at.at()?,
+            };
+            approved(service, request, &audit)
+        }
+        Op::Rebalance {
+            term,
+            source,
+            target,
+            amount,
+            at,
+        } => {
+            let request = CorrectionRequest::Rebalance {
+                term_id: pick(&known.terms, *term),
+                source_section_id: pick(&known.sections, *source),
+                target_section_id: pick(&known.sections, *target),
+                amount: amount.money()?,
+                date: at.at()?,
+            };
+            approved(service, request, &audit)
+        }
+        Op::Rename {
+            section,
+            name,
+            effective,
+        } => service
+            .rename_section(
+                pick(&known.sections, *section),
+                name.clone(),
+                effective.date()?,
+                audit,
+            )
+            .is_ok(),
+        Op::Move {
+            section,
+            parent,
+            effective,
+        } => service
+            .move_section(
+                pick(&known.sections, *section),
+                parent.map(|p| pick(&known.sections, p)),
+                effective.date()?,
+                audit,
+            )
+            .is_ok(),
+        Op::Deactivate { section, effective } => service
+            .deactivate_section(pick(&known.sections, *section), effective.date()?, audit)
+            .is_ok(),
+        Op::Merge {
+            source,
+            target,
+            effective,
+        } => service
+            .merge_sections(
+                pick(&known.sections, *source),
+                pick(&known.sections, *target),
+                effective.date()?,
+                audit,
+            )
+            .is_ok(),
+    };
+    Some(accepted)
+}
+
+/// Builds every report over the final ledger; only panics matter here.
+fn exercise_reports(service: &Service, known: &Known) {
+    let reports = service.reports();
+    let mut tables = Vec::new();
+    for (index, term_id) in known.terms.iter().enumerate() {
+        if let Ok(summary) = reports.term_summary(*term_id) {
+            tables.push(summary.to_table());
+        }
+        for structure in [Structure::AsOf, Structure::Restated] {
+            if let Ok(rollup) = reports.term_rollup(*term_id, structure) {
+                tables.push(rollup.to_table());
+            }
+        }
+        if let Ok(movement) = reports.term_movement(*term_id) {
+            tables.push(movement.to_table());
+        }
+        if let Ok(integrity) = reports.term_integrity(*term_id) {
+            tables.push(integrity.to_table());
+        }
+        if let Some(base_id) = index.checked_sub(1).map(|i| known.terms[i]) {
+            if let Ok(comparison) = reports.term_comparison(base_id, *term_id) {
+                tables.push(comparison.to_table());
+            }
+        }
+        // A series has a point per bucket, so keep to terms of sane length
+        let short = service
+            .find_term(term_id)
+            .is_some_and(|t| (t.end_date - t.start_date).num_days() < 3_660);
+        if short {
+            for interval in [Interval::Day, Interval::Week, Interval::Month] {
+                if let Ok(series) =
+                    reports.sales_series(ExportScope::Term(*term_id), None, false, interval)
+                {
+                    tables.push(series.to_table());
+                }
+            }
+        }
+        for format in [ExportFormat::Csv, ExportFormat::Json] {
+            let _ = service
+                .exporter()
+                .export(ExportScope::Term(*term_id), format, std::io::sink());
+        }
+    }
+    for format in [
+        RenderFormat::Text,
+        RenderFormat::Markdown,
+        RenderFormat::Html,
+    ] {
+        let renderer = format.renderer();
+        for table in &tables {
+            renderer.render(table);
+        }
+    }
+    for sales_id in &known.sales {
+        if let Ok(lineage) = service.lineage().lineage(*sales_id) {
+            lineage.to_dot();
+        }
+    }
+    for section_id in &known.sections {
+        let _ = service.hierarchy().path(*section_id);
+    }
+    if let Some(trace) = service.trace() {
+        trace
+            .write_tla("Trace", std::io::sink())
+            .expect("writing to a sink cannot fail");
+    }
+}
+
+fuzz_target!(|input: Input| {
+    let Ok(actor) = ActorId::new(input.actor) else {
+        return;
+    };
+    let audit = AuditInfo::new(actor, "fuzz", &SystemClock).with_reason(input.reason);
+    let mut service = AccountingService::new(
+        InMemorySectionRepository::new(),
+        InMemoryTermRepository::new(),
+        InMemorySalesRepository::new(),
+        InMemoryAuditLogRepository::new(),
+    )
+    .with_close_checklist(CloseChecklist::standard())
+    .with_trace();
+    let mut known = Known::default();
+
+    let mut before = capture(&service);
+    for op in &input.ops {
+        let Some(accepted) = apply(&mut service, &mut known, op, &audit) else {
+            continue;
+        };
+        let after = capture(&service);
+        check(&service, &before, &after, accepted, op);
+        before = after;
+    }
+    exercise_reports(&service, &known);
+});
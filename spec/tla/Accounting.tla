@@ -0,0 +1,265 @@
+----------------------------- MODULE Accounting -----------------------------
+(***************************************************************************)
+(* The accounting actions of AccountingService over sections, terms and    *)
+(* the sales ledger. Names, codes, idempotency keys, audit details, access *)
+(* control and the close checklist are left out, as is the dated history  *)
+(* of names and parents. Dates are integers that order like the dates they *)
+(* stand for, and None stands for a missing parent, date or reference.     *)
+(***************************************************************************)
+EXTENDS Integers, FiniteSets
+
+CONSTANTS SectionIds, TermIds, EntryIds, Amounts, Dates
+
+None == 0
+
+VARIABLES sections, terms, entries
+
+vars == <<sections, terms, entries>>
+
+EntryTypes == {"Normal", "Adjustment", "Correction"}
+
+Entry(id, t, s, amount, type, related, d) ==
+    [id |-> id, term |-> t, section |-> s, amount |-> amount,
+     type |-> type, related |-> related, date |-> d]
+
+RECURSIVE SumOf(_)
+SumOf(es) ==
+    IF es = {} THEN 0
+    ELSE LET e == CHOOSE x \in es : TRUE
+         IN e.amount + SumOf(es \ {e})
+
+Extend(f, k, v) == [x \in DOMAIN f \cup {k} |-> IF x = k THEN v ELSE f[x]]
+
+\* The parents of s up to the root; `seen` ends the walk on a cycle
+RECURSIVE AncestorsFrom(_, _)
+AncestorsFrom(s, seen) ==
+    LET p == sections[s].parent
+    IN IF p = None \/ p \in seen \/ p \notin DOMAIN sections
+       THEN seen
+       ELSE AncestorsFrom(p, seen \cup {p})
+
+Ancestors(s) == AncestorsFrom(s, {})
+
+Active(s, d) == sections[s].deactivated = None \/ d < sections[s].deactivated
+
+InTerm(t, d) == terms[t].start <= d /\ d <= terms[t].end
+
+OpenTerms == {t \in DOMAIN terms : terms[t].status = "Open"}
+
+UsedIds == {e.id : e \in entries}
+
+Fresh(a, b) == a # b /\ {a, b} \cap UsedIds = {}
+
+Balance(s, t) == SumOf({e \in entries : e.section = s /\ e.term = t})
+
+\* No active children and nothing posted on or after d
+CanDeactivate(s, d) ==
+    /\ \A c \in DOMAIN sections : sections[c].parent = s => ~Active(c, d)
+    /\ \A e \in entries : e.section = s => e.date < d
+
+-----------------------------------------------------------------------------
+(* Actions, one per mutating operation of AccountingService. Requests the  *)
+(* service replays write nothing and are stuttering steps.                  *)
+
+CreateSection(s, p) ==
+    /\ s \notin DOMAIN sections
+    /\ p = None \/ p \in DOMAIN sections
+    /\ sections' = Extend(sections, s, [parent |-> p, deactivated |-> None, merged |-> None])
+    /\ UNCHANGED <<terms, entries>>
+
+CreateTerm(t, first, last) ==
+    /\ t \notin DOMAIN terms
+    /\ first <= last
+    /\ terms' = Extend(terms, t, [start |-> first, end |-> last, status |-> "Open", submitted |-> {}])
+    /\ UNCHANGED <<sections, entries>>
+
+RegisterSales(i, s, t, amount, d) ==
+    /\ i \notin UsedIds
+    /\ s \in DOMAIN sections
+    /\ Active(s, d)
+    /\ t \in OpenTerms
+    /\ InTerm(t, d)
+    /\ amount # 0
+    /\ entries' = entries \cup {Entry(i, t, s, amount, "Normal", None, d)}
+    /\ UNCHANGED <<sections, terms>>
+
+\* Moves sale o to target with a pair of Adjustment entries referring to it
+TransferSales(outId, inId, o, target, d) ==
+    /\ Fresh(outId, inId)
+    /\ target \in DOMAIN sections
+    /\ \E sale \in entries :
+          /\ sale.id = o
+          /\ target # sale.section
+          /\ Active(sale.section, d)
+          /\ Active(target, d)
+          /\ InTerm(sale.term, d)
+          /\ entries' = entries \cup
+                {Entry(outId, sale.term, sale.section, -sale.amount, "Adjustment", o, d),
+                 Entry(inId, sale.term, target, sale.amount, "Adjustment", o, d)}
+    /\ UNCHANGED <<sections, terms>>
+
+\* Closing a closed term writes it again unchanged
+CloseTerm(t) ==
+    /\ t \in DOMAIN terms
+    /\ terms' = [terms EXCEPT ![t].status = "Closed"]
+    /\ UNCHANGED <<sections, entries>>
+
+SubmitSection(t, s) ==
+    /\ t \in OpenTerms
+    /\ s \in DOMAIN sections
+    /\ s \notin terms[t].submitted
+    /\ terms' = [terms EXCEPT ![t].submitted = @ \cup {s}]
+    /\ UNCHANGED <<sections, entries>>
+
+\* Allowed in closed terms too
+CorrectTerm(r, c, t, s, original, correct, d) ==
+    /\ Fresh(r, c)
+    /\ t \in DOMAIN terms
+    /\ InTerm(t, d)
+    /\ s \in DOMAIN sections
+    /\ Active(s, d)
+    /\ entries' = entries \cup
+          {Entry(r, t, s, -original, "Correction", None, d),
+           Entry(c, t, s, correct, "Correction", None, d)}
+    /\ UNCHANGED <<sections, terms>>
+
+RebalanceTerm(outId, inId, t, source, target, amount, d) ==
+    /\ Fresh(outId, inId)
+    /\ amount > 0
+    /\ t \in DOMAIN terms
+    /\ InTerm(t, d)
+    /\ source \in DOMAIN sections
+    /\ target \in DOMAIN sections
+    /\ source # target
+    /\ Active(source, d)
+    /\ Active(target, d)
+    /\ entries' = entries \cup
+          {Entry(outId, t, source, -amount, "Correction", None, d),
+           Entry(inId, t, target, amount, "Correction", None, d)}
+    /\ UNCHANGED <<sections, terms>>
+
+\* Names are not modelled, so a rename changes nothing here
+RenameSection(s) ==
+    /\ s \in DOMAIN sections
+    /\ UNCHANGED vars
+
+MoveSection(s, p) ==
+    /\ s \in DOMAIN sections
+    /\ p # sections[s].parent
+    /\ p = None \/ (p \in DOMAIN sections /\ p # s /\ s \notin Ancestors(p))
+    /\ sections' = [sections EXCEPT ![s].parent = p]
+    /\ UNCHANGED <<terms, entries>>
+
+DeactivateSection(s, d) ==
+    /\ s \in DOMAIN sections
+    /\ sections[s].deactivated = None
+    /\ CanDeactivate(s, d)
+    /\ sections' = [sections EXCEPT ![s].deactivated = d]
+    /\ UNCHANGED <<terms, entries>>
+
+\* Deactivates source and moves its balance in an open term to target
+MergeSections(source, target, d, outId, inId) ==
+    /\ source \in DOMAIN sections
+    /\ target \in DOMAIN sections
+    /\ source # target
+    /\ source \notin Ancestors(target)
+    /\ sections[source].deactivated = None
+    /\ Active(target, d)
+    /\ CanDeactivate(source, d)
+    /\ sections' = [sections EXCEPT ![source].deactivated = d, ![source].merged = target]
+    /\ \/ /\ \A t \in OpenTerms : Balance(source, t) = 0
+          /\ UNCHANGED entries
+       \/ \E t \in OpenTerms :
+             /\ Balance(source, t) # 0
+             /\ InTerm(t, d)
+             /\ Fresh(outId, inId)
+             /\ entries' = entries \cup
+                   {Entry(outId, t, source, -Balance(source, t), "Adjustment", None, d),
+                    Entry(inId, t, target, Balance(source, t), "Adjustment", None, d)}
+    /\ UNCHANGED terms
+
+-----------------------------------------------------------------------------
+
+Init ==
+    /\ sections = <<>>
+    /\ terms = <<>>
+    /\ entries = {}
+
+Next ==
+    \/ \E s \in SectionIds, p \in SectionIds \cup {None} : CreateSection(s, p)
+    \/ \E t \in TermIds, a, b \in Dates : CreateTerm(t, a, b)
+    \/ \E i \in EntryIds, s \in SectionIds, t \in TermIds, a \in Amounts, d \in Dates :
+          RegisterSales(i, s, t, a, d)
+    \/ \E i, j, o \in EntryIds, s \in SectionIds, d \in Dates : TransferSales(i, j, o, s, d)
+    \/ \E t \in TermIds : CloseTerm(t)
+    \/ \E t \in TermIds, s \in SectionIds : SubmitSection(t, s)
+    \/ \E i, j \in EntryIds, t \in TermIds, s \in SectionIds, a, b \in Amounts, d \in Dates :
+          CorrectTerm(i, j, t, s, a, b, d)
+    \/ \E i, j \in EntryIds, t \in TermIds, s, u \in SectionIds, a \in Amounts, d \in Dates :
+          RebalanceTerm(i, j, t, s, u, a, d)
+    \/ \E s \in SectionIds, p \in SectionIds \cup {None} : MoveSection(s, p)
+    \/ \E s \in SectionIds, d \in Dates : DeactivateSection(s, d)
+    \/ \E s, u \in SectionIds, d \in Dates, i, j \in EntryIds : MergeSections(s, u, d, i, j)
+
+Spec == Init /\ [][Next]_vars
+
+-----------------------------------------------------------------------------
+(* Invariants                                                               *)
+
+TypeOK ==
+    /\ DOMAIN sections \subseteq SectionIds
+    /\ DOMAIN terms \subseteq TermIds
+    /\ \A s \in DOMAIN sections :
+          /\ sections[s].parent \in DOMAIN sections \cup {None}
+          /\ sections[s].deactivated \in Dates \cup {None}
+          /\ sections[s].merged \in DOMAIN sections \cup {None}
+    /\ \A t \in DOMAIN terms :
+          /\ terms[t].status \in {"Open", "Closed"}
+          /\ terms[t].submitted \subseteq DOMAIN sections
+    /\ \A e \in entries :
+          /\ e.id \in EntryIds
+          /\ e.type \in EntryTypes
+          /\ e.term \in DOMAIN terms
+          /\ e.section \in DOMAIN sections
+    /\ Cardinality(UsedIds) = Cardinality(entries)
+
+\* A section is never its own ancestor
+NoCycles == \A s \in DOMAIN sections : s \notin Ancestors(s)
+
+TermsOrdered == \A t \in DOMAIN terms : terms[t].start <= terms[t].end
+
+DatesInTerm == \A e \in entries : InTerm(e.term, e.date)
+
+\* The legs of each transfer of a sale on a date net to zero
+TransfersBalance ==
+    \A e \in entries :
+        e.type = "Adjustment" /\ e.related # None =>
+            LET legs == {x \in entries : x.related = e.related /\ x.date = e.date}
+            IN SumOf({x \in legs : x.type = "Adjustment"}) = 0
+
+\* Transfers and merges move money within a term without changing its total
+AdjustmentsNetZero ==
+    \A t \in DOMAIN terms : SumOf({e \in entries : e.term = t /\ e.type = "Adjustment"}) = 0
+
+-----------------------------------------------------------------------------
+(* Properties of every step                                                 *)
+
+EntriesAppendOnly == [][entries \subseteq entries']_vars
+
+ClosedStaysClosed ==
+    [][\A t \in DOMAIN terms : terms[t].status = "Closed" => terms'[t].status = "Closed"]_vars
+
+NormalOnlyWhileOpen ==
+    [][\A e \in entries' \ entries : e.type = "Normal" => terms[e.term].status = "Open"]_vars
+
+\* Conservation of money: a step writing only Adjustments moves money, and
+\* only registrations and corrections change the ledger total
+MovesConserve ==
+    [][LET written == entries' \ entries
+       IN (\A e \in written : e.type = "Adjustment") => SumOf(written) = 0]_vars
+
+MoneyFromRegistrationsAndCorrections ==
+    [][LET written == entries' \ entries
+       IN SumOf(written) # 0 => \E e \in written : e.type \in {"Normal", "Correction"}]_vars
+
+=============================================================================
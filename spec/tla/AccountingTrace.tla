@@ -0,0 +1,94 @@
+-------------------------- MODULE AccountingTrace --------------------------
+(***************************************************************************)
+(* Replays a trace the service wrote (`--trace Trace.tla` on the command   *)
+(* line, or TraceLog::write_tla) through the actions of Accounting. Each   *)
+(* logged step must be a step of the specification that ends in the logged *)
+(* state; TLC reports a deadlock at the first one that is not, with `step` *)
+(* giving its position in TraceLog.                                         *)
+(***************************************************************************)
+EXTENDS Accounting, Trace, Sequences
+
+VARIABLE step
+
+Log == TraceLog[step]
+
+After == Log.state
+
+Subject == Log.subject
+
+Written == After.entries \ entries
+
+\* The parameters of each action, read from the logged step
+
+ReplayCreateSection == CreateSection(Subject, After.sections[Subject].parent)
+
+ReplayCreateTerm == CreateTerm(Subject, After.terms[Subject].start, After.terms[Subject].end)
+
+ReplayRegisterSales ==
+    \E e \in Written : RegisterSales(e.id, e.section, e.term, e.amount, e.date)
+
+\* The service returns the leg posted to the target
+ReplayTransferSales ==
+    \E out \in Written, leg \in Written :
+        /\ leg.id = Subject
+        /\ out.id # Subject
+        /\ TransferSales(out.id, leg.id, leg.related, leg.section, leg.date)
+
+ReplaySubmitSection ==
+    \E s \in After.terms[Subject].submitted : SubmitSection(Subject, s)
+
+ReplayCorrectTerm ==
+    \E r, c \in Written : CorrectTerm(r.id, c.id, Subject, r.section, -r.amount, c.amount, r.date)
+
+ReplayRebalanceTerm ==
+    \E out, leg \in Written :
+        RebalanceTerm(out.id, leg.id, Subject, out.section, leg.section, leg.amount, out.date)
+
+ReplayMoveSection == MoveSection(Subject, After.sections[Subject].parent)
+
+ReplayDeactivateSection == DeactivateSection(Subject, After.sections[Subject].deactivated)
+
+ReplayMergeSections ==
+    LET source == After.sections[Subject]
+    IN \/ /\ Written = {}
+          /\ MergeSections(Subject, source.merged, source.deactivated, None, None)
+       \/ \E out, leg \in Written :
+             /\ out.section = Subject
+             /\ MergeSections(Subject, source.merged, source.deactivated, out.id, leg.id)
+
+Replay ==
+    \/ Log.action = "CreateSection" /\ ReplayCreateSection
+    \/ Log.action = "CreateTerm" /\ ReplayCreateTerm
+    \/ Log.action = "RegisterSales" /\ ReplayRegisterSales
+    \/ Log.action = "TransferSales" /\ ReplayTransferSales
+    \/ Log.action = "CloseTerm" /\ CloseTerm(Subject)
+    \/ Log.action = "SubmitSection" /\ ReplaySubmitSection
+    \/ Log.action = "CorrectTerm" /\ ReplayCorrectTerm
+    \/ Log.action = "RebalanceTerm" /\ ReplayRebalanceTerm
+    \/ Log.action = "RenameSection" /\ RenameSection(Subject)
+    \/ Log.action = "MoveSection" /\ ReplayMoveSection
+    \/ Log.action = "DeactivateSection" /\ ReplayDeactivateSection
+    \/ Log.action = "MergeSections" /\ ReplayMergeSections
+
+TraceInitState ==
+    /\ sections = TraceInit.sections
+    /\ terms = TraceInit.terms
+    /\ entries = TraceInit.entries
+    /\ step = 1
+
+TraceStep ==
+    /\ step <= Len(TraceLog)
+    /\ Replay
+    /\ sections' = After.sections
+    /\ terms' = After.terms
+    /\ entries' = After.entries
+    /\ step' = step + 1
+
+\* Stays in the last state once the whole trace is matched
+TraceDone ==
+    /\ step > Len(TraceLog)
+    /\ UNCHANGED <<vars, step>>
+
+TraceSpec == TraceInitState /\ [][TraceStep \/ TraceDone]_<<vars, step>>
+
+=============================================================================
@@ -0,0 +1,549 @@
+use super::preview::Preview;
+use super::service::{AccountingService, ServiceError};
+use crate::domain::entity::{CorrectionRequest, CorrectionStatus, PendingCorrection};
+use crate::domain::repository::{
+    AuditLogRepository, PendingCorrectionRepository, SalesRepository, SectionRepository,
+    TermRepository,
+};
+use crate::domain::value_object::{ActorId, AuditInfo, IdempotencyKey};
+use uuid::Uuid;
+
+/// Maker-checker workflow for `correct_term` and `rebalance_term`. Requests
+/// are held as `PendingCorrection`s and only written to the ledger when a
+/// checker other than the maker approves them.
+pub struct CorrectionWorkflow<P>
+where
+    P: PendingCorrectionRepository,
+{
+    correction_repo: P,
+}
+
+impl<P> CorrectionWorkflow<P>
+where
+    P: PendingCorrectionRepository,
+{
+    pub fn new(correction_repo: P) -> Self {
+        Self { correction_repo }
+    }
+
+    pub fn find(&self, id: &Uuid) -> Option<PendingCorrection> {
+        self.correction_repo.find_by_id(id)
+    }
+
+    pub fn list(&self, status: Option<CorrectionStatus>) -> Vec<PendingCorrection> {
+        match status {
+            Some(status) => self.correction_repo.find_by_status(&status),
+            None => self.correction_repo.list_all(),
+        }
+    }
+
+    /// Records a draft after checking the request against the current ledger.
+    /// Its ID comes from the service's ID generator.
+    pub fn draft<S, T, L, A>(
+        &mut self,
+        service: &AccountingService<S, T, L, A>,
+        request: CorrectionRequest,
+        maker: ActorId,
+        reason: String,
+    ) -> Result<Uuid, ServiceError>
+    where
+        S: SectionRepository,
+        T: TermRepository,
+        L: SalesRepository,
+        A: AuditLogRepository,
+    {
+        self.draft_against(service, service, request, maker, reason)
+    }
+
+    /// Like `draft`, but checks the request against `ledger`, such as a copy
+    /// with earlier requests already applied. `service` still supplies the ID
+    /// and the maker's permissions.
+    pub fn draft_against<S, T, L, A, LS, LT, LL, LA>(
+        &mut self,
+        service: &AccountingService<S, T, L, A>,
+        ledger: &AccountingService<LS, LT, LL, LA>,
+        request: CorrectionRequest,
+        maker: ActorId,
+        reason: String,
+    ) -> Result<Uuid, ServiceError>
+    where
+        S: SectionRepository,
+        T: TermRepository,
+        L: SalesRepository,
+        A: AuditLogRepository,
+        LS: SectionRepository,
+        LT: TermRepository,
+        LL: SalesRepository,
+        LA: AuditLogRepository,
+    {
+        let correction = PendingCorrection::new(service.ids().next_id(), request, maker, reason)?;
+        if self.correction_repo.find_by_id(&correction.id).is_some() {
+            return Err(format!("Pending correction {} already exists", correction.id).into());
+        }
+        service.authorize_request(&correction.request, &correction.maker)?;
+        preview_request(
+            ledger,
+            &correction.request,
+            None,
+            entry_audit(service, &correction, correction.maker.clone(), "approval"),
+        )?;
+        let id = correction.id;
+        self.correction_repo.save(correction)?;
+        Ok(id)
+    }
+
+    /// The entries a request would write if it were drafted and approved
+    /// against the current ledger; records nothing.
+    pub fn preview_draft<S, T, L, A>(
+        &self,
+        service: &AccountingService<S, T, L, A>,
+        request: &CorrectionRequest,
+        maker: ActorId,
+        reason: String,
+    ) -> Result<Preview, ServiceError>
+    where
+        S: SectionRepository,
+        T: TermRepository,
+        L: SalesRepository,
+        A: AuditLogRepository,
+    {
+        let correction =
+            PendingCorrection::new(service.ids().next_id(), request.clone(), maker, reason)?;
+        preview_request(
+            service,
+            &correction.request,
+            None,
+            entry_audit(service, &correction, correction.maker.clone(), "approval"),
+        )
+    }
+
+    /// Submits a draft for approval. The maker needs the role approval takes
+    /// on the request's sections, so a submission can always be decided.
+    pub fn submit<S, T, L, A>(
+        &mut self,
+        service: &AccountingService<S, T, L, A>,
+        id: Uuid,
+        actor: &ActorId,
+    ) -> Result<(), ServiceError>
+    where
+        S: SectionRepository,
+        T: TermRepository,
+        L: SalesRepository,
+        A: AuditLogRepository,
+    {
+        let mut correction = self.load(&id)?;
+        service.authorize_request(&correction.request, actor)?;
+        correction.submit(actor)?;
+        Ok(self.correction_repo.save(correction)?)
+    }
+
+    /// The entries approval would write and the resulting balance changes.
+    pub fn preview<S, T, L, A>(
+        &self,
+        service: &AccountingService<S, T, L, A>,
+        id: Uuid,
+    ) -> Result<Preview, ServiceError>
+    where
+        S: SectionRepository,
+        T: TermRepository,
+        L: SalesRepository,
+        A: AuditLogRepository,
+    {
+        let correction = self.load(&id)?;
+        let audit = entry_audit(service, &correction, correction.maker.clone(), "approval");
+        preview_request(
+            service,
+            &correction.request,
+            Some(approval_key(&id)?),
+            audit,
+        )
+    }
+
+    /// Approves a submitted correction and writes its entries. The entries
+    /// carry an idempotency key derived from the correction ID, so retrying an
+    /// approval whose status update failed does not post them twice. Their
+    /// audit info names the checker, the maker's reason and, as correlation
+    /// ID, the pending correction.
+    pub fn approve<S, T, L, A>(
+        &mut self,
+        service: &mut AccountingService<S, T, L, A>,
+        id: Uuid,
+        checker: ActorId,
+        source: &str,
+    ) -> Result<(), ServiceError>
+    where
+        S: SectionRepository,
+        T: TermRepository,
+        L: SalesRepository,
+        A: AuditLogRepository,
+    {
+        let mut correction = self.load(&id)?;
+        correction.approve(checker.clone())?;
+
+        let key = Some(approval_key(&id)?);
+        let audit = entry_audit(service, &correction, checker, source);
+        match correction.request.clone() {
+            CorrectionRequest::Correct {
+                term_id,
+                section_id,
+                original_amount,
+                correct_amount,
+                date,
+            } => service.correct_term(
+                term_id,
+                section_id,
+                original_amount,
+                correct_amount,
+                date,
+                key,
+                audit,
+            )?,
+            CorrectionRequest::Rebalance {
+                term_id,
+                source_section_id,
+                target_section_id,
+                amount,
+                date,
+            } => service.rebalance_term(
+                term_id,
+                source_section_id,
+                target_section_id,
+                amount,
+                date,
+                key,
+                audit,
+            )?,
+        }
+
+        Ok(self.correction_repo.save(correction)?)
+    }
+
+    /// Rejects a submitted correction. The checker is authorized as for
+    /// approval, so only someone who could post the request may turn it down.
+    pub fn reject<S, T, L, A>(
+        &mut self,
+        service: &AccountingService<S, T, L, A>,
+        id: Uuid,
+        checker: ActorId,
+        reason: String,
+    ) -> Result<(), ServiceError>
+    where
+        S: SectionRepository,
+        T: TermRepository,
+        L: SalesRepository,
+        A: AuditLogRepository,
+    {
+        let mut correction = self.load(&id)?;
+        service.authorize_request(&correction.request, &checker)?;
+        correction.reject(checker, reason)?;
+        Ok(self.correction_repo.save(correction)?)
+    }
+
+    fn load(&self, id: &Uuid) -> Result<PendingCorrection, ServiceError> {
+        self.correction_repo
+            .find_by_id(id)
+            .ok_or_else(|| ServiceError::NotFound("Pending correction not found".to_string()))
+    }
+}
+
+fn entry_audit<S, T, L, A>(
+    service: &AccountingService<S, T, L, A>,
+    correction: &PendingCorrection,
+    actor: ActorId,
+    source: &str,
+) -> AuditInfo
+where
+    S: SectionRepository,
+    T: TermRepository,
+    L: SalesRepository,
+    A: AuditLogRepository,
+{
+    AuditInfo::new(actor, source, service.clock())
+        .with_reason(Some(correction.reason.clone()))
+        .with_correlation_id(Some(correction.id.to_string()))
+}
+
+fn approval_key(id: &Uuid) -> Result<IdempotencyKey, ServiceError> {
+    Ok(IdempotencyKey::new(format!("correction-{}", id))?)
+}
+
+fn preview_request<S, T, L, A>(
+    service: &AccountingService<S, T, L, A>,
+    request: &CorrectionRequest,
+    key: Option<IdempotencyKey>,
+    audit: AuditInfo,
+) -> Result<Preview, ServiceError>
+where
+    S: SectionRepository,
+    T: TermRepository,
+    L: SalesRepository,
+    A: AuditLogRepository,
+{
+    match request.clone() {
+        CorrectionRequest::Correct {
+            term_id,
+            section_id,
+            original_amount,
+            correct_amount,
+            date,
+        } => service.preview_correct_term(
+            term_id,
+            section_id,
+            original_amount,
+            correct_amount,
+            date,
+            key,
+            audit,
+        ),
+        CorrectionRequest::Rebalance {
+            term_id,
+            source_section_id,
+            target_section_id,
+            amount,
+            date,
+        } => service.preview_rebalance_term(
+            term_id,
+            source_section_id,
+            target_section_id,
+            amount,
+            date,
+            key,
+            audit,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entity::{Section, SectionType, Term};
+    use crate::domain::value_object::Money;
+    use crate::infrastructure::in_memory::InMemoryPendingCorrectionRepository;
+    use crate::test_support::{audit, service};
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_rebalance_written_only_after_approval() {
+        let mut service = service();
+        let mut workflow = CorrectionWorkflow::new(InMemoryPendingCorrectionRepository::new());
+
+        let section_a = Section::new(
+            service.ids().next_id(),
+            "Section A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_a_id = service.create_section(section_a, None, audit()).unwrap();
+        let section_b = Section::new(
+            service.ids().next_id(),
+            "Section B".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_b_id = service.create_section(section_b, None, audit()).unwrap();
+
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+        service.close_term(term_id, None, audit()).unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2025, 12, 31)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        let request = CorrectionRequest::Rebalance {
+            term_id,
+            source_section_id: section_a_id,
+            target_section_id: section_b_id,
+            amount: Money::new(Decimal::from_str("100.00").unwrap()),
+            date,
+        };
+        let maker = ActorId::new("alice".to_string()).unwrap();
+        let checker = ActorId::new("bob".to_string()).unwrap();
+
+        let id = workflow
+            .draft(&service, request, maker.clone(), "Misposted".to_string())
+            .unwrap();
+        // Cannot decide on a draft, and only the maker submits
+        assert!(workflow
+            .approve(&mut service, id, checker.clone(), "test")
+            .is_err());
+        assert!(workflow.submit(&service, id, &checker).is_err());
+        workflow.submit(&service, id, &maker).unwrap();
+        assert!(service.list_sales(&term_id).is_empty());
+
+        let preview = workflow.preview(&service, id).unwrap();
+        assert_eq!(preview.sales.len(), 2);
+
+        // Maker cannot approve their own correction
+        assert!(workflow.approve(&mut service, id, maker, "test").is_err());
+        assert!(service.list_sales(&term_id).is_empty());
+
+        workflow
+            .approve(&mut service, id, checker.clone(), "test")
+            .unwrap();
+        assert_eq!(service.list_sales(&term_id).len(), 2);
+        let approved = workflow.find(&id).unwrap();
+        assert_eq!(approved.status, CorrectionStatus::Approved);
+        assert_eq!(approved.checker, Some(checker.clone()));
+
+        // Decisions are final
+        assert!(workflow.approve(&mut service, id, checker, "test").is_err());
+        assert_eq!(service.list_sales(&term_id).len(), 2);
+    }
+
+    #[test]
+    fn test_rejected_correction_writes_nothing() {
+        let mut service = service();
+        let mut workflow = CorrectionWorkflow::new(InMemoryPendingCorrectionRepository::new());
+
+        let section = Section::new(
+            service.ids().next_id(),
+            "Section A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_id = service.create_section(section, None, audit()).unwrap();
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        let maker = ActorId::new("alice".to_string()).unwrap();
+        let checker = ActorId::new("bob".to_string()).unwrap();
+
+        // Requests that would fail on approval are refused as drafts
+        let bad = CorrectionRequest::Correct {
+            term_id,
+            section_id: Uuid::new_v4(),
+            original_amount: Money::new(Decimal::from_str("100.00").unwrap()),
+            correct_amount: Money::new(Decimal::from_str("150.00").unwrap()),
+            date,
+        };
+        assert!(workflow
+            .draft(&service, bad, maker.clone(), "Misposted".to_string())
+            .is_err());
+
+        let request = CorrectionRequest::Correct {
+            term_id,
+            section_id,
+            original_amount: Money::new(Decimal::from_str("100.00").unwrap()),
+            correct_amount: Money::new(Decimal::from_str("150.00").unwrap()),
+            date,
+        };
+        let id = workflow
+            .draft(&service, request, maker.clone(), "Misposted".to_string())
+            .unwrap();
+        workflow.submit(&service, id, &maker).unwrap();
+        assert!(workflow
+            .reject(&service, id, checker.clone(), "  ".to_string())
+            .is_err());
+        workflow
+            .reject(&service, id, checker, "Wrong section".to_string())
+            .unwrap();
+
+        let rejected = workflow.find(&id).unwrap();
+        assert_eq!(rejected.status, CorrectionStatus::Rejected);
+        assert_eq!(rejected.rejection_reason.as_deref(), Some("Wrong section"));
+        assert!(service.list_sales(&term_id).is_empty());
+        assert_eq!(workflow.list(Some(CorrectionStatus::Rejected)).len(), 1);
+        assert!(workflow.list(Some(CorrectionStatus::Submitted)).is_empty());
+    }
+
+    #[test]
+    fn test_submit_and_reject_need_the_approval_role() {
+        use crate::application::authorization::AccessPolicy;
+        use crate::domain::entity::{Role, RoleGrant};
+
+        let mut service = service();
+        let mut workflow = CorrectionWorkflow::new(InMemoryPendingCorrectionRepository::new());
+        let section = Section::new(
+            service.ids().next_id(),
+            "Section A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_id = service.create_section(section, None, audit()).unwrap();
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+
+        let actor = |name: &str| ActorId::new(name.to_string()).unwrap();
+        let grant = |name: &str, role| RoleGrant::new(Uuid::new_v4(), actor(name), role, None);
+        let policy = |carol| {
+            AccessPolicy::new(vec![
+                grant("alice", Role::Accountant),
+                grant("bob", Role::Accountant),
+                grant("carol", carol),
+                grant("erin", Role::Auditor),
+            ])
+        };
+        let mut service = service.with_policy(policy(Role::Accountant));
+
+        let request = CorrectionRequest::Correct {
+            term_id,
+            section_id,
+            original_amount: Money::new(Decimal::from_str("100.00").unwrap()),
+            correct_amount: Money::new(Decimal::from_str("150.00").unwrap()),
+            date: NaiveDate::from_ymd_opt(2025, 6, 1)
+                .unwrap()
+                .and_hms_opt(10, 0, 0)
+                .unwrap(),
+        };
+        let by_carol = workflow
+            .draft(
+                &service,
+                request.clone(),
+                actor("carol"),
+                "Typo".to_string(),
+            )
+            .unwrap();
+        let by_alice = workflow
+            .draft(&service, request, actor("alice"), "Typo".to_string())
+            .unwrap();
+        workflow
+            .submit(&service, by_alice, &actor("alice"))
+            .unwrap();
+
+        // Carol is demoted to clerk before she submits her draft
+        service = service.with_policy(policy(Role::Clerk));
+        assert!(matches!(
+            workflow.submit(&service, by_carol, &actor("carol")),
+            Err(ServiceError::Forbidden(_))
+        ));
+        for checker in ["carol", "erin", "mallory"] {
+            assert!(matches!(
+                workflow.reject(&service, by_alice, actor(checker), "No".to_string()),
+                Err(ServiceError::Forbidden(_))
+            ));
+        }
+        assert_eq!(
+            workflow.find(&by_alice).unwrap().status,
+            CorrectionStatus::Submitted
+        );
+        workflow
+            .reject(&service, by_alice, actor("bob"), "No".to_string())
+            .unwrap();
+    }
+}
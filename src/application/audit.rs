@@ -0,0 +1,168 @@
+use crate::domain::entity::{AuditRecord, AuditedEntity};
+use crate::domain::repository::AuditLogRepository;
+use crate::domain::value_object::ActorId;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+/// Filters for `AuditLog::query`; unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    pub actor: Option<ActorId>,
+    pub entity: Option<AuditedEntity>,
+    pub entity_id: Option<Uuid>,
+    pub correlation_id: Option<String>,
+    /// Inclusive lower bound on the change timestamp
+    pub from: Option<NaiveDateTime>,
+    /// Inclusive upper bound on the change timestamp
+    pub to: Option<NaiveDateTime>,
+}
+
+impl AuditQuery {
+    fn matches(&self, record: &AuditRecord) -> bool {
+        self.actor.as_ref().is_none_or(|a| *a == record.audit.actor)
+            && self.entity.is_none_or(|e| e == record.entity)
+            && self.entity_id.is_none_or(|id| id == record.entity_id)
+            && self
+                .correlation_id
+                .as_ref()
+                .is_none_or(|c| record.audit.correlation_id.as_ref() == Some(c))
+            && self.from.is_none_or(|from| record.audit.timestamp >= from)
+            && self.to.is_none_or(|to| record.audit.timestamp <= to)
+    }
+}
+
+pub struct AuditLog<'a, A>
+where
+    A: AuditLogRepository,
+{
+    audit_repo: &'a A,
+}
+
+impl<'a, A> AuditLog<'a, A>
+where
+    A: AuditLogRepository,
+{
+    pub fn new(audit_repo: &'a A) -> Self {
+        Self { audit_repo }
+    }
+
+    /// Matching records, oldest first.
+    pub fn query(&self, query: &AuditQuery) -> Vec<AuditRecord> {
+        let candidates = match query.entity_id {
+            Some(id) => self.audit_repo.find_by_entity(&id),
+            None => self.audit_repo.list_all(),
+        };
+        candidates
+            .into_iter()
+            .filter(|r| query.matches(r))
+            .collect()
+    }
+
+    /// Every recorded change to one section, term or sale, oldest first.
+    pub fn history(&self, entity_id: Uuid) -> Vec<AuditRecord> {
+        self.audit_repo.find_by_entity(&entity_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entity::{AuditAction, Section, SectionType, Term};
+    use crate::domain::value_object::Money;
+    use crate::test_support::{audit_as, service};
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_changes_are_logged_with_actor() {
+        let mut service = service();
+
+        let section_a = Section::new(
+            service.ids().next_id(),
+            "Section A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_a_id = service
+            .create_section(section_a, None, audit_as("alice"))
+            .unwrap();
+        let section_b = Section::new(
+            service.ids().next_id(),
+            "Section B".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_b_id = service
+            .create_section(section_b, None, audit_as("alice"))
+            .unwrap();
+
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit_as("alice")).unwrap();
+        service.close_term(term_id, None, audit_as("bob")).unwrap();
+
+        let amount = Money::new(Decimal::from_str("100.00").unwrap());
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+
+        // Corrections need a reason
+        assert!(service
+            .rebalance_term(
+                term_id,
+                section_a_id,
+                section_b_id,
+                amount,
+                date,
+                None,
+                audit_as("bob"),
+            )
+            .is_err());
+        let reasoned = audit_as("bob")
+            .with_reason(Some("Misallocated in June".to_string()))
+            .with_correlation_id(Some("ticket-7".to_string()));
+        service
+            .rebalance_term(
+                term_id,
+                section_a_id,
+                section_b_id,
+                amount,
+                date,
+                None,
+                reasoned,
+            )
+            .unwrap();
+
+        let history = service.audit_log().history(term_id);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].action, AuditAction::Created);
+        assert_eq!(history[1].action, AuditAction::Closed);
+        assert_eq!(history[1].audit.actor.as_str(), "bob");
+
+        let by_bob = service.audit_log().query(&AuditQuery {
+            actor: Some(ActorId::new("bob".to_string()).unwrap()),
+            ..AuditQuery::default()
+        });
+        assert_eq!(by_bob.len(), 3);
+
+        let ticket = service.audit_log().query(&AuditQuery {
+            entity: Some(AuditedEntity::Sales),
+            correlation_id: Some("ticket-7".to_string()),
+            ..AuditQuery::default()
+        });
+        assert_eq!(ticket.len(), 2);
+        for record in ticket {
+            let sales = service.find_sales(&record.entity_id).unwrap();
+            let info = sales.audit.unwrap();
+            assert_eq!(info.reason.as_deref(), Some("Misallocated in June"));
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use crate::domain::entity::{Role, RoleGrant};
+use crate::domain::repository::RoleGrantRepository;
+use crate::domain::value_object::ActorId;
+use std::collections::HashMap;
+use std::fmt;
+use uuid::Uuid;
+
+/// The mutating operations an `AccessPolicy` governs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    CreateSection,
+    CreateTerm,
+    RegisterSales,
+    TransferSales,
+    CloseTerm,
+    CorrectTerm,
+    RebalanceTerm,
+    RenameSection,
+    MoveSection,
+    DeactivateSection,
+    MergeSections,
+    SubmitSection,
+    ManageRoles,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            Operation::CreateSection => "create sections",
+            Operation::CreateTerm => "create terms",
+            Operation::RegisterSales => "register sales",
+            Operation::TransferSales => "transfer sales",
+            Operation::CloseTerm => "close terms",
+            Operation::CorrectTerm => "correct terms",
+            Operation::RebalanceTerm => "rebalance terms",
+            Operation::RenameSection => "rename sections",
+            Operation::MoveSection => "move sections",
+            Operation::DeactivateSection => "deactivate sections",
+            Operation::MergeSections => "merge sections",
+            Operation::SubmitSection => "submit sections",
+            Operation::ManageRoles => "manage roles",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// Which roles may perform each operation, and where each actor holds them.
+///
+/// An actor may perform an operation on a section when one of their grants
+/// names an allowed role and is scoped to the section or one of its
+/// ancestors. Operations that touch no section (terms, roles) need an
+/// unscoped grant. Auditors are allowed nothing by default: reads are not
+/// governed by the policy.
+///
+/// The policy trusts the `ActorId` it is given. Nothing here authenticates
+/// it, so an actor who claims another's identity gets that actor's roles, and
+/// the separation between the maker and the checker of a correction does not
+/// hold against them either.
+#[derive(Debug, Clone)]
+pub struct AccessPolicy {
+    rules: HashMap<Operation, Vec<Role>>,
+    grants: Vec<RoleGrant>,
+}
+
+impl AccessPolicy {
+    /// The default rules: clerks register and transfer sales, accountants
+    /// also correct and rebalance, and controllers may do everything,
+    /// including reorganizing sections.
+    pub fn new(grants: Vec<RoleGrant>) -> Self {
+        use Operation::*;
+        use Role::*;
+
+        let rules = [
+            (CreateSection, vec![Controller]),
+            (CreateTerm, vec![Controller]),
+            (RegisterSales, vec![Clerk, Accountant, Controller]),
+            (TransferSales, vec![Clerk, Accountant, Controller]),
+            (CloseTerm, vec![Controller]),
+            (CorrectTerm, vec![Accountant, Controller]),
+            (RebalanceTerm, vec![Accountant, Controller]),
+            (RenameSection, vec![Controller]),
+            (MoveSection, vec![Controller]),
+            (DeactivateSection, vec![Controller]),
+            (MergeSections, vec![Controller]),
+            (SubmitSection, vec![Clerk, Accountant, Controller]),
+            (ManageRoles, vec![Controller]),
+        ]
+        .into_iter()
+        .collect();
+
+        Self {
+            rules,
+            grants: grants.into_iter().filter(|g| !g.revoked).collect(),
+        }
+    }
+
+    /// A policy over the stored grants, or `None` while nothing has ever
+    /// been granted, so a store stays open until its first role is assigned.
+    /// Revoked grants still count here: revoking every role leaves the store
+    /// closed to everyone rather than open again.
+    pub fn load<R: RoleGrantRepository>(repo: &R) -> Option<Self> {
+        let grants = repo.list_all();
+        if grants.is_empty() {
+            None
+        } else {
+            Some(Self::new(grants))
+        }
+    }
+
+    /// Checks `actor` may save `grant` among those in `repo`. Once anything
+    /// is granted this takes an unscoped controller; until then the only
+    /// grant allowed is an unscoped controller role for `actor` themselves,
+    /// so whoever opens a store cannot hand out roles they do not hold.
+    pub fn check_grant<R: RoleGrantRepository>(
+        repo: &R,
+        actor: &ActorId,
+        grant: &RoleGrant,
+    ) -> Result<(), String> {
+        match Self::load(repo) {
+            Some(policy) => policy.check(actor, Operation::ManageRoles, &[]),
+            None if grant.actor == *actor
+                && grant.role == Role::Controller
+                && grant.scope.is_none() =>
+            {
+                Ok(())
+            }
+            None => Err(format!(
+                "The first role granted must be an unscoped controller role for {}",
+                actor
+            )),
+        }
+    }
+
+    /// Checks `actor` may revoke `grant` among those in `repo`. This takes an
+    /// unscoped controller, and the last active unscoped controller role
+    /// cannot be revoked, so someone is always left who can manage roles.
+    pub fn check_revoke<R: RoleGrantRepository>(
+        repo: &R,
+        actor: &ActorId,
+        grant: &RoleGrant,
+    ) -> Result<(), String> {
+        if let Some(policy) = Self::load(repo) {
+            policy.check(actor, Operation::ManageRoles, &[])?;
+        }
+        let controls =
+            |g: &RoleGrant| !g.revoked && g.role == Role::Controller && g.scope.is_none();
+        if controls(grant)
+            && !repo
+                .list_all()
+                .iter()
+                .any(|g| g.id != grant.id && controls(g))
+        {
+            return Err(format!(
+                "Role grant {} is the last unscoped controller role and cannot be revoked",
+                grant.id
+            ));
+        }
+        Ok(())
+    }
+
+    /// Replaces the roles allowed to perform `operation`.
+    pub fn allow(mut self, operation: Operation, roles: &[Role]) -> Self {
+        self.rules.insert(operation, roles.to_vec());
+        self
+    }
+
+    pub fn allowed_roles(&self, operation: Operation) -> &[Role] {
+        self.rules.get(&operation).map_or(&[], |roles| roles)
+    }
+
+    /// Checks `actor` may perform `operation` on the section whose path from
+    /// itself up to the root is `path`; an empty path needs an unscoped grant.
+    pub fn check(
+        &self,
+        actor: &ActorId,
+        operation: Operation,
+        path: &[Uuid],
+    ) -> Result<(), String> {
+        let allowed = self.allowed_roles(operation);
+        let permitted = self.grants.iter().any(|g| {
+            g.actor == *actor
+                && allowed.contains(&g.role)
+                && g.scope.is_none_or(|scope| path.contains(&scope))
+        });
+        if permitted {
+            Ok(())
+        } else if path.is_empty() {
+            Err(format!("{} is not permitted to {}", actor, operation))
+        } else {
+            Err(format!(
+                "{} is not permitted to {} in this section",
+                actor, operation
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::in_memory::InMemoryRoleGrantRepository;
+
+    fn actor(name: &str) -> ActorId {
+        ActorId::new(name.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_grants_apply_to_their_subtree() {
+        let division_x = Uuid::new_v4();
+        let division_y = Uuid::new_v4();
+        let section_x = Uuid::new_v4();
+        let policy = AccessPolicy::new(vec![
+            RoleGrant::new(
+                Uuid::new_v4(),
+                actor("carol"),
+                Role::Clerk,
+                Some(division_x),
+            ),
+            RoleGrant::new(Uuid::new_v4(), actor("dave"), Role::Controller, None),
+        ]);
+
+        let in_x = [section_x, division_x];
+        let in_y = [division_y];
+        assert!(policy
+            .check(&actor("carol"), Operation::TransferSales, &in_x)
+            .is_ok());
+        assert!(policy
+            .check(&actor("carol"), Operation::TransferSales, &in_y)
+            .is_err());
+        assert!(policy
+            .check(&actor("carol"), Operation::RebalanceTerm, &in_x)
+            .is_err());
+        assert!(policy
+            .check(&actor("carol"), Operation::CloseTerm, &[])
+            .is_err());
+        assert!(policy
+            .check(&actor("dave"), Operation::CloseTerm, &[])
+            .is_ok());
+        assert!(policy
+            .check(&actor("dave"), Operation::RebalanceTerm, &in_y)
+            .is_ok());
+
+        let strict = policy.allow(Operation::TransferSales, &[Role::Accountant]);
+        assert!(strict
+            .check(&actor("carol"), Operation::TransferSales, &in_x)
+            .is_err());
+    }
+
+    #[test]
+    fn test_first_grant_is_a_controller_role_for_the_actor() {
+        let mut repo = InMemoryRoleGrantRepository::new();
+        let division_x = Uuid::new_v4();
+        let grant =
+            |name: &str, role, scope| RoleGrant::new(Uuid::new_v4(), actor(name), role, scope);
+
+        for first in [
+            grant("mallory", Role::Controller, None),
+            grant("frank", Role::Clerk, None),
+            grant("frank", Role::Controller, Some(division_x)),
+        ] {
+            assert!(AccessPolicy::check_grant(&repo, &actor("frank"), &first).is_err());
+        }
+        let first = grant("frank", Role::Controller, None);
+        AccessPolicy::check_grant(&repo, &actor("frank"), &first).unwrap();
+        repo.save(first).unwrap();
+
+        let clerk = grant("grace", Role::Clerk, Some(division_x));
+        AccessPolicy::check_grant(&repo, &actor("frank"), &clerk).unwrap();
+        repo.save(clerk).unwrap();
+        let own = grant("grace", Role::Controller, None);
+        assert!(AccessPolicy::check_grant(&repo, &actor("grace"), &own).is_err());
+    }
+
+    #[test]
+    fn test_last_controller_cannot_be_revoked() {
+        let mut repo = InMemoryRoleGrantRepository::new();
+        let boss = RoleGrant::new(Uuid::new_v4(), actor("boss"), Role::Controller, None);
+        let deputy = RoleGrant::new(Uuid::new_v4(), actor("deputy"), Role::Controller, None);
+        let clerk = RoleGrant::new(Uuid::new_v4(), actor("carol"), Role::Clerk, None);
+        repo.save(boss.clone()).unwrap();
+        repo.save(deputy.clone()).unwrap();
+        repo.save(clerk.clone()).unwrap();
+
+        assert!(AccessPolicy::check_revoke(&repo, &actor("carol"), &deputy).is_err());
+        AccessPolicy::check_revoke(&repo, &actor("boss"), &deputy).unwrap();
+        let mut revoked = deputy;
+        revoked.revoke().unwrap();
+        repo.save(revoked).unwrap();
+
+        assert!(AccessPolicy::check_revoke(&repo, &actor("boss"), &boss).is_err());
+        AccessPolicy::check_revoke(&repo, &actor("boss"), &clerk).unwrap();
+    }
+
+    #[test]
+    fn test_revoking_every_grant_keeps_the_policy() {
+        let mut repo = InMemoryRoleGrantRepository::new();
+        let mut grant = RoleGrant::new(Uuid::new_v4(), actor("boss"), Role::Controller, None);
+        grant.revoke().unwrap();
+        repo.save(grant).unwrap();
+
+        let policy = AccessPolicy::load(&repo).expect("a revoked grant still counts");
+        assert!(policy
+            .check(&actor("mallory"), Operation::ManageRoles, &[])
+            .is_err());
+        let own = RoleGrant::new(Uuid::new_v4(), actor("mallory"), Role::Controller, None);
+        assert!(AccessPolicy::check_grant(&repo, &actor("mallory"), &own).is_err());
+    }
+
+    #[test]
+    fn test_revoked_grants_are_ignored() {
+        let mut grant = RoleGrant::new(Uuid::new_v4(), actor("erin"), Role::Controller, None);
+        grant.revoke().unwrap();
+        let policy = AccessPolicy::new(vec![grant]);
+        assert!(policy
+            .check(&actor("erin"), Operation::CreateTerm, &[])
+            .is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use super::service::ServiceError;
+use crate::domain::entity::PendingCorrection;
+use crate::domain::repository::PendingCorrectionRepository;
+use serde::Serialize;
+use std::fmt;
+use uuid::Uuid;
+
+/// A condition `close_term` requires before it closes a term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum CloseCheck {
+    /// The term's integrity report has no violations
+    IntegrityClean,
+    /// No correction for the term is still a draft or awaiting approval
+    NoPendingCorrections,
+    /// Every section active at the end of the term without child sections
+    /// has submitted its figures
+    SectionsSubmitted,
+    /// No entry in the term is dated after the moment of closing
+    NoFutureEntries,
+}
+
+/// One reason a term cannot be closed yet.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CloseFailure {
+    pub check: CloseCheck,
+    pub message: String,
+    /// Entries, sections or corrections the failure is about
+    pub entity_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CloseError {
+    /// Checks failed and no override was given
+    Blocked(Vec<CloseFailure>),
+    /// The close itself was refused, for example for an unknown term
+    Rejected(ServiceError),
+}
+
+impl fmt::Display for CloseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CloseError::Blocked(failures) => {
+                let messages: Vec<&str> = failures.iter().map(|f| f.message.as_str()).collect();
+                write!(f, "Term cannot be closed: {}", messages.join("; "))
+            }
+            CloseError::Rejected(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl<E: Into<ServiceError>> From<E> for CloseError {
+    fn from(error: E) -> Self {
+        CloseError::Rejected(error.into())
+    }
+}
+
+impl From<CloseError> for String {
+    fn from(error: CloseError) -> Self {
+        error.to_string()
+    }
+}
+
+/// Lists the pending-correction store each time the checklist is evaluated.
+type CorrectionSource = Box<dyn Fn() -> Result<Vec<PendingCorrection>, String>>;
+
+/// The checks `close_term` runs. Pending corrections live outside the
+/// service, so `NoPendingCorrections` fails unless a correction store is
+/// supplied with `with_corrections` or `with_corrections_from`.
+///
+/// The default checklist is deliberately empty: a service built without one
+/// has no correction store, so `standard()` could never pass there. The CLI
+/// and the server both run `standard()` with their store.
+#[derive(Default)]
+pub struct CloseChecklist {
+    checks: Vec<CloseCheck>,
+    corrections: Option<CorrectionSource>,
+}
+
+impl CloseChecklist {
+    pub fn new(checks: &[CloseCheck]) -> Self {
+        Self {
+            checks: checks.to_vec(),
+            corrections: None,
+        }
+    }
+
+    /// Every check.
+    pub fn standard() -> Self {
+        Self::new(&[
+            CloseCheck::IntegrityClean,
+            CloseCheck::NoPendingCorrections,
+            CloseCheck::SectionsSubmitted,
+            CloseCheck::NoFutureEntries,
+        ])
+    }
+
+    pub fn with_corrections<P: PendingCorrectionRepository + 'static>(mut self, repo: P) -> Self {
+        self.corrections = Some(Box::new(move || Ok(repo.list_all())));
+        self
+    }
+
+    /// Opens the correction store afresh at every close, so a long-running
+    /// process sees corrections other processes filed since it started.
+    pub fn with_corrections_from<P, F>(mut self, open: F) -> Self
+    where
+        P: PendingCorrectionRepository,
+        F: Fn() -> Result<P, String> + 'static,
+    {
+        self.corrections = Some(Box::new(move || open().map(|repo| repo.list_all())));
+        self
+    }
+
+    pub fn checks(&self) -> &[CloseCheck] {
+        &self.checks
+    }
+
+    /// Every correction in the store, or `None` when there is no store.
+    pub(crate) fn corrections(&self) -> Option<Result<Vec<PendingCorrection>, String>> {
+        self.corrections.as_ref().map(|list| list())
+    }
+}
@@ -0,0 +1,298 @@
+use crate::domain::entity::{Sales, SalesType, Section, Term};
+use crate::domain::repository::{SalesRepository, SectionRepository, TermRepository};
+use crate::domain::value_object::Money;
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportScope {
+    Term(Uuid),
+    /// Entries dated within `[from, to]`, across terms.
+    DateRange {
+        from: NaiveDate,
+        to: NaiveDate,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("{0}")]
+    NotFound(String),
+    #[error("failed to write export: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to write CSV: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("failed to write JSON: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// One exported ledger line: a `Sales` entry with its section and term resolved.
+#[derive(Debug, Clone, Serialize)]
+pub struct LedgerRow {
+    pub sales_id: Uuid,
+    pub date: NaiveDateTime,
+    pub amount: Money,
+    pub sales_type: SalesType,
+    pub section_id: Uuid,
+    pub section_name: Option<String>,
+    pub term_id: Uuid,
+    pub term_start: Option<NaiveDate>,
+    pub term_end: Option<NaiveDate>,
+    pub related_sales_id: Option<Uuid>,
+}
+
+pub struct LedgerExporter<'a, S, T, L>
+where
+    S: SectionRepository,
+    T: TermRepository,
+    L: SalesRepository,
+{
+    section_repo: &'a S,
+    term_repo: &'a T,
+    sales_repo: &'a L,
+}
+
+impl<'a, S, T, L> LedgerExporter<'a, S, T, L>
+where
+    S: SectionRepository,
+    T: TermRepository,
+    L: SalesRepository,
+{
+    pub fn new(section_repo: &'a S, term_repo: &'a T, sales_repo: &'a L) -> Self {
+        Self {
+            section_repo,
+            term_repo,
+            sales_repo,
+        }
+    }
+
+    /// Writes the entries in `scope` to `writer`, ordered by date, each row as
+    /// it is read from the repository. Returns the number of rows written.
+    ///
+    /// Memory use follows the repository: `FileSalesLedger` reads each entry
+    /// from the store as it is written, while `FileSalesRepository` holds the
+    /// whole ledger once opened.
+    pub fn export<W: Write>(
+        &self,
+        scope: ExportScope,
+        format: ExportFormat,
+        writer: W,
+    ) -> Result<usize, ExportError> {
+        let sales: Box<dyn Iterator<Item = Sales>> = match scope {
+            ExportScope::Term(term_id) => {
+                let term = self
+                    .term_repo
+                    .find_by_id(&term_id)
+                    .ok_or_else(|| ExportError::NotFound("Term not found".to_string()))?;
+                Box::new(
+                    self.sales_repo
+                        .iter_by_date(term.start_date, term.end_date)
+                        .filter(move |s| s.term_id == term_id),
+                )
+            }
+            ExportScope::DateRange { from, to } => self.sales_repo.iter_by_date(from, to),
+        };
+
+        let rows = self.rows(sales);
+        match format {
+            ExportFormat::Csv => write_csv(rows, writer),
+            ExportFormat::Json => write_json(rows, writer),
+        }
+    }
+
+    /// Resolves sections and term bounds lazily, caching each lookup. Sections
+    /// are labelled with the name they had on the entry's date.
+    fn rows<'s>(
+        &'s self,
+        sales: impl Iterator<Item = Sales> + 's,
+    ) -> impl Iterator<Item = LedgerRow> + 's {
+        let mut sections: HashMap<Uuid, Option<Section>> = HashMap::new();
+        let mut terms: HashMap<Uuid, Option<Term>> = HashMap::new();
+        sales.map(move |s| {
+            let section_name = sections
+                .entry(s.section_id)
+                .or_insert_with(|| self.section_repo.find_by_id(&s.section_id))
+                .as_ref()
+                .map(|section| section.name_on(s.date.date()).to_string());
+            let term = terms
+                .entry(s.term_id)
+                .or_insert_with(|| self.term_repo.find_by_id(&s.term_id));
+            LedgerRow {
+                sales_id: s.id,
+                date: s.date,
+                amount: s.amount,
+                sales_type: s.sales_type,
+                section_id: s.section_id,
+                section_name,
+                term_id: s.term_id,
+                term_start: term.as_ref().map(|t| t.start_date),
+                term_end: term.as_ref().map(|t| t.end_date),
+                related_sales_id: s.related_sales_id,
+            }
+        })
+    }
+}
+
+fn write_csv<W: Write>(
+    rows: impl Iterator<Item = LedgerRow>,
+    writer: W,
+) -> Result<usize, ExportError> {
+    let mut csv_writer = csv::Writer::from_writer(writer);
+    let mut count = 0;
+    for row in rows {
+        csv_writer.serialize(row)?;
+        count += 1;
+    }
+    // An empty export still gets a header line
+    if count == 0 {
+        csv_writer.write_record([
+            "sales_id",
+            "date",
+            "amount",
+            "sales_type",
+            "section_id",
+            "section_name",
+            "term_id",
+            "term_start",
+            "term_end",
+            "related_sales_id",
+        ])?;
+    }
+    csv_writer.flush()?;
+    Ok(count)
+}
+
+/// Writes a JSON array element by element rather than building it in memory.
+fn write_json<W: Write>(
+    rows: impl Iterator<Item = LedgerRow>,
+    mut writer: W,
+) -> Result<usize, ExportError> {
+    let mut count = 0;
+    writer.write_all(b"[")?;
+    for row in rows {
+        if count > 0 {
+            writer.write_all(b",")?;
+        }
+        writer.write_all(b"\n  ")?;
+        serde_json::to_writer(&mut writer, &row)?;
+        count += 1;
+    }
+    writer.write_all(if count > 0 { b"\n]\n" } else { b"]\n" })?;
+    writer.flush()?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entity::{Section, SectionType};
+    use crate::test_support::{audit, service, MemoryService};
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn setup() -> (MemoryService, Uuid) {
+        let mut service = service();
+
+        let section = Section::new(
+            service.ids().next_id(),
+            "Tokyo, East".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_id = service.create_section(section, None, audit()).unwrap();
+
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+
+        for (day, amount) in [(1, "100.00"), (2, "-20.50")] {
+            let date = NaiveDate::from_ymd_opt(2025, 6, day)
+                .unwrap()
+                .and_hms_opt(10, 0, 0)
+                .unwrap();
+            let amount = Money::new(Decimal::from_str(amount).unwrap());
+            service
+                .register_sales(amount, date, section_id, None, audit())
+                .unwrap();
+        }
+        (service, term_id)
+    }
+
+    #[test]
+    fn test_export_term_csv() {
+        let (mut service, term_id) = setup();
+        let section_id = service.list_sections()[0].id;
+        let renamed = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+        service
+            .rename_section(section_id, "Tokyo".to_string(), renamed, None, audit())
+            .unwrap();
+
+        let mut out = Vec::new();
+        let count = service
+            .exporter()
+            .export(ExportScope::Term(term_id), ExportFormat::Csv, &mut out)
+            .unwrap();
+        assert_eq!(count, 2);
+
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("sales_id,date,amount,sales_type,section_id,section_name"));
+        assert!(lines[1].contains(",100.00,Normal,"));
+        assert!(lines[1].contains("\"Tokyo, East\""));
+        assert!(lines[2].contains(",-20.50,Normal,"));
+        // Each row names the section as it was on the entry's date
+        assert!(lines[2].contains(",Tokyo,"));
+    }
+
+    #[test]
+    fn test_export_date_range_json() {
+        let (service, _) = setup();
+
+        let mut out = Vec::new();
+        let day = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+        let count = service
+            .exporter()
+            .export(
+                ExportScope::DateRange { from: day, to: day },
+                ExportFormat::Json,
+                &mut out,
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        let rows = value.as_array().unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["amount"], "-20.50");
+        assert_eq!(rows[0]["section_name"], "Tokyo, East");
+        assert_eq!(rows[0]["term_start"], "2025-01-01");
+    }
+
+    #[test]
+    fn test_export_unknown_term() {
+        let (service, _) = setup();
+
+        let result = service.exporter().export(
+            ExportScope::Term(Uuid::new_v4()),
+            ExportFormat::Json,
+            Vec::new(),
+        );
+        assert!(matches!(result, Err(ExportError::NotFound(_))));
+    }
+}
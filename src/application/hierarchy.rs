@@ -0,0 +1,173 @@
+use crate::domain::entity::Section;
+use crate::domain::repository::SectionRepository;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Separator between section names in `HierarchyService::path`.
+pub const PATH_SEPARATOR: &str = " > ";
+
+/// Navigation of the current section tree, backed by the materialized paths
+/// the section repository keeps.
+pub struct HierarchyService<'a, S>
+where
+    S: SectionRepository,
+{
+    section_repo: &'a S,
+}
+
+impl<'a, S> HierarchyService<'a, S>
+where
+    S: SectionRepository,
+{
+    pub fn new(section_repo: &'a S) -> Self {
+        Self { section_repo }
+    }
+
+    /// The parent, grandparent and so on up to the root, nearest first.
+    pub fn ancestors(&self, id: Uuid) -> Result<Vec<Section>, String> {
+        let path = self.path_ids(id)?;
+        Ok(path
+            .iter()
+            .rev()
+            .skip(1)
+            .filter_map(|ancestor_id| self.section_repo.find_by_id(ancestor_id))
+            .collect())
+    }
+
+    /// Every section below `id`, in tree order: each section is followed by
+    /// its own subtree, and siblings are ordered by name.
+    pub fn descendants(&self, id: Uuid) -> Result<Vec<Section>, String> {
+        let mut subtree = self.subtree(id)?;
+        subtree.remove(0);
+        Ok(subtree)
+    }
+
+    /// `id` itself followed by its descendants, in tree order.
+    pub fn subtree(&self, id: Uuid) -> Result<Vec<Section>, String> {
+        let root = self
+            .section_repo
+            .find_by_id(&id)
+            .ok_or("Section not found")?;
+        let mut sections = self.section_repo.find_descendants(&id);
+        sections.push(root);
+
+        // Sort by the names along each path below (and including) the root
+        let names: HashMap<Uuid, String> =
+            sections.iter().map(|s| (s.id, s.name.clone())).collect();
+        let mut keyed: Vec<(Vec<(String, Uuid)>, Section)> = sections
+            .into_iter()
+            .map(|section| {
+                let key = self
+                    .section_repo
+                    .find_path(&section.id)
+                    .into_iter()
+                    .skip_while(|step| *step != id)
+                    .map(|step| (names.get(&step).cloned().unwrap_or_default(), step))
+                    .collect();
+                (key, section)
+            })
+            .collect();
+        keyed.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(keyed.into_iter().map(|(_, section)| section).collect())
+    }
+
+    /// Names from the root down to the section, such as
+    /// "Sales Dept > Division East > Tokyo".
+    pub fn path(&self, id: Uuid) -> Result<String, String> {
+        let names: Vec<String> = self
+            .path_ids(id)?
+            .iter()
+            .filter_map(|step| self.section_repo.find_by_id(step))
+            .map(|s| s.name)
+            .collect();
+        Ok(names.join(PATH_SEPARATOR))
+    }
+
+    /// 0 for a top-level section.
+    pub fn depth(&self, id: Uuid) -> Result<usize, String> {
+        Ok(self.path_ids(id)?.len() - 1)
+    }
+
+    /// Whether `id` is `root` or one of its descendants.
+    pub fn is_within(&self, id: Uuid, root: Uuid) -> bool {
+        self.section_repo.find_path(&id).contains(&root)
+    }
+
+    fn path_ids(&self, id: Uuid) -> Result<Vec<Uuid>, String> {
+        let path = self.section_repo.find_path(&id);
+        if path.is_empty() {
+            return Err("Section not found".to_string());
+        }
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::entity::{Section, SectionType};
+    use crate::test_support::{audit, service};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_tree_navigation_follows_moves() {
+        let mut service = service();
+        let mut add = |name: &str, section_type, parent_id| {
+            let section = Section::new(
+                service.ids().next_id(),
+                name.to_string(),
+                section_type,
+                parent_id,
+            )
+            .unwrap();
+            service.create_section(section, None, audit()).unwrap()
+        };
+        let dept = add("Sales Dept", SectionType::Department, None);
+        let east = add("East", SectionType::Division, Some(dept));
+        let west = add("West", SectionType::Division, Some(dept));
+        let tokyo = add("Tokyo", SectionType::Section, Some(east));
+        let chiba = add("Chiba", SectionType::Section, Some(east));
+
+        let hierarchy = service.hierarchy();
+        assert_eq!(hierarchy.path(tokyo).unwrap(), "Sales Dept > East > Tokyo");
+        assert_eq!(hierarchy.depth(tokyo).unwrap(), 2);
+        let ancestors: Vec<_> = hierarchy
+            .ancestors(tokyo)
+            .unwrap()
+            .iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(ancestors, vec![east, dept]);
+        let subtree: Vec<_> = hierarchy
+            .subtree(dept)
+            .unwrap()
+            .iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(subtree, vec![dept, east, chiba, tokyo, west]);
+        assert!(hierarchy.is_within(tokyo, dept));
+        assert!(!hierarchy.is_within(tokyo, west));
+        assert!(hierarchy.path(uuid::Uuid::new_v4()).is_err());
+
+        // Moving a division moves its whole subtree
+        let north = Section::new(
+            service.ids().next_id(),
+            "North".to_string(),
+            SectionType::Department,
+            None,
+        )
+        .unwrap();
+        let north = service.create_section(north, None, audit()).unwrap();
+        service
+            .move_section(
+                east,
+                Some(north),
+                NaiveDate::from_ymd_opt(2025, 4, 1).unwrap(),
+                None,
+                audit(),
+            )
+            .unwrap();
+        let hierarchy = service.hierarchy();
+        assert_eq!(hierarchy.path(tokyo).unwrap(), "North > East > Tokyo");
+        assert_eq!(hierarchy.descendants(dept).unwrap().len(), 1);
+    }
+}
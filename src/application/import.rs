@@ -0,0 +1,538 @@
+use super::reference::{parse_date_time, resolve_section, SectionRefError};
+use super::service::{AccountingService, Registration};
+use crate::domain::repository::{
+    AuditLogRepository, SalesRepository, SectionRepository, TermRepository,
+};
+use crate::domain::value_object::{AuditInfo, IdempotencyKey, Money};
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::io::Read;
+use std::str::FromStr;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Every row is validated first; nothing is written unless all rows pass,
+    /// and then all of them are written together.
+    AllOrNothing,
+    /// Valid rows are registered, invalid rows are reported and skipped.
+    BestEffort,
+}
+
+/// Failure of the import as a whole (as opposed to a single row).
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("failed to read CSV: {0}")]
+    Csv(#[from] csv::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RowError {
+    #[error("malformed row: {0}")]
+    Malformed(String),
+    #[error("invalid date: {0}")]
+    InvalidDate(String),
+    #[error("invalid amount: {0}")]
+    InvalidAmount(String),
+    #[error("invalid external reference: {0}")]
+    InvalidReference(String),
+    #[error("external reference {0} appears more than once in the file")]
+    DuplicateReference(String),
+    #[error("unknown section: {0}")]
+    UnknownSection(String),
+    #[error("ambiguous section: {0} matches more than one section")]
+    AmbiguousSection(String),
+    #[error("rejected: {0}")]
+    Rejected(String),
+    #[error("not imported because another row failed")]
+    Aborted,
+}
+
+#[derive(Debug, Clone)]
+pub struct RowResult {
+    /// 1-based line number in the source file, counting the header.
+    pub line: usize,
+    pub reference: Option<String>,
+    pub outcome: Result<Uuid, RowError>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    pub rows: Vec<RowResult>,
+}
+
+impl ImportReport {
+    pub fn accepted(&self) -> usize {
+        self.rows.iter().filter(|r| r.outcome.is_ok()).count()
+    }
+
+    pub fn rejected(&self) -> usize {
+        self.rows.len() - self.accepted()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawRow {
+    date: String,
+    section: String,
+    amount: String,
+    reference: String,
+}
+
+struct ParsedRow {
+    date: NaiveDateTime,
+    section_id: Uuid,
+    amount: Money,
+    key: IdempotencyKey,
+}
+
+/// Imports sales from CSV with the header `date,section,amount,reference`.
+///
+/// `section` is resolved as a section ID first, then as a section code and
+/// then by exact name. The external `reference` becomes the idempotency key
+/// of the registered sale, so re-importing the same file does not create
+/// duplicates. Every registered sale carries `audit`, and every row is
+/// previewed as `audit`'s actor before anything is written, so an
+/// all-or-nothing import is also refused when the actor may not post a row.
+pub fn import_sales<S, T, L, A, R>(
+    service: &mut AccountingService<S, T, L, A>,
+    reader: R,
+    mode: ImportMode,
+    audit: &AuditInfo,
+) -> Result<ImportReport, ImportError>
+where
+    S: SectionRepository,
+    T: TermRepository,
+    L: SalesRepository,
+    A: AuditLogRepository,
+    R: Read,
+{
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+
+    // 1. Parse and resolve every row, and run the service and policy checks
+    let mut parsed = Vec::new();
+    let mut seen_references = HashSet::new();
+    for (index, record) in csv_reader.deserialize::<RawRow>().enumerate() {
+        let line = index + 2;
+        let (reference, row) = match record {
+            Ok(raw) => {
+                let reference = raw.reference.clone();
+                let row = parse_row(service, raw).and_then(|row| {
+                    if !seen_references.insert(row.key.clone()) {
+                        return Err(RowError::DuplicateReference(reference.clone()));
+                    }
+                    service
+                        .preview_register_sales(
+                            row.amount,
+                            row.date,
+                            row.section_id,
+                            Some(row.key.clone()),
+                            audit.clone(),
+                        )
+                        .map_err(|e| RowError::Rejected(e.to_string()))?;
+                    Ok(row)
+                });
+                (Some(reference), row)
+            }
+            Err(e) if e.is_io_error() => return Err(e.into()),
+            Err(e) => (None, Err(RowError::Malformed(e.to_string()))),
+        };
+        parsed.push((line, reference, row));
+    }
+
+    if mode == ImportMode::AllOrNothing {
+        return Ok(register_all(service, parsed, audit));
+    }
+
+    // 2. Register the rows that passed
+    let mut report = ImportReport::default();
+    for (line, reference, row) in parsed {
+        let outcome = row.and_then(|row| {
+            service
+                .register_sales(
+                    row.amount,
+                    row.date,
+                    row.section_id,
+                    Some(row.key),
+                    audit.clone(),
+                )
+                .map_err(|e| RowError::Rejected(e.to_string()))
+        });
+        report.rows.push(RowResult {
+            line,
+            reference,
+            outcome,
+        });
+    }
+
+    Ok(report)
+}
+
+type Parsed = (usize, Option<String>, Result<ParsedRow, RowError>);
+
+/// Writes every row in one batch if all of them passed. A row that failed
+/// keeps its error and the others are aborted; when the batch itself fails,
+/// every row carries its error.
+fn register_all<S, T, L, A>(
+    service: &mut AccountingService<S, T, L, A>,
+    parsed: Vec<Parsed>,
+    audit: &AuditInfo,
+) -> ImportReport
+where
+    S: SectionRepository,
+    T: TermRepository,
+    L: SalesRepository,
+    A: AuditLogRepository,
+{
+    let mut report = ImportReport::default();
+    if parsed.iter().any(|(_, _, r)| r.is_err()) {
+        for (line, reference, row) in parsed {
+            report.rows.push(RowResult {
+                line,
+                reference,
+                outcome: row.and(Err(RowError::Aborted)),
+            });
+        }
+        return report;
+    }
+
+    let mut rows = Vec::new();
+    let mut registrations = Vec::new();
+    for (line, reference, row) in parsed {
+        if let Ok(row) = row {
+            registrations.push(Registration {
+                amount: row.amount,
+                date: row.date,
+                section_id: row.section_id,
+                idempotency_key: Some(row.key),
+            });
+        }
+        rows.push((line, reference));
+    }
+    let outcomes: Vec<Result<Uuid, RowError>> =
+        match service.register_sales_batch(registrations, audit.clone()) {
+            Ok(ids) => ids.into_iter().map(Ok).collect(),
+            Err(e) => vec![Err(RowError::Rejected(e.to_string())); rows.len()],
+        };
+    for ((line, reference), outcome) in rows.into_iter().zip(outcomes) {
+        report.rows.push(RowResult {
+            line,
+            reference,
+            outcome,
+        });
+    }
+    report
+}
+
+fn parse_row<S, T, L, A>(
+    service: &AccountingService<S, T, L, A>,
+    raw: RawRow,
+) -> Result<ParsedRow, RowError>
+where
+    S: SectionRepository,
+    T: TermRepository,
+    L: SalesRepository,
+    A: AuditLogRepository,
+{
+    let date = parse_date_time(&raw.date).ok_or_else(|| RowError::InvalidDate(raw.date.clone()))?;
+    let amount = Decimal::from_str(&raw.amount)
+        .map(Money::new)
+        .map_err(|_| RowError::InvalidAmount(raw.amount.clone()))?;
+    let key = IdempotencyKey::new(raw.reference.clone())
+        .map_err(|e| RowError::InvalidReference(e.to_string()))?;
+    let section_id = resolve_section(service, &raw.section).map_err(|e| match e {
+        SectionRefError::Unknown(value) => RowError::UnknownSection(value),
+        SectionRefError::Ambiguous(value) => RowError::AmbiguousSection(value),
+    })?;
+
+    Ok(ParsedRow {
+        date,
+        section_id,
+        amount,
+        key,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::authorization::AccessPolicy;
+    use crate::domain::entity::Sales;
+    use crate::domain::entity::{Role, RoleGrant, Section, SectionType, Term};
+    use crate::domain::value_object::{ActorId, SectionCode};
+    use crate::infrastructure::in_memory::{
+        InMemoryAuditLogRepository, InMemorySalesRepository, InMemorySectionRepository,
+        InMemoryTermRepository,
+    };
+    use crate::test_support::{audit, audit_as, service, MemoryService};
+    use chrono::NaiveDate;
+
+    fn setup() -> MemoryService {
+        let mut service = service();
+
+        let section = Section::new(
+            service.ids().next_id(),
+            "Tokyo".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap()
+        .with_code(SectionCode::new("JP-TKY-01".to_string()).unwrap());
+        service.create_section(section, None, audit()).unwrap();
+
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        service.create_term(term, None, audit()).unwrap();
+        service
+    }
+
+    const CSV: &str = "date,section,amount,reference
+2025-06-01,Tokyo,100.00,ext-1
+2025-06-02T09:30:00,Osaka,50.00,ext-2
+2025-06-03,Tokyo,abc,ext-3
+2025-06-04,JP-TKY-01,25.00,ext-4
+";
+
+    #[test]
+    fn test_import_best_effort() {
+        let mut service = setup();
+
+        let report = import_sales(
+            &mut service,
+            CSV.as_bytes(),
+            ImportMode::BestEffort,
+            &audit(),
+        )
+        .unwrap();
+        assert_eq!(report.rows.len(), 4);
+        assert_eq!(report.accepted(), 2);
+        assert_eq!(
+            report.rows[1].outcome,
+            Err(RowError::UnknownSection("Osaka".to_string()))
+        );
+        assert_eq!(report.rows[2].line, 4);
+        assert_eq!(
+            report.rows[2].outcome,
+            Err(RowError::InvalidAmount("abc".to_string()))
+        );
+
+        // Re-importing the same file replays the original entries
+        let again = import_sales(
+            &mut service,
+            CSV.as_bytes(),
+            ImportMode::BestEffort,
+            &audit(),
+        )
+        .unwrap();
+        assert_eq!(again.rows[0].outcome, report.rows[0].outcome);
+        assert_eq!(again.rows[3].outcome, report.rows[3].outcome);
+    }
+
+    #[test]
+    fn test_import_all_or_nothing() {
+        let mut service = setup();
+
+        let report = import_sales(
+            &mut service,
+            CSV.as_bytes(),
+            ImportMode::AllOrNothing,
+            &audit(),
+        )
+        .unwrap();
+        assert_eq!(report.accepted(), 0);
+        assert_eq!(report.rows[0].outcome, Err(RowError::Aborted));
+
+        let valid = "date,section,amount,reference\n2025-06-01,Tokyo,100.00,ext-1\n";
+        let report = import_sales(
+            &mut service,
+            valid.as_bytes(),
+            ImportMode::AllOrNothing,
+            &audit(),
+        )
+        .unwrap();
+        assert_eq!(report.accepted(), 1);
+    }
+
+    #[test]
+    fn test_import_duplicate_reference() {
+        let mut service = setup();
+
+        let csv = "date,section,amount,reference
+2025-06-01,Tokyo,100.00,ext-1
+2025-06-02,Tokyo,100.00,ext-1
+";
+        let report = import_sales(
+            &mut service,
+            csv.as_bytes(),
+            ImportMode::BestEffort,
+            &audit(),
+        )
+        .unwrap();
+        assert!(report.rows[0].outcome.is_ok());
+        assert_eq!(
+            report.rows[1].outcome,
+            Err(RowError::DuplicateReference("ext-1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_import_all_or_nothing_checks_the_policy() {
+        let mut service = setup();
+        let kyoto = Section::new(
+            service.ids().next_id(),
+            "Kyoto".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let kyoto_id = service.create_section(kyoto, None, audit()).unwrap();
+        let clerk = ActorId::new("carol".to_string()).unwrap();
+        let mut service = service.with_policy(AccessPolicy::new(vec![RoleGrant::new(
+            Uuid::new_v4(),
+            clerk,
+            Role::Clerk,
+            Some(kyoto_id),
+        )]));
+
+        // carol may post to Kyoto but not to Tokyo, so neither row is written
+        let csv = "date,section,amount,reference
+2025-06-01,Kyoto,100.00,ext-1
+2025-06-02,Tokyo,100.00,ext-2
+";
+        let report = import_sales(
+            &mut service,
+            csv.as_bytes(),
+            ImportMode::AllOrNothing,
+            &audit_as("carol"),
+        )
+        .unwrap();
+        assert_eq!(report.rows[0].outcome, Err(RowError::Aborted));
+        assert!(matches!(report.rows[1].outcome, Err(RowError::Rejected(_))));
+        let term = service.find_open_term().unwrap();
+        assert!(service.list_sales(&term.id).is_empty());
+    }
+
+    /// A sales store with room for `room` more entries.
+    struct FillingSalesRepository {
+        inner: InMemorySalesRepository,
+        room: usize,
+    }
+
+    impl SalesRepository for FillingSalesRepository {
+        fn save(&mut self, sales: Sales) -> Result<(), String> {
+            self.save_all(vec![sales])
+        }
+
+        fn save_all(&mut self, sales: Vec<Sales>) -> Result<(), String> {
+            if sales.len() > self.room {
+                return Err("Store is full".to_string());
+            }
+            self.room -= sales.len();
+            self.inner.save_all(sales)
+        }
+
+        fn find_by_id(&self, id: &Uuid) -> Option<Sales> {
+            self.inner.find_by_id(id)
+        }
+
+        fn find_by_term(&self, term_id: &Uuid) -> Vec<Sales> {
+            self.inner.find_by_term(term_id)
+        }
+
+        fn find_by_section_and_term(&self, section_id: &Uuid, term_id: &Uuid) -> Vec<Sales> {
+            self.inner.find_by_section_and_term(section_id, term_id)
+        }
+
+        fn find_by_date_range(&self, from: NaiveDate, to: NaiveDate) -> Vec<Sales> {
+            self.inner.find_by_date_range(from, to)
+        }
+
+        fn iter_by_date(
+            &self,
+            from: NaiveDate,
+            to: NaiveDate,
+        ) -> Box<dyn Iterator<Item = Sales> + '_> {
+            self.inner.iter_by_date(from, to)
+        }
+
+        fn find_related(&self, sales_id: &Uuid) -> Vec<Sales> {
+            self.inner.find_related(sales_id)
+        }
+
+        fn find_by_idempotency_key(&self, key: &IdempotencyKey) -> Vec<Sales> {
+            self.inner.find_by_idempotency_key(key)
+        }
+    }
+
+    #[test]
+    fn test_import_all_or_nothing_writes_rows_together() {
+        let mut service = AccountingService::new(
+            InMemorySectionRepository::new(),
+            InMemoryTermRepository::new(),
+            FillingSalesRepository {
+                inner: InMemorySalesRepository::new(),
+                room: 2,
+            },
+            InMemoryAuditLogRepository::new(),
+        );
+        let tokyo = Section::new(
+            service.ids().next_id(),
+            "Tokyo".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        service.create_section(tokyo, None, audit()).unwrap();
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+
+        // Every row passes on its own; the third only fails once the first
+        // two are stored, so none of them may be
+        let csv = "date,section,amount,reference
+2025-06-01,Tokyo,100.00,ext-1
+2025-06-02,Tokyo,100.00,ext-2
+2025-06-03,Tokyo,100.00,ext-3
+";
+        let report = import_sales(
+            &mut service,
+            csv.as_bytes(),
+            ImportMode::AllOrNothing,
+            &audit(),
+        )
+        .unwrap();
+        assert_eq!(report.accepted(), 0);
+        assert_eq!(
+            report.rows[2].outcome,
+            Err(RowError::Rejected("Store is full".to_string()))
+        );
+        assert!(service.list_sales(&term_id).is_empty());
+
+        let two = "date,section,amount,reference
+2025-06-01,Tokyo,100.00,ext-1
+2025-06-02,Tokyo,100.00,ext-2
+";
+        let report = import_sales(
+            &mut service,
+            two.as_bytes(),
+            ImportMode::AllOrNothing,
+            &audit(),
+        )
+        .unwrap();
+        assert_eq!(report.accepted(), 2);
+        assert_eq!(service.list_sales(&term_id).len(), 2);
+    }
+}
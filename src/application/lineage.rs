@@ -0,0 +1,276 @@
+use crate::domain::entity::{Sales, SalesType};
+use crate::domain::repository::{SalesRepository, SectionRepository};
+use crate::domain::value_object::Money;
+use chrono::NaiveDateTime;
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Write;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct LineageNode {
+    pub sales_id: Uuid,
+    pub section_id: Uuid,
+    /// The section's name on the entry's date
+    pub section_name: Option<String>,
+    pub sales_type: SalesType,
+    pub amount: Money,
+    pub date: NaiveDateTime,
+}
+
+#[derive(Debug, Clone)]
+pub struct SalesLineage {
+    /// The original entry the derivation tree starts from.
+    pub root_id: Uuid,
+    pub nodes: Vec<LineageNode>,
+    /// Edges as (parent, child), following `related_sales_id` and from the
+    /// anchor leg of a merge to its other leg.
+    pub edges: Vec<(Uuid, Uuid)>,
+}
+
+impl SalesLineage {
+    pub fn node(&self, sales_id: &Uuid) -> Option<&LineageNode> {
+        self.nodes.iter().find(|n| n.sales_id == *sales_id)
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        out.push_str("digraph sales_lineage {\n");
+        out.push_str("    node [shape=box];\n");
+        for node in &self.nodes {
+            let section = node
+                .section_name
+                .clone()
+                .unwrap_or_else(|| node.section_id.to_string());
+            // The original entry is drawn with a double border.
+            let peripheries = if node.sales_id == self.root_id { 2 } else { 1 };
+            let _ = writeln!(
+                out,
+                "    \"{}\" [label=\"{:?}\\n{}\\n{}\\n{}\", peripheries={}];",
+                node.sales_id,
+                node.sales_type,
+                escape_dot(&section),
+                node.amount,
+                node.date,
+                peripheries
+            );
+        }
+        for (parent, child) in &self.edges {
+            let _ = writeln!(out, "    \"{}\" -> \"{}\";", parent, child);
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// The entry `sales` derives from: the related sale of a transfer leg, or the
+/// anchor leg of a merge for its other leg.
+fn parent_of(sales: &Sales) -> Option<Uuid> {
+    sales
+        .related_sales_id
+        .or(sales.merge_anchor_id.filter(|id| *id != sales.id))
+}
+
+/// Escapes a value for a quoted DOT label, so it stays on one line.
+fn escape_dot(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+pub struct LineageService<'a, S, L>
+where
+    S: SectionRepository,
+    L: SalesRepository,
+{
+    section_repo: &'a S,
+    sales_repo: &'a L,
+}
+
+impl<'a, S, L> LineageService<'a, S, L>
+where
+    S: SectionRepository,
+    L: SalesRepository,
+{
+    pub fn new(section_repo: &'a S, sales_repo: &'a L) -> Self {
+        Self {
+            section_repo,
+            sales_repo,
+        }
+    }
+
+    /// Builds the full derivation graph containing `sales_id`, starting from
+    /// its original entry regardless of which node in the tree was given.
+    pub fn lineage(&self, sales_id: Uuid) -> Result<SalesLineage, String> {
+        let root = self.find_root(sales_id)?;
+
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::new();
+        visited.insert(root.id);
+        queue.push_back(root);
+
+        while let Some(sales) = queue.pop_front() {
+            let mut children = self.sales_repo.find_related(&sales.id);
+            if sales.merge_anchor_id == Some(sales.id) {
+                let day = sales.date.date();
+                children.extend(
+                    self.sales_repo
+                        .iter_by_date(day, day)
+                        .filter(|s| s.merge_anchor_id == Some(sales.id) && s.id != sales.id),
+                );
+            }
+            children.sort_by(|a, b| a.date.cmp(&b.date).then(a.id.cmp(&b.id)));
+            for child in children {
+                if visited.insert(child.id) {
+                    edges.push((sales.id, child.id));
+                    queue.push_back(child);
+                }
+            }
+            nodes.push(self.to_node(sales));
+        }
+
+        Ok(SalesLineage {
+            root_id: nodes[0].sales_id,
+            nodes,
+            edges,
+        })
+    }
+
+    fn find_root(&self, sales_id: Uuid) -> Result<Sales, String> {
+        let mut current = self
+            .sales_repo
+            .find_by_id(&sales_id)
+            .ok_or("Sales not found")?;
+        let mut seen = HashSet::new();
+        seen.insert(current.id);
+        while let Some(parent_id) = parent_of(&current) {
+            let parent = self
+                .sales_repo
+                .find_by_id(&parent_id)
+                .ok_or("Related sales not found")?;
+            if !seen.insert(parent.id) {
+                return Err("Cycle detected in sales lineage".to_string());
+            }
+            current = parent;
+        }
+        Ok(current)
+    }
+
+    fn to_node(&self, sales: Sales) -> LineageNode {
+        LineageNode {
+            sales_id: sales.id,
+            section_id: sales.section_id,
+            section_name: self
+                .section_repo
+                .find_by_id(&sales.section_id)
+                .map(|s| s.name_on(sales.date.date()).to_string()),
+            sales_type: sales.sales_type,
+            amount: sales.amount,
+            date: sales.date,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::entity::{SalesType, Section, SectionType, Term};
+    use crate::domain::value_object::Money;
+    use crate::test_support::{audit, service};
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_lineage_follows_transfers() {
+        let mut service = service();
+
+        let section_a = Section::new(
+            service.ids().next_id(),
+            "Section A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_a_id = service.create_section(section_a, None, audit()).unwrap();
+        let section_b = Section::new(
+            service.ids().next_id(),
+            "Section B".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_b_id = service.create_section(section_b, None, audit()).unwrap();
+
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        service.create_term(term, None, audit()).unwrap();
+
+        let amount = Money::new(Decimal::from_str("100.00").unwrap());
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        let sales_id = service
+            .register_sales(amount, date, section_a_id, None, audit())
+            .unwrap();
+        let new_id = service
+            .transform_sales(sales_id, section_b_id, date, None, audit())
+            .unwrap();
+
+        // Starting from a derived entry still yields the whole tree.
+        let lineage = service.lineage().lineage(new_id).unwrap();
+        assert_eq!(lineage.root_id, sales_id);
+        assert_eq!(lineage.nodes.len(), 3);
+        assert_eq!(lineage.edges.len(), 2);
+
+        let moved = lineage.node(&new_id).unwrap();
+        assert_eq!(moved.section_name.as_deref(), Some("Section B"));
+        assert_eq!(moved.sales_type, SalesType::Adjustment);
+
+        let dot = lineage.to_dot();
+        assert!(dot.starts_with("digraph sales_lineage {"));
+        assert!(dot.contains(&format!("\"{}\" -> \"{}\"", sales_id, new_id)));
+
+        // Later renames do not relabel the entries, and a line break in a
+        // name stays inside its quoted label
+        service
+            .rename_section(
+                section_b_id,
+                "Section \"B\"\nWest".to_string(),
+                NaiveDate::from_ymd_opt(2025, 7, 1).unwrap(),
+                None,
+                audit(),
+            )
+            .unwrap();
+        let lineage = service.lineage().lineage(new_id).unwrap();
+        let moved = lineage.node(&new_id).unwrap();
+        assert_eq!(moved.section_name.as_deref(), Some("Section B"));
+        service
+            .rename_section(
+                section_a_id,
+                "Section \"A\"\r\nEast".to_string(),
+                NaiveDate::from_ymd_opt(2025, 5, 1).unwrap(),
+                None,
+                audit(),
+            )
+            .unwrap();
+        let dot = service.lineage().lineage(new_id).unwrap().to_dot();
+        assert!(dot.contains("Section \\\"A\\\"\\r\\nEast"));
+        assert_eq!(dot.lines().count(), 8);
+    }
+
+    #[test]
+    fn test_lineage_unknown_sales() {
+        let service = service();
+
+        let result = service.lineage().lineage(uuid::Uuid::new_v4());
+        assert_eq!(result.unwrap_err(), "Sales not found");
+    }
+}
@@ -1 +1,14 @@
+pub mod approval;
+pub mod audit;
+pub mod authorization;
+pub mod checklist;
+pub mod export;
+pub mod hierarchy;
+pub mod import;
+pub mod lineage;
+pub mod preview;
+pub mod reference;
+pub mod render;
+pub mod report;
 pub mod service;
+pub mod trace;
@@ -0,0 +1,27 @@
+use crate::domain::entity::{Sales, Section, Term};
+use crate::domain::value_object::Money;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// How one section's term total would move if the planned entries were written.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BalanceChange {
+    pub term_id: Uuid,
+    pub section_id: Uuid,
+    pub section_name: Option<String>,
+    pub before: Money,
+    pub after: Money,
+    pub change: Money,
+}
+
+/// Records a mutating operation would write, computed without persisting
+/// anything. `replayed` is set when the idempotency key matches an earlier
+/// request, in which case nothing new would be written.
+#[derive(Debug, Clone, Serialize)]
+pub struct Preview {
+    pub replayed: bool,
+    pub sections: Vec<Section>,
+    pub terms: Vec<Term>,
+    pub sales: Vec<Sales>,
+    pub balances: Vec<BalanceChange>,
+}
@@ -0,0 +1,108 @@
+use super::service::AccountingService;
+use crate::domain::repository::{
+    AuditLogRepository, SalesRepository, SectionRepository, TermRepository,
+};
+use crate::domain::value_object::SectionCode;
+use chrono::{NaiveDate, NaiveDateTime};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Why a section reference did not name exactly one section.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SectionRefError {
+    #[error("Section not found: {0}")]
+    Unknown(String),
+    #[error("Section name is ambiguous, use the ID: {0}")]
+    Ambiguous(String),
+}
+
+/// Resolves a section given by ID, by code or by name, in that order. A name
+/// must be unique.
+pub fn resolve_section<S, T, L, A>(
+    service: &AccountingService<S, T, L, A>,
+    reference: &str,
+) -> Result<Uuid, SectionRefError>
+where
+    S: SectionRepository,
+    T: TermRepository,
+    L: SalesRepository,
+    A: AuditLogRepository,
+{
+    if let Ok(id) = Uuid::parse_str(reference) {
+        if service.find_section(&id).is_some() {
+            return Ok(id);
+        }
+    }
+    if let Some(section) = SectionCode::new(reference.to_string())
+        .ok()
+        .and_then(|code| service.find_section_by_code(&code))
+    {
+        return Ok(section.id);
+    }
+    match service.find_sections_by_name(reference).as_slice() {
+        [section] => Ok(section.id),
+        [] => Err(SectionRefError::Unknown(reference.to_string())),
+        _ => Err(SectionRefError::Ambiguous(reference.to_string())),
+    }
+}
+
+/// Accepts `YYYY-MM-DD`.
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+/// Accepts `YYYY-MM-DD` (taken as midnight) or a full date-time.
+pub fn parse_date_time(value: &str) -> Option<NaiveDateTime> {
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| parse_date(value).and_then(|d| d.and_hms_opt(0, 0, 0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entity::{Section, SectionType};
+    use crate::test_support::{audit, service};
+
+    #[test]
+    fn test_sections_resolve_by_id_code_or_unique_name() {
+        let mut service = service();
+        let mut ids = Vec::new();
+        for code in ["JP-TKY-01", "JP-TKY-02"] {
+            let section = Section::new(
+                service.ids().next_id(),
+                "Tokyo".to_string(),
+                SectionType::Section,
+                None,
+            )
+            .unwrap()
+            .with_code(SectionCode::new(code.to_string()).unwrap());
+            ids.push(service.create_section(section, None, audit()).unwrap());
+        }
+
+        assert_eq!(resolve_section(&service, &ids[0].to_string()), Ok(ids[0]));
+        assert_eq!(resolve_section(&service, "JP-TKY-02"), Ok(ids[1]));
+        assert_eq!(
+            resolve_section(&service, "Tokyo"),
+            Err(SectionRefError::Ambiguous("Tokyo".to_string()))
+        );
+        assert_eq!(
+            resolve_section(&service, "Osaka"),
+            Err(SectionRefError::Unknown("Osaka".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_dates_take_a_day_or_a_time() {
+        let day = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        assert_eq!(parse_date("2025-06-01"), Some(day));
+        assert_eq!(parse_date("2025-06-01T09:30:00"), None);
+        assert_eq!(parse_date_time("2025-06-01"), day.and_hms_opt(0, 0, 0));
+        assert_eq!(
+            parse_date_time("2025-06-01T09:30:00"),
+            day.and_hms_opt(9, 30, 0)
+        );
+        assert_eq!(parse_date_time("06/01/2025"), None);
+    }
+}
@@ -0,0 +1,546 @@
+use super::report::{
+    IntegrityReport, MovementLine, SalesSeries, Structure, TermComparison, TermMovement,
+    TermRollup, TermSummary, VarianceStatus,
+};
+use rust_decimal::Decimal;
+
+/// A titled table of text cells, the common shape every report renders to.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Table {
+    pub title: Option<String>,
+    pub headers: Vec<String>,
+    pub rows: Vec<Row>,
+    /// Lines shown after the table, such as tie-out issues
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub cells: Vec<String>,
+    /// Position in the section hierarchy; rows deeper than the row before
+    /// them are its children. 0 for flat tables.
+    pub depth: usize,
+}
+
+impl Table {
+    pub fn new(headers: &[&str]) -> Self {
+        Self {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_title(mut self, title: String) -> Self {
+        self.title = Some(title);
+        self
+    }
+
+    pub fn push(&mut self, cells: Vec<String>) {
+        self.push_nested(cells, 0);
+    }
+
+    pub fn push_nested(&mut self, cells: Vec<String>, depth: usize) {
+        self.rows.push(Row { cells, depth });
+    }
+
+    /// Whether the row at `index` has rows below it in the hierarchy.
+    fn has_children(&self, index: usize) -> bool {
+        self.rows
+            .get(index + 1)
+            .is_some_and(|next| next.depth > self.rows[index].depth)
+    }
+}
+
+/// Turns a `Table` into a document in some output format.
+pub trait Renderer {
+    fn render(&self, table: &Table) -> String;
+}
+
+/// Report results that can be laid out as a `Table`.
+pub trait ToTable {
+    fn to_table(&self) -> Table;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderFormat {
+    /// Aligned plain-text columns
+    #[default]
+    Text,
+    /// A GitHub-flavored Markdown table
+    Markdown,
+    /// A self-contained HTML page; hierarchical rows can be collapsed
+    Html,
+}
+
+impl RenderFormat {
+    pub fn renderer(self) -> Box<dyn Renderer> {
+        match self {
+            RenderFormat::Text => Box::new(TextRenderer),
+            RenderFormat::Markdown => Box::new(MarkdownRenderer),
+            RenderFormat::Html => Box::new(HtmlRenderer),
+        }
+    }
+}
+
+pub struct TextRenderer;
+
+impl Renderer for TextRenderer {
+    fn render(&self, table: &Table) -> String {
+        let cells: Vec<Vec<String>> = table
+            .rows
+            .iter()
+            .map(|row| indented(&row.cells, row.depth, "  "))
+            .collect();
+        let mut widths: Vec<usize> = table.headers.iter().map(|h| h.chars().count()).collect();
+        for row in &cells {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let format_row = |cells: &[String]| {
+            let line: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            format!("{}\n", line.join("  ").trim_end())
+        };
+
+        let mut out = String::new();
+        if let Some(title) = &table.title {
+            out.push_str(&format!("{}\n", title));
+        }
+        let separator: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+        out.push_str(&format_row(&table.headers));
+        out.push_str(&format_row(&separator));
+        for row in &cells {
+            out.push_str(&format_row(row));
+        }
+        for note in &table.notes {
+            out.push_str(&format!("{}\n", note));
+        }
+        out
+    }
+}
+
+pub struct MarkdownRenderer;
+
+impl Renderer for MarkdownRenderer {
+    fn render(&self, table: &Table) -> String {
+        let escaped = |cells: &[String]| -> Vec<String> {
+            cells.iter().map(|c| escape_markdown_cell(c)).collect()
+        };
+        let format_row = |cells: &[String]| format!("| {} |\n", cells.join(" | "));
+
+        let mut out = String::new();
+        if let Some(title) = &table.title {
+            out.push_str(&format!("## {}\n\n", title));
+        }
+        out.push_str(&format_row(&escaped(&table.headers)));
+        out.push_str(&format!("|{}\n", " --- |".repeat(table.headers.len())));
+        for row in &table.rows {
+            // Leading spaces would be collapsed, so indent with em spaces
+            let cells = indented(&escaped(&row.cells), row.depth, "&emsp;");
+            out.push_str(&format_row(&cells));
+        }
+        if !table.notes.is_empty() {
+            out.push('\n');
+            for note in &table.notes {
+                out.push_str(&format!("- {}\n", note));
+            }
+        }
+        out
+    }
+}
+
+/// Renders a complete page with inline styles and script, so the file can be
+/// opened or mailed without anything else. Rows with children get a toggle
+/// that hides or shows everything below them.
+pub struct HtmlRenderer;
+
+const HTML_STYLE: &str = "body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { padding: 0.25em 0.75em; border-bottom: 1px solid #ddd; text-align: left; }
+th { background: #f4f4f4; }
+tr.hidden { display: none; }
+button.toggle { border: none; background: none; cursor: pointer; width: 1.5em; padding: 0; }
+span.leaf { display: inline-block; width: 1.5em; }";
+
+const HTML_SCRIPT: &str = "document.querySelectorAll('button.toggle').forEach(function (button) {
+  button.addEventListener('click', function () {
+    var row = button.closest('tr');
+    var depth = Number(row.dataset.depth);
+    var collapse = button.textContent === '\\u25be';
+    button.textContent = collapse ? '\\u25b8' : '\\u25be';
+    for (var next = row.nextElementSibling; next && Number(next.dataset.depth) > depth;
+         next = next.nextElementSibling) {
+      next.classList.toggle('hidden', collapse);
+      var inner = next.querySelector('button.toggle');
+      if (inner) { inner.textContent = '\\u25be'; }
+    }
+  });
+});";
+
+impl Renderer for HtmlRenderer {
+    fn render(&self, table: &Table) -> String {
+        let title = table.title.as_deref().unwrap_or("Report");
+        let mut out = String::new();
+        out.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        out.push_str(&format!("<title>{}</title>\n", escape_html(title)));
+        out.push_str(&format!(
+            "<style>\n{}\n</style>\n</head>\n<body>\n",
+            HTML_STYLE
+        ));
+        out.push_str(&format!(
+            "<h1>{}</h1>\n<table>\n<thead>\n<tr>",
+            escape_html(title)
+        ));
+        for header in &table.headers {
+            out.push_str(&format!("<th>{}</th>", escape_html(header)));
+        }
+        out.push_str("</tr>\n</thead>\n<tbody>\n");
+
+        let nested = table.rows.iter().any(|row| row.depth > 0);
+        for (index, row) in table.rows.iter().enumerate() {
+            out.push_str(&format!("<tr data-depth=\"{}\">", row.depth));
+            for (column, cell) in row.cells.iter().enumerate() {
+                if column == 0 && nested {
+                    let marker = if table.has_children(index) {
+                        "<button class=\"toggle\">\u{25be}</button>"
+                    } else {
+                        "<span class=\"leaf\"></span>"
+                    };
+                    out.push_str(&format!(
+                        "<td style=\"padding-left: {}em\">{}{}</td>",
+                        0.75 + 1.5 * row.depth as f64,
+                        marker,
+                        escape_html(cell)
+                    ));
+                } else {
+                    out.push_str(&format!("<td>{}</td>", escape_html(cell)));
+                }
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</tbody>\n</table>\n");
+
+        if !table.notes.is_empty() {
+            out.push_str("<ul>\n");
+            for note in &table.notes {
+                out.push_str(&format!("<li>{}</li>\n", escape_html(note)));
+            }
+            out.push_str("</ul>\n");
+        }
+        out.push_str(&format!(
+            "<script>\n{}\n</script>\n</body>\n</html>\n",
+            HTML_SCRIPT
+        ));
+        out
+    }
+}
+
+/// `cells` with the first one indented `depth` times.
+fn indented(cells: &[String], depth: usize, indent: &str) -> Vec<String> {
+    let mut cells = cells.to_vec();
+    if let Some(first) = cells.first_mut() {
+        *first = format!("{}{}", indent.repeat(depth), first);
+    }
+    cells
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Markdown passes inline HTML through, so cells are escaped as in
+/// `HtmlRenderer`; pipes and line breaks would otherwise end the cell or row.
+fn escape_markdown_cell(text: &str) -> String {
+    escape_html(text)
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace(['\r', '\n'], "<br>")
+}
+
+fn percent(pct: Option<Decimal>) -> String {
+    pct.map(|p| format!("{}%", p)).unwrap_or_default()
+}
+
+impl ToTable for TermSummary {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new(&["Section", "Normal", "Adjustment", "Correction", "Total"])
+            .with_title(format!(
+                "Term {} ({} to {}, {:?})",
+                self.term_id, self.start_date, self.end_date, self.status
+            ));
+        for s in &self.sections {
+            table.push(vec![
+                s.section_name
+                    .clone()
+                    .unwrap_or_else(|| s.section_id.to_string()),
+                s.normal.to_string(),
+                s.adjustment.to_string(),
+                s.correction.to_string(),
+                s.total.to_string(),
+            ]);
+        }
+        table.push(vec![
+            "Total".to_string(),
+            String::new(),
+            String::new(),
+            String::new(),
+            self.total.to_string(),
+        ]);
+        table
+    }
+}
+
+impl ToTable for TermRollup {
+    fn to_table(&self) -> Table {
+        let structure = match self.structure {
+            Structure::AsOf => "term-end",
+            Structure::Restated => "current",
+        };
+        let mut table = Table::new(&["Section", "Own", "Total"]).with_title(format!(
+            "Term {} ({} to {}, {} structure)",
+            self.term_id, self.start_date, self.end_date, structure
+        ));
+        for l in &self.lines {
+            table.push_nested(
+                vec![l.name.clone(), l.own.to_string(), l.total.to_string()],
+                l.depth,
+            );
+        }
+        table.push(vec![
+            "Total".to_string(),
+            String::new(),
+            self.total.to_string(),
+        ]);
+        table
+    }
+}
+
+impl ToTable for TermComparison {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new(&["Section", "Base", "Current", "Variance", "%", "Status"])
+            .with_title(format!(
+                "Term {} compared with {}",
+                self.current_term_id, self.base_term_id
+            ));
+        for l in &self.lines {
+            let status = match l.status {
+                VarianceStatus::Continuing => String::new(),
+                status => format!("{:?}", status),
+            };
+            table.push_nested(
+                vec![
+                    l.name.clone(),
+                    l.base.to_string(),
+                    l.current.to_string(),
+                    l.variance.to_string(),
+                    percent(l.variance_pct),
+                    status,
+                ],
+                l.depth,
+            );
+        }
+        table.push(vec![
+            "Total".to_string(),
+            self.base_total.to_string(),
+            self.current_total.to_string(),
+            self.variance.to_string(),
+            percent(self.variance_pct),
+            String::new(),
+        ]);
+        table
+    }
+}
+
+impl ToTable for TermMovement {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new(&[
+            "Section",
+            "Opening",
+            "Transfers In",
+            "Transfers Out",
+            "Adjustments",
+            "Corrections",
+            "Closing",
+            "Ties Out",
+        ])
+        .with_title(format!(
+            "Term {} ({} to {})",
+            self.term_id, self.start_date, self.end_date
+        ));
+        let row = |name: String, l: &MovementLine| {
+            vec![
+                name,
+                l.opening.to_string(),
+                l.transfers_in.to_string(),
+                l.transfers_out.to_string(),
+                l.adjustments.to_string(),
+                l.corrections.to_string(),
+                l.closing.to_string(),
+                if l.ties_out() { "yes" } else { "NO" }.to_string(),
+            ]
+        };
+        let label = |l: &MovementLine| {
+            l.section_name
+                .clone()
+                .unwrap_or_else(|| l.section_id.to_string())
+        };
+        for l in &self.sections {
+            table.push(row(label(l), l));
+        }
+        table.push(row("Total".to_string(), &self.total));
+
+        let flagged = self
+            .exceptions()
+            .map(|l| (label(l), l))
+            .chain(Some(("Total".to_string(), &self.total)).filter(|(_, t)| !t.ties_out()));
+        for (name, line) in flagged {
+            for issue in &line.issues {
+                table.notes.push(format!("{}: {}", name, issue));
+            }
+        }
+        table
+    }
+}
+
+impl ToTable for SalesSeries {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new(&["From", "To", "Entries", "Total"]).with_title(format!(
+            "{:?} totals {} to {}",
+            self.interval, self.from, self.to
+        ));
+        for p in &self.points {
+            table.push(vec![
+                p.start.to_string(),
+                p.end.to_string(),
+                p.entries.to_string(),
+                p.total.to_string(),
+            ]);
+        }
+        table.push(vec![
+            "Total".to_string(),
+            String::new(),
+            self.points
+                .iter()
+                .map(|p| p.entries)
+                .sum::<usize>()
+                .to_string(),
+            self.total.to_string(),
+        ]);
+        table
+    }
+}
+
+impl ToTable for IntegrityReport {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new(&["Rule", "Violation", "IDs"]).with_title(format!(
+            "Term {} ({} to {}): {} entries checked",
+            self.term_id, self.start_date, self.end_date, self.entries_checked
+        ));
+        for v in &self.violations {
+            let ids: Vec<String> = v.entity_ids.iter().map(ToString::to_string).collect();
+            table.push(vec![
+                format!("{:?}", v.rule),
+                v.message.clone(),
+                ids.join(" "),
+            ]);
+        }
+        table.notes.push(if self.is_consistent() {
+            "No violations".to_string()
+        } else {
+            format!("{} violation(s)", self.violations.len())
+        });
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_object::Money;
+    use chrono::NaiveDate;
+    use uuid::Uuid;
+
+    fn hierarchy() -> Table {
+        let mut table = Table::new(&["Section", "Total"]).with_title("Rollup".to_string());
+        table.push_nested(vec!["Division".to_string(), "30".to_string()], 0);
+        table.push_nested(vec!["A | B".to_string(), "30".to_string()], 1);
+        table.push(vec!["Total".to_string(), "30".to_string()]);
+        table
+    }
+
+    #[test]
+    fn test_text_aligns_columns() {
+        let mut table = Table::new(&["Name", "Total"]);
+        table.push(vec!["Tokyo".to_string(), "1".to_string()]);
+        assert_eq!(
+            TextRenderer.render(&table),
+            "Name   Total\n-----  -----\nTokyo  1\n"
+        );
+        assert!(TextRenderer
+            .render(&hierarchy())
+            .contains("\n  A | B   30\n"));
+    }
+
+    #[test]
+    fn test_movement_notes_name_unknown_sections_by_id() {
+        let line = |section_id, issues: &[&str]| MovementLine {
+            section_id,
+            section_name: None,
+            opening: Money::zero(),
+            transfers_in: Money::zero(),
+            transfers_out: Money::zero(),
+            adjustments: Money::zero(),
+            corrections: Money::zero(),
+            closing: Money::zero(),
+            issues: issues.iter().map(|i| i.to_string()).collect(),
+        };
+        let section_id = Uuid::from_u128(1);
+        let movement = TermMovement {
+            term_id: Uuid::from_u128(2),
+            start_date: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+            sections: vec![line(section_id, &["Transfer of unknown sale"])],
+            total: line(Uuid::from_u128(2), &["Transfers do not match"]),
+        };
+        assert_eq!(
+            movement.to_table().notes,
+            vec![
+                format!("{}: Transfer of unknown sale", section_id),
+                "Total: Transfers do not match".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_markdown_and_html_keep_hierarchy() {
+        let markdown = MarkdownRenderer.render(&hierarchy());
+        assert!(markdown.starts_with("## Rollup\n\n| Section | Total |\n| --- | --- |\n"));
+        assert!(markdown.contains("| &emsp;A \\| B | 30 |"));
+
+        let html = HtmlRenderer.render(&hierarchy());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(!html.contains("<script src") && !html.contains("<link"));
+        // Only the division has rows below it to collapse
+        assert_eq!(html.matches("<button class=\"toggle\">").count(), 1);
+        assert!(html.contains("<tr data-depth=\"1\">"));
+    }
+
+    #[test]
+    fn test_markdown_cells_escape_html_and_line_breaks() {
+        let mut table = Table::new(&["Section", "Note"]);
+        table.push(vec![
+            "<b>A&B</b>".to_string(),
+            "one\r\ntwo\nthree".to_string(),
+        ]);
+        let markdown = MarkdownRenderer.render(&table);
+        assert!(markdown.contains("| &lt;b&gt;A&amp;B&lt;/b&gt; | one<br>two<br>three |\n"));
+    }
+}
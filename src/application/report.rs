@@ -0,0 +1,1785 @@
+use super::export::{ExportError, ExportFormat, ExportScope};
+use super::service::ServiceError;
+use crate::domain::entity::{Sales, SalesType, TermStatus};
+use crate::domain::repository::{SalesRepository, SectionRepository, TermRepository};
+use crate::domain::value_object::Money;
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use uuid::Uuid;
+
+/// Term total of one section, split by `SalesType`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SectionTotal {
+    pub section_id: Uuid,
+    pub section_name: Option<String>,
+    pub normal: Money,
+    pub adjustment: Money,
+    pub correction: Money,
+    pub total: Money,
+}
+
+impl SectionTotal {
+    fn new(section_id: Uuid, section_name: Option<String>) -> Self {
+        Self {
+            section_id,
+            section_name,
+            normal: Money::zero(),
+            adjustment: Money::zero(),
+            correction: Money::zero(),
+            total: Money::zero(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TermSummary {
+    pub term_id: Uuid,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub status: TermStatus,
+    pub sections: Vec<SectionTotal>,
+    pub total: Money,
+}
+
+/// Which organization structure a rollup follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Structure {
+    /// Names and parents as they were at the end of the term
+    AsOf,
+    /// Today's names and parents, to compare past terms with current ones
+    Restated,
+}
+
+/// One section of a rollup with its own entries and those of its subtree.
+#[derive(Debug, Clone, Serialize)]
+pub struct RollupLine {
+    pub section_id: Uuid,
+    pub name: String,
+    pub parent_id: Option<Uuid>,
+    /// 0 for top-level sections
+    pub depth: usize,
+    pub own: Money,
+    pub total: Money,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TermRollup {
+    pub term_id: Uuid,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub structure: Structure,
+    /// Depth-first, children after their parent and ordered by name
+    pub lines: Vec<RollupLine>,
+    pub total: Money,
+}
+
+/// How a section's subtree fared between the two compared terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum VarianceStatus {
+    /// Entries in both terms
+    Continuing,
+    /// Entries only in the current term
+    New,
+    /// Entries only in the base term
+    Disappeared,
+}
+
+/// One section of a comparison, with subtree totals for both terms.
+#[derive(Debug, Clone, Serialize)]
+pub struct VarianceLine {
+    pub section_id: Uuid,
+    pub name: String,
+    pub parent_id: Option<Uuid>,
+    /// 0 for top-level sections
+    pub depth: usize,
+    pub base: Money,
+    pub current: Money,
+    /// `current - base`
+    pub variance: Money,
+    /// Variance as a percentage of the base total, or `None` when the base
+    /// total is zero
+    pub variance_pct: Option<Decimal>,
+    pub status: VarianceStatus,
+}
+
+impl VarianceLine {
+    fn new(line: &RollupLine, base: Option<Money>, current: Option<Money>) -> Self {
+        let status = match (base, current) {
+            (None, Some(_)) => VarianceStatus::New,
+            (Some(_), None) => VarianceStatus::Disappeared,
+            _ => VarianceStatus::Continuing,
+        };
+        let base = base.unwrap_or_else(Money::zero);
+        let current = current.unwrap_or_else(Money::zero);
+        Self {
+            section_id: line.section_id,
+            name: line.name.clone(),
+            parent_id: line.parent_id,
+            depth: line.depth,
+            base,
+            current,
+            variance: current - base,
+            variance_pct: variance_pct(base, current),
+            status,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TermComparison {
+    pub base_term_id: Uuid,
+    pub current_term_id: Uuid,
+    /// Depth-first, children after their parent and ordered by name
+    pub lines: Vec<VarianceLine>,
+    pub base_total: Money,
+    pub current_total: Money,
+    pub variance: Money,
+    pub variance_pct: Option<Decimal>,
+}
+
+impl TermComparison {
+    /// Writes the lines as CSV, or the whole comparison as JSON. Returns the
+    /// number of lines written.
+    pub fn write<W: Write>(
+        &self,
+        format: ExportFormat,
+        mut writer: W,
+    ) -> Result<usize, ExportError> {
+        match format {
+            ExportFormat::Csv => {
+                let mut csv_writer = csv::Writer::from_writer(writer);
+                for line in &self.lines {
+                    csv_writer.serialize(line)?;
+                }
+                // An empty comparison still gets a header line
+                if self.lines.is_empty() {
+                    csv_writer.write_record([
+                        "section_id",
+                        "name",
+                        "parent_id",
+                        "depth",
+                        "base",
+                        "current",
+                        "variance",
+                        "variance_pct",
+                        "status",
+                    ])?;
+                }
+                csv_writer.flush()?;
+            }
+            ExportFormat::Json => {
+                serde_json::to_writer_pretty(&mut writer, self)?;
+                writer.write_all(b"\n")?;
+                writer.flush()?;
+            }
+        }
+        Ok(self.lines.len())
+    }
+}
+
+/// Roll-forward of one section over a term: opening + transfers in -
+/// transfers out + adjustments + corrections = closing.
+#[derive(Debug, Clone, Serialize)]
+pub struct MovementLine {
+    pub section_id: Uuid,
+    pub section_name: Option<String>,
+    /// Normal sales
+    pub opening: Money,
+    pub transfers_in: Money,
+    /// Entries transferred away, as a positive amount
+    pub transfers_out: Money,
+    /// Adjustments other than transfers, such as merges
+    pub adjustments: Money,
+    pub corrections: Money,
+    /// The section's balance for the term, the sum of the components
+    pub closing: Money,
+    /// Why the section's transfers do not tie out, empty when they do
+    pub issues: Vec<String>,
+}
+
+impl MovementLine {
+    fn new(section_id: Uuid, section_name: Option<String>) -> Self {
+        Self {
+            section_id,
+            section_name,
+            opening: Money::zero(),
+            transfers_in: Money::zero(),
+            transfers_out: Money::zero(),
+            adjustments: Money::zero(),
+            corrections: Money::zero(),
+            closing: Money::zero(),
+            issues: Vec::new(),
+        }
+    }
+
+    /// The closing balance the components add up to.
+    pub fn computed(&self) -> Money {
+        self.opening + self.transfers_in - self.transfers_out + self.adjustments + self.corrections
+    }
+
+    pub fn ties_out(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TermMovement {
+    pub term_id: Uuid,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    /// Ordered by section name
+    pub sections: Vec<MovementLine>,
+    /// Column totals; transfers in and out match when every transfer is whole
+    pub total: MovementLine,
+}
+
+impl TermMovement {
+    /// Sections whose transfers do not tie out.
+    pub fn exceptions(&self) -> impl Iterator<Item = &MovementLine> {
+        self.sections.iter().filter(|s| !s.ties_out())
+    }
+}
+
+/// Most points `ReportService::sales_series` returns; longer series are
+/// rejected rather than built.
+pub const MAX_SERIES_POINTS: u64 = 10_000;
+
+/// Bucket width of a `SalesSeries`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Interval {
+    Day,
+    /// ISO weeks, starting on Monday
+    Week,
+    /// Calendar months
+    Month,
+}
+
+impl Interval {
+    /// First day of the bucket containing `date`.
+    fn bucket_start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Interval::Day => date,
+            Interval::Week => date
+                .checked_sub_days(Days::new(u64::from(date.weekday().num_days_from_monday())))
+                .unwrap_or(NaiveDate::MIN),
+            Interval::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// Number of buckets from the one containing `from` to the one
+    /// containing `to`, for `from <= to`.
+    fn bucket_count(self, from: NaiveDate, to: NaiveDate) -> u64 {
+        let days = (to - self.bucket_start(from)).num_days().unsigned_abs();
+        match self {
+            Interval::Day => days + 1,
+            Interval::Week => days / 7 + 1,
+            Interval::Month => {
+                let months =
+                    |date: NaiveDate| i64::from(date.year()) * 12 + i64::from(date.month0());
+                (months(to) - months(from)).unsigned_abs() + 1
+            }
+        }
+    }
+
+    /// First day of the bucket after the one starting on `start`.
+    fn next(self, start: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Interval::Day => start.checked_add_days(Days::new(1)),
+            Interval::Week => start.checked_add_days(Days::new(7)),
+            Interval::Month => start.checked_add_months(Months::new(1)),
+        }
+    }
+}
+
+/// Entries dated within `[start, end]`. The first and last buckets are cut
+/// to the requested range, so they may be shorter than the interval.
+#[derive(Debug, Clone, Serialize)]
+pub struct SeriesPoint {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub total: Money,
+    pub entries: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SalesSeries {
+    /// `None` for every section
+    pub section_id: Option<Uuid>,
+    pub include_subtree: bool,
+    pub interval: Interval,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// One per bucket from `from` to `to`, including empty ones
+    pub points: Vec<SeriesPoint>,
+    pub total: Money,
+}
+
+/// A consistency rule checked by `ReportService::term_integrity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum IntegrityRule {
+    /// Transfer legs of a sale on one date net to zero; so do the two legs
+    /// of a merge, which share a `merge_anchor_id`
+    TransferBalances,
+    /// An Adjustment's `related_sales_id` names an existing sale in the term
+    AdjustmentReference,
+    /// Entries are dated within their term
+    DateInTerm,
+    /// Entries are posted to existing sections
+    SectionExists,
+    /// Entries are posted to sections without active children on the
+    /// entry's date
+    LeafPosting,
+    /// The section hierarchy, summing each section's entries, rolls up to the
+    /// sum of all the term's entries
+    TotalsAgree,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    pub rule: IntegrityRule,
+    pub message: String,
+    /// Entries, sections or sales the violation is about
+    pub entity_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityReport {
+    pub term_id: Uuid,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub entries_checked: usize,
+    pub violations: Vec<Violation>,
+}
+
+impl IntegrityReport {
+    pub fn is_consistent(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+pub struct ReportService<'a, S, T, L>
+where
+    S: SectionRepository,
+    T: TermRepository,
+    L: SalesRepository,
+{
+    section_repo: &'a S,
+    term_repo: &'a T,
+    sales_repo: &'a L,
+}
+
+impl<'a, S, T, L> ReportService<'a, S, T, L>
+where
+    S: SectionRepository,
+    T: TermRepository,
+    L: SalesRepository,
+{
+    pub fn new(section_repo: &'a S, term_repo: &'a T, sales_repo: &'a L) -> Self {
+        Self {
+            section_repo,
+            term_repo,
+            sales_repo,
+        }
+    }
+
+    /// Term Total = Sum(Normal Sales) + Sum(Adjustments) + Sum(Corrections),
+    /// per section that has entries in the term. Sections are named as they
+    /// were at the end of the term.
+    pub fn term_summary(&self, term_id: Uuid) -> Result<TermSummary, ServiceError> {
+        let term = self
+            .term_repo
+            .find_by_id(&term_id)
+            .ok_or_else(|| ServiceError::not_found("Term"))?;
+
+        let mut totals: HashMap<Uuid, SectionTotal> = HashMap::new();
+        for sales in self.sales_repo.find_by_term(&term_id) {
+            let entry = totals.entry(sales.section_id).or_insert_with(|| {
+                let name = self
+                    .section_repo
+                    .find_by_id(&sales.section_id)
+                    .map(|s| s.name_on(term.end_date).to_string());
+                SectionTotal::new(sales.section_id, name)
+            });
+            match sales.sales_type {
+                SalesType::Normal => entry.normal = entry.normal + sales.amount,
+                SalesType::Adjustment => entry.adjustment = entry.adjustment + sales.amount,
+                SalesType::Correction => entry.correction = entry.correction + sales.amount,
+            }
+            entry.total = entry.total + sales.amount;
+        }
+
+        let mut sections: Vec<SectionTotal> = totals.into_values().collect();
+        sections.sort_by(|a, b| {
+            a.section_name
+                .cmp(&b.section_name)
+                .then(a.section_id.cmp(&b.section_id))
+        });
+        let total = sections.iter().fold(Money::zero(), |acc, s| acc + s.total);
+
+        Ok(TermSummary {
+            term_id: term.id,
+            start_date: term.start_date,
+            end_date: term.end_date,
+            status: term.status,
+            sections,
+            total,
+        })
+    }
+
+    /// Term totals rolled up the section hierarchy. With `Structure::AsOf`
+    /// each section sits where it was at the end of the term, so later moves
+    /// do not change past rollups; `Structure::Restated` uses the current
+    /// hierarchy instead. Sections with no entries in their subtree are left
+    /// out.
+    pub fn term_rollup(
+        &self,
+        term_id: Uuid,
+        structure: Structure,
+    ) -> Result<TermRollup, ServiceError> {
+        let term = self
+            .term_repo
+            .find_by_id(&term_id)
+            .ok_or_else(|| ServiceError::not_found("Term"))?;
+
+        let lines = self.rollup_lines(&self.own_totals(term_id), structure, term.end_date);
+        let total = lines
+            .iter()
+            .filter(|l| l.depth == 0)
+            .fold(Money::zero(), |acc, l| acc + l.total);
+
+        Ok(TermRollup {
+            term_id: term.id,
+            start_date: term.start_date,
+            end_date: term.end_date,
+            structure,
+            lines,
+            total,
+        })
+    }
+
+    /// Compares `current_term_id` with `base_term_id` (the prior term, or the
+    /// same term last year), section by section and rolled up the hierarchy.
+    /// Both terms are rolled up under the current hierarchy, so sections moved
+    /// in between are compared like for like rather than showing as variance.
+    pub fn term_comparison(
+        &self,
+        base_term_id: Uuid,
+        current_term_id: Uuid,
+    ) -> Result<TermComparison, ServiceError> {
+        let base_term = self
+            .term_repo
+            .find_by_id(&base_term_id)
+            .ok_or_else(|| ServiceError::not_found("Base term"))?;
+        let current_term = self
+            .term_repo
+            .find_by_id(&current_term_id)
+            .ok_or_else(|| ServiceError::not_found("Term"))?;
+
+        let base_own = self.own_totals(base_term.id);
+        let current_own = self.own_totals(current_term.id);
+        let subtree_totals = |own: &HashMap<Uuid, Money>, date: NaiveDate| {
+            self.rollup_lines(own, Structure::Restated, date)
+                .into_iter()
+                .map(|l| (l.section_id, l.total))
+                .collect::<HashMap<Uuid, Money>>()
+        };
+        let base = subtree_totals(&base_own, base_term.end_date);
+        let current = subtree_totals(&current_own, current_term.end_date);
+
+        // Lay out every section with entries in either term
+        let either: HashMap<Uuid, Money> = base_own
+            .keys()
+            .chain(current_own.keys())
+            .map(|id| (*id, Money::zero()))
+            .collect();
+        let lines: Vec<VarianceLine> = self
+            .rollup_lines(&either, Structure::Restated, current_term.end_date)
+            .iter()
+            .map(|l| {
+                VarianceLine::new(
+                    l,
+                    base.get(&l.section_id).copied(),
+                    current.get(&l.section_id).copied(),
+                )
+            })
+            .collect();
+
+        let top_level = |pick: fn(&VarianceLine) -> Money| {
+            lines
+                .iter()
+                .filter(|l| l.depth == 0)
+                .fold(Money::zero(), |acc, l| acc + pick(l))
+        };
+        let base_total = top_level(|l| l.base);
+        let current_total = top_level(|l| l.current);
+
+        Ok(TermComparison {
+            base_term_id: base_term.id,
+            current_term_id: current_term.id,
+            base_total,
+            current_total,
+            variance: current_total - base_total,
+            variance_pct: variance_pct(base_total, current_total),
+            lines,
+        })
+    }
+
+    /// Roll-forward of every section with entries in the term. A transfer is an
+    /// Adjustment pair pointing at the transferred sale through
+    /// `related_sales_id`: the leg in that sale's section is the transfer out,
+    /// the other the transfer in. Adjustments without a related sale (merges)
+    /// are reported as adjustments.
+    ///
+    /// Every entry falls in exactly one component, so the closing balance is
+    /// their sum and equals the section's balance by construction. A section
+    /// is flagged only when it holds a transfer leg whose sale is unknown or
+    /// whose counterpart is missing or for a different amount.
+    pub fn term_movement(&self, term_id: Uuid) -> Result<TermMovement, ServiceError> {
+        let term = self
+            .term_repo
+            .find_by_id(&term_id)
+            .ok_or_else(|| ServiceError::not_found("Term"))?;
+        let entries = self.sales_repo.find_by_term(&term_id);
+
+        // Transfer legs of the same sale on the same date must cancel out
+        let mut transfers: HashMap<(Uuid, NaiveDateTime), Money> = HashMap::new();
+        for sales in &entries {
+            if let (SalesType::Adjustment, Some(related_id)) =
+                (&sales.sales_type, sales.related_sales_id)
+            {
+                let net = transfers
+                    .entry((related_id, sales.date))
+                    .or_insert_with(Money::zero);
+                *net = *net + sales.amount;
+            }
+        }
+
+        let mut lines: HashMap<Uuid, MovementLine> = HashMap::new();
+        let mut transferred: HashMap<Uuid, Option<Uuid>> = HashMap::new();
+        for sales in &entries {
+            let line = lines.entry(sales.section_id).or_insert_with(|| {
+                let name = self
+                    .section_repo
+                    .find_by_id(&sales.section_id)
+                    .map(|s| s.name_on(term.end_date).to_string());
+                MovementLine::new(sales.section_id, name)
+            });
+            match (&sales.sales_type, sales.related_sales_id) {
+                (SalesType::Normal, _) => line.opening = line.opening + sales.amount,
+                (SalesType::Correction, _) => line.corrections = line.corrections + sales.amount,
+                (SalesType::Adjustment, None) => line.adjustments = line.adjustments + sales.amount,
+                (SalesType::Adjustment, Some(related_id)) => {
+                    let source_section = *transferred.entry(related_id).or_insert_with(|| {
+                        self.sales_repo
+                            .find_by_id(&related_id)
+                            .map(|s| s.section_id)
+                    });
+                    let outgoing = match source_section {
+                        Some(section_id) => section_id == sales.section_id,
+                        None => {
+                            line.issues
+                                .push(format!("Transfer of unknown sale {}", related_id));
+                            sales.amount.amount().is_sign_negative()
+                        }
+                    };
+                    if outgoing {
+                        line.transfers_out = line.transfers_out - sales.amount;
+                    } else {
+                        line.transfers_in = line.transfers_in + sales.amount;
+                    }
+                    if !transfers[&(related_id, sales.date)].amount().is_zero() {
+                        line.issues.push(format!(
+                            "Transfer of sale {} on {} does not balance",
+                            related_id, sales.date
+                        ));
+                    }
+                }
+            }
+        }
+
+        let mut sections: Vec<MovementLine> = lines.into_values().collect();
+        for line in &mut sections {
+            line.closing = line.computed();
+        }
+        sections.sort_by(|a, b| {
+            a.section_name
+                .cmp(&b.section_name)
+                .then(a.section_id.cmp(&b.section_id))
+        });
+
+        let mut total = MovementLine::new(term.id, None);
+        for line in &sections {
+            total.opening = total.opening + line.opening;
+            total.transfers_in = total.transfers_in + line.transfers_in;
+            total.transfers_out = total.transfers_out + line.transfers_out;
+            total.adjustments = total.adjustments + line.adjustments;
+            total.corrections = total.corrections + line.corrections;
+            total.closing = total.closing + line.closing;
+        }
+        if total.transfers_in != total.transfers_out {
+            total
+                .issues
+                .push("Transfers in and out do not match".to_string());
+        }
+
+        Ok(TermMovement {
+            term_id: term.id,
+            start_date: term.start_date,
+            end_date: term.end_date,
+            sections,
+            total,
+        })
+    }
+
+    /// Totals of the entries in `scope` per day, week or month, with a zero
+    /// point for every bucket without entries. Restricted to `section_id`
+    /// when given, and to its subtree in the current hierarchy when
+    /// `include_subtree` is set. Rejected if it would have more than
+    /// `MAX_SERIES_POINTS` buckets.
+    pub fn sales_series(
+        &self,
+        scope: ExportScope,
+        section_id: Option<Uuid>,
+        include_subtree: bool,
+        interval: Interval,
+    ) -> Result<SalesSeries, ServiceError> {
+        let (from, to) = match scope {
+            ExportScope::Term(term_id) => {
+                let term = self
+                    .term_repo
+                    .find_by_id(&term_id)
+                    .ok_or_else(|| ServiceError::not_found("Term"))?;
+                (term.start_date, term.end_date)
+            }
+            ExportScope::DateRange { from, to } => {
+                if from > to {
+                    return Err("Start date must be on or before end date".into());
+                }
+                (from, to)
+            }
+        };
+        let count = interval.bucket_count(from, to);
+        if count > MAX_SERIES_POINTS {
+            return Err(format!(
+                "The series would have {} points, more than the {} allowed; use a shorter range or a longer interval",
+                count, MAX_SERIES_POINTS
+            )
+            .into());
+        }
+        let entries = match scope {
+            ExportScope::Term(term_id) => self.sales_repo.find_by_term(&term_id),
+            ExportScope::DateRange { from, to } => self.sales_repo.find_by_date_range(from, to),
+        };
+
+        let sections: Option<HashSet<Uuid>> = match section_id {
+            Some(id) => {
+                if self.section_repo.find_by_id(&id).is_none() {
+                    return Err(ServiceError::not_found("Section"));
+                }
+                let mut ids = HashSet::from([id]);
+                if include_subtree {
+                    ids.extend(self.section_repo.find_descendants(&id).iter().map(|s| s.id));
+                }
+                Some(ids)
+            }
+            None => None,
+        };
+
+        let mut buckets: HashMap<NaiveDate, (Money, usize)> = HashMap::new();
+        for sales in entries {
+            let date = sales.date.date();
+            if date < from || date > to {
+                continue;
+            }
+            if sections
+                .as_ref()
+                .is_some_and(|ids| !ids.contains(&sales.section_id))
+            {
+                continue;
+            }
+            let bucket = buckets
+                .entry(interval.bucket_start(date))
+                .or_insert((Money::zero(), 0));
+            bucket.0 = bucket.0 + sales.amount;
+            bucket.1 += 1;
+        }
+
+        let mut points = Vec::new();
+        let mut start = Some(interval.bucket_start(from));
+        while let Some(bucket_start) = start.filter(|s| *s <= to) {
+            let next = interval.next(bucket_start);
+            let end = next
+                .and_then(|n| n.pred_opt())
+                .map_or(to, |last| last.min(to));
+            let (total, entries) = buckets
+                .get(&bucket_start)
+                .copied()
+                .unwrap_or((Money::zero(), 0));
+            points.push(SeriesPoint {
+                start: bucket_start.max(from),
+                end,
+                total,
+                entries,
+            });
+            start = next;
+        }
+        let total = points.iter().fold(Money::zero(), |acc, p| acc + p.total);
+
+        Ok(SalesSeries {
+            section_id,
+            include_subtree: section_id.is_some() && include_subtree,
+            interval,
+            from,
+            to,
+            points,
+            total,
+        })
+    }
+
+    /// Checks every entry of the term against the `IntegrityRule`s and lists
+    /// each violation with the IDs involved. Leaf postings are checked
+    /// against the hierarchy on the entry's date.
+    pub fn term_integrity(&self, term_id: Uuid) -> Result<IntegrityReport, ServiceError> {
+        let term = self
+            .term_repo
+            .find_by_id(&term_id)
+            .ok_or_else(|| ServiceError::not_found("Term"))?;
+        let mut entries = self.sales_repo.find_by_term(&term_id);
+        entries.sort_by(|a, b| a.date.cmp(&b.date).then(a.id.cmp(&b.id)));
+        let mut violations = Vec::new();
+
+        let all_sections = self.section_repo.list_all();
+        let known: HashSet<Uuid> = all_sections.iter().map(|s| s.id).collect();
+        // Whether a section has sections below it on a given day
+        let mut has_children: HashMap<(Uuid, NaiveDate), bool> = HashMap::new();
+        let mut legs: HashMap<(NaiveDateTime, LegGroup), (Money, Vec<Uuid>)> = HashMap::new();
+        for sales in &entries {
+            if sales.date.date() < term.start_date || sales.date.date() > term.end_date {
+                violations.push(Violation {
+                    rule: IntegrityRule::DateInTerm,
+                    message: format!(
+                        "Entry dated {} is outside the term ({} to {})",
+                        sales.date, term.start_date, term.end_date
+                    ),
+                    entity_ids: vec![sales.id],
+                });
+            }
+
+            if !known.contains(&sales.section_id) {
+                violations.push(Violation {
+                    rule: IntegrityRule::SectionExists,
+                    message: format!("Entry is posted to unknown section {}", sales.section_id),
+                    entity_ids: vec![sales.id, sales.section_id],
+                });
+            } else if *has_children
+                .entry((sales.section_id, sales.date.date()))
+                .or_insert_with(|| {
+                    let date = sales.date.date();
+                    all_sections.iter().any(|s| {
+                        s.is_active_on(date) && s.parent_on(date) == Some(sales.section_id)
+                    })
+                })
+            {
+                violations.push(Violation {
+                    rule: IntegrityRule::LeafPosting,
+                    message: format!(
+                        "Entry is posted to section {}, which has sections below it on {}",
+                        sales.section_id,
+                        sales.date.date()
+                    ),
+                    entity_ids: vec![sales.id, sales.section_id],
+                });
+            }
+
+            if sales.sales_type != SalesType::Adjustment {
+                continue;
+            }
+            if let Some(related_id) = sales.related_sales_id {
+                let related = self.sales_repo.find_by_id(&related_id);
+                if related.is_none_or(|r| r.term_id != term_id) {
+                    violations.push(Violation {
+                        rule: IntegrityRule::AdjustmentReference,
+                        message: format!(
+                            "Adjustment refers to sale {}, which is not in the term",
+                            related_id
+                        ),
+                        entity_ids: vec![sales.id, related_id],
+                    });
+                }
+            }
+            let group = match (sales.related_sales_id, sales.merge_anchor_id) {
+                (Some(related_id), _) => LegGroup::Transfer(related_id),
+                (None, Some(anchor_id)) => LegGroup::Merge(anchor_id),
+                (None, None) => LegGroup::Unpaired(sales.id),
+            };
+            let leg = legs
+                .entry((sales.date, group))
+                .or_insert_with(|| (Money::zero(), Vec::new()));
+            leg.0 = leg.0 + sales.amount;
+            leg.1.push(sales.id);
+        }
+
+        let mut unbalanced: Vec<_> = legs
+            .into_iter()
+            .filter(|(_, (net, _))| !net.amount().is_zero())
+            .collect();
+        unbalanced.sort_by_key(|(key, _)| *key);
+        for ((date, group), (net, ids)) in unbalanced {
+            let message = match group {
+                LegGroup::Transfer(related_id) => format!(
+                    "Transfer of sale {} on {} nets to {}",
+                    related_id, date, net
+                ),
+                LegGroup::Merge(anchor_id) => format!(
+                    "Merge anchored at entry {} on {} nets to {}",
+                    anchor_id, date, net
+                ),
+                LegGroup::Unpaired(_) => format!(
+                    "Adjustment on {} without a related sale or merge is {}",
+                    date, net
+                ),
+            };
+            violations.push(Violation {
+                rule: IntegrityRule::TransferBalances,
+                message,
+                entity_ids: ids,
+            });
+        }
+
+        // The rollup looks each section's entries up on their own, so it only
+        // reaches what the hierarchy does; the term's entries are summed as a
+        // whole
+        let ledger_total = entries.iter().fold(Money::zero(), |acc, s| acc + s.amount);
+        let mut own: HashMap<Uuid, Money> = HashMap::new();
+        for section in &all_sections {
+            let posted = self
+                .sales_repo
+                .find_by_section_and_term(&section.id, &term_id);
+            if !posted.is_empty() {
+                own.insert(
+                    section.id,
+                    posted.iter().fold(Money::zero(), |acc, s| acc + s.amount),
+                );
+            }
+        }
+        let rollup_total = self
+            .rollup_lines(&own, Structure::AsOf, term.end_date)
+            .iter()
+            .filter(|l| l.depth == 0)
+            .fold(Money::zero(), |acc, l| acc + l.total);
+        if rollup_total != ledger_total {
+            violations.push(Violation {
+                rule: IntegrityRule::TotalsAgree,
+                message: format!(
+                    "The section hierarchy rolls up to {} but the term's entries sum to {}",
+                    rollup_total, ledger_total
+                ),
+                entity_ids: vec![term.id],
+            });
+        }
+
+        Ok(IntegrityReport {
+            term_id: term.id,
+            start_date: term.start_date,
+            end_date: term.end_date,
+            entries_checked: entries.len(),
+            violations,
+        })
+    }
+
+    fn own_totals(&self, term_id: Uuid) -> HashMap<Uuid, Money> {
+        own_totals(&self.sales_repo.find_by_term(&term_id))
+    }
+
+    /// Rollup lines for the sections in `own` and their ancestors, with the
+    /// hierarchy as of `date` or, when restated, as it is now.
+    fn rollup_lines(
+        &self,
+        own: &HashMap<Uuid, Money>,
+        structure: Structure,
+        date: NaiveDate,
+    ) -> Vec<RollupLine> {
+        let mut nodes: HashMap<Uuid, (String, Option<Uuid>)> = HashMap::new();
+        for section in self.section_repo.list_all() {
+            let node = match structure {
+                Structure::AsOf => (section.name_on(date).to_string(), section.parent_on(date)),
+                Structure::Restated => (section.name.clone(), section.parent_id),
+            };
+            nodes.insert(section.id, node);
+        }
+        // Entries of sections that no longer exist are kept as top-level lines
+        for id in own.keys() {
+            nodes.entry(*id).or_insert_with(|| (id.to_string(), None));
+        }
+
+        let mut children: HashMap<Option<Uuid>, Vec<Uuid>> = HashMap::new();
+        for (id, (_, parent_id)) in &nodes {
+            let parent_id = parent_id.filter(|p| nodes.contains_key(p) && !is_cycle(&nodes, *id));
+            children.entry(parent_id).or_default().push(*id);
+        }
+        for ids in children.values_mut() {
+            ids.sort_by(|a, b| nodes[a].0.cmp(&nodes[b].0).then(a.cmp(b)));
+        }
+
+        let mut lines = Vec::new();
+        let mut visited = HashSet::new();
+        for root in children.get(&None).cloned().unwrap_or_default() {
+            rollup(root, 0, &nodes, &children, own, &mut visited, &mut lines);
+        }
+        lines
+    }
+}
+
+/// The Adjustments that must net to zero together on one date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum LegGroup {
+    /// Legs of a transfer of the related sale
+    Transfer(Uuid),
+    /// Legs of a merge, by their anchor entry
+    Merge(Uuid),
+    /// An Adjustment linked to nothing, which must be zero on its own
+    Unpaired(Uuid),
+}
+
+/// `current - base` as a percentage of `base`, to two decimal places. Taken
+/// against the magnitude of `base`, so a growing loss reads as negative.
+/// `None` when `base` is zero or the percentage is too large to represent.
+fn variance_pct(base: Money, current: Money) -> Option<Decimal> {
+    if base.amount().is_zero() {
+        return None;
+    }
+    let variance = (current - base).amount();
+    variance
+        .checked_mul(Decimal::ONE_HUNDRED)?
+        .checked_div(base.amount().abs())
+        .map(|pct| pct.round_dp(2))
+}
+
+/// Whether following parents from `id` leads back to `id`.
+fn is_cycle(nodes: &HashMap<Uuid, (String, Option<Uuid>)>, id: Uuid) -> bool {
+    let mut seen = HashSet::new();
+    let mut current = nodes.get(&id).and_then(|n| n.1);
+    while let Some(parent_id) = current {
+        if parent_id == id {
+            return true;
+        }
+        if !seen.insert(parent_id) {
+            return false;
+        }
+        current = nodes.get(&parent_id).and_then(|n| n.1);
+    }
+    false
+}
+
+/// Sum of each section's own entries among `entries`.
+fn own_totals(entries: &[Sales]) -> HashMap<Uuid, Money> {
+    let mut own: HashMap<Uuid, Money> = HashMap::new();
+    for sales in entries {
+        let entry = own.entry(sales.section_id).or_insert_with(Money::zero);
+        *entry = *entry + sales.amount;
+    }
+    own
+}
+
+/// Appends the line for `id` and its subtree, returning the subtree total,
+/// or `None` when the subtree has no entries and was left out.
+fn rollup(
+    id: Uuid,
+    depth: usize,
+    nodes: &HashMap<Uuid, (String, Option<Uuid>)>,
+    children: &HashMap<Option<Uuid>, Vec<Uuid>>,
+    own: &HashMap<Uuid, Money>,
+    visited: &mut HashSet<Uuid>,
+    lines: &mut Vec<RollupLine>,
+) -> Option<Money> {
+    if !visited.insert(id) {
+        return None;
+    }
+    let index = lines.len();
+    let (name, parent_id) = &nodes[&id];
+    let own_amount = own.get(&id).copied();
+    lines.push(RollupLine {
+        section_id: id,
+        name: name.clone(),
+        parent_id: *parent_id,
+        depth,
+        own: own_amount.unwrap_or_else(Money::zero),
+        total: Money::zero(),
+    });
+
+    let mut total = own_amount;
+    for child in children.get(&Some(id)).into_iter().flatten() {
+        if let Some(child_total) = rollup(*child, depth + 1, nodes, children, own, visited, lines) {
+            total = Some(total.unwrap_or_else(Money::zero) + child_total);
+        }
+    }
+
+    match total {
+        Some(total) => {
+            lines[index].total = total;
+            Some(total)
+        }
+        None => {
+            lines.truncate(index);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IntegrityRule, Interval, ReportService, Structure, VarianceStatus};
+    use crate::application::export::{ExportFormat, ExportScope};
+    use crate::application::service::ServiceError;
+    use crate::domain::entity::{Sales, SalesType, Section, SectionType, Term};
+    use crate::domain::repository::{SalesRepository, SectionRepository, TermRepository};
+    use crate::domain::value_object::Money;
+    use crate::infrastructure::in_memory::{
+        InMemorySalesRepository, InMemorySectionRepository, InMemoryTermRepository,
+    };
+    use crate::test_support::{audit, service};
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_term_summary_splits_by_type() {
+        let mut service = service();
+
+        let section_a = Section::new(
+            service.ids().next_id(),
+            "Section A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_a_id = service.create_section(section_a, None, audit()).unwrap();
+        let section_b = Section::new(
+            service.ids().next_id(),
+            "Section B".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_b_id = service.create_section(section_b, None, audit()).unwrap();
+
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+
+        let amount = Money::new(Decimal::from_str("100.00").unwrap());
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        service
+            .register_sales(amount, date, section_a_id, None, audit())
+            .unwrap();
+        let rebalance = Money::new(Decimal::from_str("30.00").unwrap());
+        service
+            .rebalance_term(
+                term_id,
+                section_a_id,
+                section_b_id,
+                rebalance,
+                date,
+                None,
+                audit(),
+            )
+            .unwrap();
+
+        let summary = service.reports().term_summary(term_id).unwrap();
+        assert_eq!(summary.sections.len(), 2);
+        assert_eq!(summary.total, amount);
+
+        let a = &summary.sections[0];
+        assert_eq!(a.section_name.as_deref(), Some("Section A"));
+        assert_eq!(a.normal, amount);
+        assert_eq!(a.correction, -rebalance);
+        assert_eq!(a.total, amount - rebalance);
+        assert_eq!(summary.sections[1].total, rebalance);
+    }
+
+    #[test]
+    fn test_comparison_reports_variance_and_changed_sections() {
+        let mut service = service();
+
+        let division = Section::new(
+            service.ids().next_id(),
+            "Division".to_string(),
+            SectionType::Division,
+            None,
+        )
+        .unwrap();
+        let division_id = service.create_section(division, None, audit()).unwrap();
+        let mut add_section = |name: &str| {
+            let section = Section::new(
+                service.ids().next_id(),
+                name.to_string(),
+                SectionType::Section,
+                Some(division_id),
+            )
+            .unwrap();
+            service.create_section(section, None, audit()).unwrap()
+        };
+        let kept_id = add_section("Kept");
+        let closed_id = add_section("Closed");
+        let opened_id = add_section("Opened");
+
+        let money = |amount: &str| Money::new(Decimal::from_str(amount).unwrap());
+        let mut term_with = |year: i32, entries: &[(uuid::Uuid, &str)]| {
+            let term = Term::new(
+                service.ids().next_id(),
+                NaiveDate::from_ymd_opt(year, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(year, 12, 31).unwrap(),
+            )
+            .unwrap();
+            let term_id = service.create_term(term, None, audit()).unwrap();
+            let date = NaiveDate::from_ymd_opt(year, 6, 1)
+                .unwrap()
+                .and_hms_opt(10, 0, 0)
+                .unwrap();
+            for (section_id, amount) in entries {
+                service
+                    .register_sales(money(amount), date, *section_id, None, audit())
+                    .unwrap();
+            }
+            service.close_term(term_id, None, audit()).unwrap();
+            term_id
+        };
+        let base_id = term_with(2024, &[(kept_id, "200.00"), (closed_id, "50.00")]);
+        let current_id = term_with(2025, &[(kept_id, "250.00"), (opened_id, "30.00")]);
+
+        let comparison = service
+            .reports()
+            .term_comparison(base_id, current_id)
+            .unwrap();
+        let lines: Vec<(&str, VarianceStatus)> = comparison
+            .lines
+            .iter()
+            .map(|l| (l.name.as_str(), l.status))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                ("Division", VarianceStatus::Continuing),
+                ("Closed", VarianceStatus::Disappeared),
+                ("Kept", VarianceStatus::Continuing),
+                ("Opened", VarianceStatus::New),
+            ]
+        );
+
+        let division_line = &comparison.lines[0];
+        assert_eq!(division_line.base, money("250.00"));
+        assert_eq!(division_line.current, money("280.00"));
+        assert_eq!(
+            division_line.variance_pct,
+            Some(Decimal::from_str("12").unwrap())
+        );
+        let kept = &comparison.lines[2];
+        assert_eq!(kept.variance, money("50.00"));
+        assert_eq!(kept.variance_pct, Some(Decimal::from_str("25").unwrap()));
+        assert_eq!(comparison.lines[3].variance_pct, None);
+        assert_eq!(comparison.variance, money("30.00"));
+
+        let mut csv = Vec::new();
+        let written = comparison.write(ExportFormat::Csv, &mut csv).unwrap();
+        assert_eq!(written, 4);
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("section_id,name,parent_id,depth,base,current,variance"));
+        assert!(csv.contains(",Opened,"));
+        assert!(csv.contains(",New\n"));
+
+        assert!(service
+            .reports()
+            .term_comparison(uuid::Uuid::new_v4(), current_id)
+            .is_err());
+    }
+
+    #[test]
+    fn test_movement_ties_out_and_flags_broken_transfers() {
+        let mut service = service();
+
+        let section_a = Section::new(
+            service.ids().next_id(),
+            "Section A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_a_id = service.create_section(section_a, None, audit()).unwrap();
+        let section_b = Section::new(
+            service.ids().next_id(),
+            "Section B".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_b_id = service.create_section(section_b, None, audit()).unwrap();
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+
+        let money = |amount: &str| Money::new(Decimal::from_str(amount).unwrap());
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        let sale_id = service
+            .register_sales(money("100.00"), date, section_a_id, None, audit())
+            .unwrap();
+        service
+            .register_sales(money("40.00"), date, section_b_id, None, audit())
+            .unwrap();
+        service
+            .transform_sales(sale_id, section_b_id, date, None, audit())
+            .unwrap();
+        service
+            .rebalance_term(
+                term_id,
+                section_b_id,
+                section_a_id,
+                money("10.00"),
+                date,
+                None,
+                audit(),
+            )
+            .unwrap();
+
+        let movement = service.reports().term_movement(term_id).unwrap();
+        assert_eq!(movement.exceptions().count(), 0);
+        let a = &movement.sections[0];
+        assert_eq!(a.opening, money("100.00"));
+        assert_eq!(a.transfers_out, money("100.00"));
+        assert_eq!(a.corrections, money("10.00"));
+        assert_eq!(a.closing, money("10.00"));
+        let b = &movement.sections[1];
+        assert_eq!(b.transfers_in, money("100.00"));
+        assert_eq!(b.closing, money("130.00"));
+        assert_eq!(movement.total.closing, money("140.00"));
+        assert!(movement.total.ties_out());
+
+        // A transfer leg without its counterpart is flagged
+        let mut sales_repo = InMemorySalesRepository::new();
+        for sales in service.list_sales(&term_id) {
+            sales_repo.save(sales).unwrap();
+        }
+        let mut stray = Sales::new(
+            Uuid::new_v4(),
+            money("25.00"),
+            date,
+            section_b_id,
+            term_id,
+            SalesType::Adjustment,
+        );
+        stray.related_sales_id = Some(sale_id);
+        sales_repo.save(stray).unwrap();
+        let mut section_repo = InMemorySectionRepository::new();
+        for section in service.list_sections() {
+            section_repo.save(section).unwrap();
+        }
+        let mut term_repo = InMemoryTermRepository::new();
+        for term in service.list_terms() {
+            term_repo.save(term).unwrap();
+        }
+        let movement = ReportService::new(&section_repo, &term_repo, &sales_repo)
+            .term_movement(term_id)
+            .unwrap();
+        let exceptions: Vec<_> = movement.exceptions().map(|s| s.section_id).collect();
+        assert_eq!(exceptions, vec![section_a_id, section_b_id]);
+        assert!(!movement.total.ties_out());
+    }
+
+    #[test]
+    fn test_series_fills_empty_buckets() {
+        let mut service = service();
+
+        let division = Section::new(
+            service.ids().next_id(),
+            "Division".to_string(),
+            SectionType::Division,
+            None,
+        )
+        .unwrap();
+        let division_id = service.create_section(division, None, audit()).unwrap();
+        let team = Section::new(
+            service.ids().next_id(),
+            "Team".to_string(),
+            SectionType::Section,
+            Some(division_id),
+        )
+        .unwrap();
+        let team_id = service.create_section(team, None, audit()).unwrap();
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 3, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+
+        let money = |amount: &str| Money::new(Decimal::from_str(amount).unwrap());
+        let at = |month: u32, day: u32| {
+            NaiveDate::from_ymd_opt(2025, month, day)
+                .unwrap()
+                .and_hms_opt(23, 30, 0)
+                .unwrap()
+        };
+        service
+            .register_sales(money("100.00"), at(1, 6), team_id, None, audit())
+            .unwrap();
+        service
+            .register_sales(money("20.00"), at(1, 12), division_id, None, audit())
+            .unwrap();
+        service
+            .register_sales(money("5.00"), at(3, 31), team_id, None, audit())
+            .unwrap();
+
+        let reports = service.reports();
+        let monthly = reports
+            .sales_series(ExportScope::Term(term_id), None, false, Interval::Month)
+            .unwrap();
+        let totals: Vec<Money> = monthly.points.iter().map(|p| p.total).collect();
+        assert_eq!(totals, vec![money("120.00"), Money::zero(), money("5.00")]);
+        assert_eq!(monthly.points[1].entries, 0);
+        assert_eq!(monthly.total, money("125.00"));
+
+        // Weeks start on Monday; the first is cut to the start of the term
+        let weekly = reports
+            .sales_series(
+                ExportScope::Term(term_id),
+                Some(team_id),
+                false,
+                Interval::Week,
+            )
+            .unwrap();
+        assert_eq!(
+            weekly.points[0].start,
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()
+        );
+        assert_eq!(
+            weekly.points[0].end,
+            NaiveDate::from_ymd_opt(2025, 1, 5).unwrap()
+        );
+        assert_eq!(weekly.points[1].total, money("100.00"));
+        assert_eq!(weekly.points.last().unwrap().total, money("5.00"));
+        assert_eq!(weekly.total, money("105.00"));
+
+        let range = ExportScope::DateRange {
+            from: NaiveDate::from_ymd_opt(2025, 1, 10).unwrap(),
+            to: NaiveDate::from_ymd_opt(2025, 1, 12).unwrap(),
+        };
+        let daily = reports
+            .sales_series(range, Some(division_id), true, Interval::Day)
+            .unwrap();
+        assert_eq!(daily.points.len(), 3);
+        assert_eq!(daily.points[2].total, money("20.00"));
+        let own_only = reports
+            .sales_series(range, Some(team_id), true, Interval::Day)
+            .unwrap();
+        assert!(own_only.total.amount().is_zero());
+
+        // Too many buckets are refused before any are built
+        let everything = ExportScope::DateRange {
+            from: NaiveDate::MIN,
+            to: NaiveDate::MAX,
+        };
+        let result = reports.sales_series(everything, None, false, Interval::Day);
+        assert!(matches!(result, Err(ServiceError::Rejected(_))));
+        let century = ExportScope::DateRange {
+            from: NaiveDate::from_ymd_opt(2000, 1, 1).unwrap(),
+            to: NaiveDate::from_ymd_opt(2099, 12, 31).unwrap(),
+        };
+        assert!(reports
+            .sales_series(century, None, false, Interval::Day)
+            .is_err());
+        let months = reports
+            .sales_series(century, None, false, Interval::Month)
+            .unwrap();
+        assert_eq!(months.points.len(), 1200);
+    }
+
+    #[test]
+    fn test_integrity_lists_each_violation() {
+        let mut service = service();
+        let division = Section::new(
+            service.ids().next_id(),
+            "Division".to_string(),
+            SectionType::Division,
+            None,
+        )
+        .unwrap();
+        let division_id = service.create_section(division, None, audit()).unwrap();
+        let team = Section::new(
+            service.ids().next_id(),
+            "Team".to_string(),
+            SectionType::Section,
+            Some(division_id),
+        )
+        .unwrap();
+        let team_id = service.create_section(team, None, audit()).unwrap();
+        let other = Section::new(
+            service.ids().next_id(),
+            "Other".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let other_id = service.create_section(other, None, audit()).unwrap();
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+
+        let money = |amount: &str| Money::new(Decimal::from_str(amount).unwrap());
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        let sale_id = service
+            .register_sales(money("100.00"), date, team_id, None, audit())
+            .unwrap();
+        service
+            .transform_sales(sale_id, other_id, date, None, audit())
+            .unwrap();
+        service
+            .merge_sections(
+                other_id,
+                team_id,
+                NaiveDate::from_ymd_opt(2025, 7, 1).unwrap(),
+                None,
+                audit(),
+            )
+            .unwrap();
+        // A section that only gains a child after its entry still took a
+        // leaf posting
+        let region = Section::new(
+            service.ids().next_id(),
+            "Region".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let region_id = service.create_section(region, None, audit()).unwrap();
+        service
+            .register_sales(money("7.00"), date, region_id, None, audit())
+            .unwrap();
+        let branch = Section::new(
+            service.ids().next_id(),
+            "Branch".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let branch_id = service.create_section(branch, None, audit()).unwrap();
+        service
+            .move_section(
+                branch_id,
+                Some(region_id),
+                NaiveDate::from_ymd_opt(2025, 8, 1).unwrap(),
+                None,
+                audit(),
+            )
+            .unwrap();
+        // Once its only child is deactivated, a section takes leaf postings
+        // again
+        service
+            .deactivate_section(
+                branch_id,
+                NaiveDate::from_ymd_opt(2025, 9, 1).unwrap(),
+                None,
+                audit(),
+            )
+            .unwrap();
+        let after_branch = NaiveDate::from_ymd_opt(2025, 9, 10)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        service
+            .register_sales(money("4.00"), after_branch, region_id, None, audit())
+            .unwrap();
+        let clean = service.reports().term_integrity(term_id).unwrap();
+        assert!(clean.is_consistent(), "{:?}", clean.violations);
+        assert_eq!(clean.entries_checked, 7);
+
+        // Copy the ledger and damage it
+        let mut sales_repo = InMemorySalesRepository::new();
+        for sales in service.list_sales(&term_id) {
+            sales_repo.save(sales).unwrap();
+        }
+        let mut section_repo = InMemorySectionRepository::new();
+        for section in service.list_sections() {
+            section_repo.save(section).unwrap();
+        }
+        let mut term_repo = InMemoryTermRepository::new();
+        for term in service.list_terms() {
+            term_repo.save(term).unwrap();
+        }
+        let late = NaiveDate::from_ymd_opt(2026, 1, 5)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        let mut stray = Sales::new(
+            Uuid::new_v4(),
+            money("5.00"),
+            late,
+            team_id,
+            term_id,
+            SalesType::Adjustment,
+        );
+        let missing_sale = uuid::Uuid::new_v4();
+        stray.related_sales_id = Some(missing_sale);
+        sales_repo.save(stray.clone()).unwrap();
+        // Later the same day, so the violations come out in this order
+        let later = |minutes| date + chrono::Duration::minutes(minutes);
+        let parent_posting = Sales::new(
+            Uuid::new_v4(),
+            money("1.00"),
+            later(1),
+            division_id,
+            term_id,
+            SalesType::Normal,
+        );
+        sales_repo.save(parent_posting.clone()).unwrap();
+        let unknown_section = uuid::Uuid::new_v4();
+        let orphan = Sales::new(
+            Uuid::new_v4(),
+            money("2.00"),
+            later(2),
+            unknown_section,
+            term_id,
+            SalesType::Normal,
+        );
+        sales_repo.save(orphan.clone()).unwrap();
+        // Unlinked Adjustments are not pooled, even when they cancel out
+        let unpaired = |id, amount| {
+            Sales::new(
+                Uuid::from_u128(id),
+                money(amount),
+                later(3),
+                team_id,
+                term_id,
+                SalesType::Adjustment,
+            )
+        };
+        sales_repo.save(unpaired(1, "3.00")).unwrap();
+        sales_repo.save(unpaired(2, "-3.00")).unwrap();
+        let later_region = Sales::new(
+            Uuid::new_v4(),
+            money("1.00"),
+            later(4) + chrono::Duration::days(90),
+            region_id,
+            term_id,
+            SalesType::Normal,
+        );
+        sales_repo.save(later_region.clone()).unwrap();
+
+        let report = ReportService::new(&section_repo, &term_repo, &sales_repo)
+            .term_integrity(term_id)
+            .unwrap();
+        let found: Vec<(IntegrityRule, Vec<uuid::Uuid>)> = report
+            .violations
+            .iter()
+            .map(|v| (v.rule, v.entity_ids.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    IntegrityRule::LeafPosting,
+                    vec![parent_posting.id, division_id]
+                ),
+                (
+                    IntegrityRule::SectionExists,
+                    vec![orphan.id, unknown_section]
+                ),
+                (IntegrityRule::LeafPosting, vec![later_region.id, region_id]),
+                (IntegrityRule::DateInTerm, vec![stray.id]),
+                (
+                    IntegrityRule::AdjustmentReference,
+                    vec![stray.id, missing_sale]
+                ),
+                (
+                    IntegrityRule::TransferBalances,
+                    vec![uuid::Uuid::from_u128(1)]
+                ),
+                (
+                    IntegrityRule::TransferBalances,
+                    vec![uuid::Uuid::from_u128(2)]
+                ),
+                (IntegrityRule::TransferBalances, vec![stray.id]),
+                // The entry in the unknown section is outside the hierarchy
+                (IntegrityRule::TotalsAgree, vec![term_id]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_integrity_reconciles_rollup_with_ledger() {
+        let mut service = service();
+        let section = Section::new(
+            service.ids().next_id(),
+            "Section".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_id = service.create_section(section, None, audit()).unwrap();
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        let money = |amount: &str| Money::new(Decimal::from_str(amount).unwrap());
+        service
+            .register_sales(money("100.00"), date, section_id, None, audit())
+            .unwrap();
+        assert!(service
+            .reports()
+            .term_integrity(term_id)
+            .unwrap()
+            .violations
+            .is_empty());
+
+        // An entry posted to a section the hierarchy does not know is left
+        // out of the rollup
+        let mut sales_repo = InMemorySalesRepository::new();
+        for sales in service.list_sales(&term_id) {
+            sales_repo.save(sales).unwrap();
+        }
+        let orphan = Sales::new(
+            Uuid::new_v4(),
+            money("25.00"),
+            date,
+            Uuid::new_v4(),
+            term_id,
+            SalesType::Normal,
+        );
+        sales_repo.save(orphan.clone()).unwrap();
+        let mut section_repo = InMemorySectionRepository::new();
+        for section in service.list_sections() {
+            section_repo.save(section).unwrap();
+        }
+        let mut term_repo = InMemoryTermRepository::new();
+        for term in service.list_terms() {
+            term_repo.save(term).unwrap();
+        }
+
+        let report = ReportService::new(&section_repo, &term_repo, &sales_repo)
+            .term_integrity(term_id)
+            .unwrap();
+        assert_eq!(report.violations.len(), 2);
+        assert_eq!(report.violations[0].rule, IntegrityRule::SectionExists);
+        assert_eq!(
+            report.violations[0].entity_ids,
+            vec![orphan.id, orphan.section_id]
+        );
+        let violation = &report.violations[1];
+        assert_eq!(violation.rule, IntegrityRule::TotalsAgree);
+        assert_eq!(violation.entity_ids, vec![term_id]);
+        assert_eq!(
+            violation.message,
+            "The section hierarchy rolls up to 100.00 but the term's entries sum to 125.00"
+        );
+    }
+
+    #[test]
+    fn test_rollup_uses_structure_of_the_term() {
+        let mut service = service();
+
+        let division_x = Section::new(
+            service.ids().next_id(),
+            "Division X".to_string(),
+            SectionType::Division,
+            None,
+        )
+        .unwrap();
+        let division_x_id = service.create_section(division_x, None, audit()).unwrap();
+        let division_y = Section::new(
+            service.ids().next_id(),
+            "Division Y".to_string(),
+            SectionType::Division,
+            None,
+        )
+        .unwrap();
+        let division_y_id = service.create_section(division_y, None, audit()).unwrap();
+        let team = Section::new(
+            service.ids().next_id(),
+            "Team".to_string(),
+            SectionType::Section,
+            Some(division_x_id),
+        )
+        .unwrap();
+        let team_id = service.create_section(team, None, audit()).unwrap();
+
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+        let amount = Money::new(Decimal::from_str("100.00").unwrap());
+        let date = NaiveDate::from_ymd_opt(2024, 6, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        service
+            .register_sales(amount, date, team_id, None, audit())
+            .unwrap();
+        service.close_term(term_id, None, audit()).unwrap();
+
+        service
+            .move_section(
+                team_id,
+                Some(division_y_id),
+                NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+                None,
+                audit(),
+            )
+            .unwrap();
+
+        let as_of = service
+            .reports()
+            .term_rollup(term_id, Structure::AsOf)
+            .unwrap();
+        let names: Vec<(&str, usize)> = as_of
+            .lines
+            .iter()
+            .map(|l| (l.name.as_str(), l.depth))
+            .collect();
+        assert_eq!(names, vec![("Division X", 0), ("Team", 1)]);
+        assert_eq!(as_of.lines[0].total, amount);
+        assert!(as_of.lines[0].own.amount().is_zero());
+        assert_eq!(as_of.total, amount);
+
+        let restated = service
+            .reports()
+            .term_rollup(term_id, Structure::Restated)
+            .unwrap();
+        assert_eq!(restated.lines[0].section_id, division_y_id);
+        assert_eq!(restated.lines[0].total, amount);
+        assert_eq!(restated.total, amount);
+    }
+}
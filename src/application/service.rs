@@ -1,44 +1,478 @@
-use crate::domain::entity::{Sales, SalesType, Section, Term};
-use crate::domain::repository::{SalesRepository, SectionRepository, TermRepository};
-use crate::domain::value_object::Money;
-use chrono::NaiveDateTime;
+use super::audit::AuditLog;
+use super::authorization::{AccessPolicy, Operation};
+use super::checklist::{CloseCheck, CloseChecklist, CloseError, CloseFailure};
+use super::export::LedgerExporter;
+use super::hierarchy::HierarchyService;
+use super::lineage::LineageService;
+use super::preview::{BalanceChange, Preview};
+use super::report::ReportService;
+use super::trace::{LedgerState, TraceLog};
+use crate::domain::entity::{
+    AuditAction, AuditRecord, AuditedEntity, Change, CorrectionRequest, CorrectionStatus,
+    KeyedChange, Sales, SalesType, Section, Term, TermStatus,
+};
+use crate::domain::provider::{Clock, IdGenerator, RandomIds, SystemClock};
+use crate::domain::repository::{
+    AuditLogRepository, RoleGrantRepository, SalesRepository, SectionRepository, TermRepository,
+};
+use crate::domain::value_object::{ActorId, AuditInfo, IdempotencyKey, Money, SectionCode};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use std::collections::HashSet;
+use thiserror::Error;
 use uuid::Uuid;
 
-pub struct AccountingService<S, T, L>
+const IDEMPOTENCY_MISMATCH: &str = "Idempotency key already used for a different request";
+
+/// Why the service refused an operation.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ServiceError {
+    /// The entity the operation acts on does not exist. Entities the request
+    /// only refers to, such as a new parent, are `Rejected` when missing.
+    #[error("{0}")]
+    NotFound(String),
+    /// The actor's roles do not permit the operation
+    #[error("{0}")]
+    Forbidden(String),
+    /// The idempotency key was already used for a different request
+    #[error("{0}")]
+    IdempotencyConflict(String),
+    /// A business rule rules the operation out
+    #[error("{0}")]
+    Rejected(String),
+}
+
+impl ServiceError {
+    pub(crate) fn not_found(what: &str) -> Self {
+        ServiceError::NotFound(format!("{} not found", what))
+    }
+
+    fn idempotency_mismatch() -> Self {
+        ServiceError::IdempotencyConflict(IDEMPOTENCY_MISMATCH.to_string())
+    }
+}
+
+impl From<String> for ServiceError {
+    fn from(message: String) -> Self {
+        ServiceError::Rejected(message)
+    }
+}
+
+impl From<&str> for ServiceError {
+    fn from(message: &str) -> Self {
+        ServiceError::Rejected(message.to_string())
+    }
+}
+
+impl From<ServiceError> for String {
+    fn from(error: ServiceError) -> Self {
+        error.to_string()
+    }
+}
+
+/// Records an operation will write once validated. `id` is what the operation
+/// returns: the new (or replayed) record for creations, the term for
+/// corrections and closing, the section for section changes.
+struct Plan {
+    id: Uuid,
+    replayed: bool,
+    operation: Operation,
+    action: AuditAction,
+    sections: Vec<Section>,
+    terms: Vec<Term>,
+    sales: Vec<Sales>,
+    /// Sections the operation acts on without writing them, for authorization
+    subjects: Vec<Uuid>,
+    records: Vec<AuditRecord>,
+}
+
+fn check_postable(amount: Money) -> Result<(), ServiceError> {
+    if !amount.is_postable() {
+        return Err(format!(
+            "Amount {} is out of range (at most {} either way, to {} decimal places)",
+            amount,
+            Money::MAX_ENTRY,
+            Money::MAX_SCALE
+        )
+        .into());
+    }
+    Ok(())
+}
+
+impl Plan {
+    fn new(id: Uuid, operation: Operation) -> Self {
+        let action = match operation {
+            Operation::CloseTerm => AuditAction::Closed,
+            Operation::RenameSection => AuditAction::Renamed,
+            Operation::MoveSection => AuditAction::Moved,
+            Operation::DeactivateSection => AuditAction::Deactivated,
+            Operation::MergeSections => AuditAction::Merged,
+            Operation::SubmitSection => AuditAction::Submitted,
+            _ => AuditAction::Created,
+        };
+        Self {
+            id,
+            replayed: false,
+            operation,
+            action,
+            sections: Vec::new(),
+            terms: Vec::new(),
+            sales: Vec::new(),
+            subjects: Vec::new(),
+            records: Vec::new(),
+        }
+    }
+
+    /// Attaches `audit` to every record the plan writes and prepares the
+    /// matching audit log entries, with IDs from `ids`. Correction entries
+    /// must carry a reason.
+    fn stamped(mut self, audit: AuditInfo, ids: &dyn IdGenerator) -> Result<Self, ServiceError> {
+        if self.replayed {
+            return Ok(self);
+        }
+        if audit.reason.is_none()
+            && self
+                .sales
+                .iter()
+                .any(|s| s.sales_type == SalesType::Correction)
+        {
+            return Err("A reason is required for corrections".into());
+        }
+
+        for section in &mut self.sections {
+            section.audit = Some(audit.clone());
+            self.records.push(AuditRecord::new(
+                ids.next_id(),
+                AuditedEntity::Section,
+                section.id,
+                self.action,
+                audit.clone(),
+            ));
+        }
+        for term in &mut self.terms {
+            term.audit = Some(audit.clone());
+            self.records.push(AuditRecord::new(
+                ids.next_id(),
+                AuditedEntity::Term,
+                term.id,
+                self.action,
+                audit.clone(),
+            ));
+        }
+        // Sales entries are never updated, only written
+        for sales in &mut self.sales {
+            sales.audit = Some(audit.clone());
+            self.records.push(AuditRecord::new(
+                ids.next_id(),
+                AuditedEntity::Sales,
+                sales.id,
+                AuditAction::Created,
+                audit.clone(),
+            ));
+        }
+        Ok(self)
+    }
+
+    fn replayed(id: Uuid, operation: Operation) -> Self {
+        Self {
+            replayed: true,
+            ..Self::new(id, operation)
+        }
+    }
+
+    /// Names the sections a request acts on, so that a replay is authorized
+    /// like the request that wrote it.
+    fn on(mut self, sections: impl IntoIterator<Item = Uuid>) -> Self {
+        self.subjects.extend(sections);
+        self
+    }
+}
+
+/// One sale to register, as `register_sales` takes it.
+#[derive(Debug, Clone)]
+pub struct Registration {
+    pub amount: Money,
+    pub date: NaiveDateTime,
+    pub section_id: Uuid,
+    pub idempotency_key: Option<IdempotencyKey>,
+}
+
+type PolicySource = Box<dyn Fn() -> Result<Option<AccessPolicy>, String>>;
+
+pub struct AccountingService<S, T, L, A>
 where
     S: SectionRepository,
     T: TermRepository,
     L: SalesRepository,
+    A: AuditLogRepository,
 {
     section_repo: S,
     term_repo: T,
     sales_repo: L,
+    audit_repo: A,
+    policy: Option<AccessPolicy>,
+    grants: Option<PolicySource>,
+    checklist: CloseChecklist,
+    trace: Option<TraceLog>,
+    ids: Box<dyn IdGenerator>,
+    clock: Box<dyn Clock>,
 }
 
-impl<S, T, L> AccountingService<S, T, L>
+impl<S, T, L, A> AccountingService<S, T, L, A>
 where
     S: SectionRepository,
     T: TermRepository,
     L: SalesRepository,
+    A: AuditLogRepository,
 {
-    pub fn new(section_repo: S, term_repo: T, sales_repo: L) -> Self {
+    pub fn new(section_repo: S, term_repo: T, sales_repo: L, audit_repo: A) -> Self {
         Self {
             section_repo,
             term_repo,
             sales_repo,
+            audit_repo,
+            policy: None,
+            grants: None,
+            checklist: CloseChecklist::default(),
+            trace: None,
+            ids: Box::new(RandomIds),
+            clock: Box::new(SystemClock),
         }
     }
 
-    pub fn create_section(&mut self, section: Section) -> Result<Uuid, String> {
-        let id = section.id;
-        self.section_repo.save(section)?;
-        Ok(id)
+    /// Enforces `policy` on every mutating operation, checked against the
+    /// actor in the operation's audit info. Without a policy every actor may
+    /// perform every operation.
+    pub fn with_policy(mut self, policy: AccessPolicy) -> Self {
+        self.policy = Some(policy);
+        self
     }
 
-    pub fn create_term(&mut self, term: Term) -> Result<Uuid, String> {
-        let id = term.id;
-        self.term_repo.save(term)?;
-        Ok(id)
+    /// Like `with_policy`, but rebuilds the policy from the grants `open`
+    /// returns before each operation, so a long-running caller sees roles
+    /// granted after it started. No grants leaves every actor unrestricted,
+    /// as `AccessPolicy::load` does.
+    pub fn with_policy_from<R, F>(mut self, open: F) -> Self
+    where
+        R: RoleGrantRepository,
+        F: Fn() -> Result<R, String> + 'static,
+    {
+        self.grants = Some(Box::new(move || {
+            open().map(|repo| AccessPolicy::load(&repo))
+        }));
+        self
+    }
+
+    /// Runs `checklist` before closing a term.
+    pub fn with_close_checklist(mut self, checklist: CloseChecklist) -> Self {
+        self.checklist = checklist;
+        self
+    }
+
+    /// Draws the ID of every sales entry and audit record the service writes
+    /// from `ids` instead of random ones. Sections and terms keep the ID they
+    /// are built with, which callers draw from `ids()`.
+    pub fn with_id_generator(mut self, ids: impl IdGenerator + 'static) -> Self {
+        self.ids = Box::new(ids);
+        self
+    }
+
+    /// Reads the time for audit timestamps and the close checks from `clock`
+    /// instead of the system clock.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// The service's ID generator, for new sections and terms and for
+    /// records kept next to the ledger such as pending corrections and role
+    /// grants.
+    pub fn ids(&self) -> &dyn IdGenerator {
+        self.ids.as_ref()
+    }
+
+    /// The service's clock, for stamping audit info.
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Records every operation applied from now on, with the ledger after
+    /// it, for checking against the TLA+ specification.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(TraceLog::new(self.ledger_state()));
+        self
+    }
+
+    pub fn trace(&self) -> Option<&TraceLog> {
+        self.trace.as_ref()
+    }
+
+    pub fn hierarchy(&self) -> HierarchyService<'_, S> {
+        HierarchyService::new(&self.section_repo)
+    }
+
+    pub fn lineage(&self) -> LineageService<'_, S, L> {
+        LineageService::new(&self.section_repo, &self.sales_repo)
+    }
+
+    pub fn exporter(&self) -> LedgerExporter<'_, S, T, L> {
+        LedgerExporter::new(&self.section_repo, &self.term_repo, &self.sales_repo)
+    }
+
+    pub fn reports(&self) -> ReportService<'_, S, T, L> {
+        ReportService::new(&self.section_repo, &self.term_repo, &self.sales_repo)
+    }
+
+    pub fn audit_log(&self) -> AuditLog<'_, A> {
+        AuditLog::new(&self.audit_repo)
+    }
+
+    pub fn find_section(&self, id: &Uuid) -> Option<Section> {
+        self.section_repo.find_by_id(id)
+    }
+
+    pub fn list_sections(&self) -> Vec<Section> {
+        self.section_repo.list_all()
+    }
+
+    pub fn find_term(&self, id: &Uuid) -> Option<Term> {
+        self.term_repo.find_by_id(id)
+    }
+
+    pub fn find_open_term(&self) -> Option<Term> {
+        self.term_repo.find_open_term()
+    }
+
+    pub fn list_terms(&self) -> Vec<Term> {
+        self.term_repo.list_all()
+    }
+
+    pub fn find_sales(&self, id: &Uuid) -> Option<Sales> {
+        self.sales_repo.find_by_id(id)
+    }
+
+    pub fn list_sales(&self, term_id: &Uuid) -> Vec<Sales> {
+        self.sales_repo.find_by_term(term_id)
+    }
+
+    pub fn find_sections_by_name(&self, name: &str) -> Vec<Section> {
+        self.section_repo.find_by_name(name)
+    }
+
+    pub fn find_section_by_code(&self, code: &SectionCode) -> Option<Section> {
+        self.section_repo.find_by_code(code)
+    }
+
+    pub fn find_children(&self, parent_id: &Uuid) -> Vec<Section> {
+        self.section_repo.find_children(parent_id)
+    }
+
+    pub fn create_section(
+        &mut self,
+        section: Section,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<Uuid, ServiceError> {
+        let plan = self.plan_create_section(section, idempotency_key)?;
+        self.apply(self.checked(plan, audit)?)
+    }
+
+    pub fn preview_create_section(
+        &self,
+        section: Section,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<Preview, ServiceError> {
+        self.plan_create_section(section, idempotency_key)
+            .and_then(|plan| self.checked(plan, audit))
+            .map(|plan| self.preview(plan))
+    }
+
+    fn plan_create_section(
+        &self,
+        mut section: Section,
+        idempotency_key: Option<IdempotencyKey>,
+    ) -> Result<Plan, ServiceError> {
+        if let Some(key) = &idempotency_key {
+            if let Some(existing) = self.section_repo.find_by_idempotency_key(key) {
+                if existing.idempotency_key.as_ref() != Some(key)
+                    || existing.code != section.code
+                    || existing.name != section.name
+                    || existing.section_type != section.section_type
+                    || existing.parent_id != section.parent_id
+                {
+                    return Err(ServiceError::idempotency_mismatch());
+                }
+                return Ok(
+                    Plan::replayed(existing.id, Operation::CreateSection).on(section.parent_id)
+                );
+            }
+        }
+
+        if let Some(code) = &section.code {
+            if self.section_repo.find_by_code(code).is_some() {
+                return Err(format!("Section code {} is already in use", code).into());
+            }
+        }
+        if let Some(parent_id) = section.parent_id {
+            if self.section_repo.find_by_id(&parent_id).is_none() {
+                return Err("Parent section not found".into());
+            }
+        }
+
+        if self.section_repo.find_by_id(&section.id).is_some() {
+            return Err(format!("Section {} already exists", section.id).into());
+        }
+        let mut plan = Plan::new(section.id, Operation::CreateSection);
+        section.idempotency_key = idempotency_key;
+        plan.sections.push(section);
+        Ok(plan)
+    }
+
+    pub fn create_term(
+        &mut self,
+        term: Term,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<Uuid, ServiceError> {
+        let plan = self.plan_create_term(term, idempotency_key)?;
+        self.apply(self.checked(plan, audit)?)
+    }
+
+    pub fn preview_create_term(
+        &self,
+        term: Term,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<Preview, ServiceError> {
+        self.plan_create_term(term, idempotency_key)
+            .and_then(|plan| self.checked(plan, audit))
+            .map(|plan| self.preview(plan))
+    }
+
+    fn plan_create_term(
+        &self,
+        mut term: Term,
+        idempotency_key: Option<IdempotencyKey>,
+    ) -> Result<Plan, ServiceError> {
+        if let Some(key) = &idempotency_key {
+            if let Some(existing) = self.term_repo.find_by_idempotency_key(key) {
+                if existing.idempotency_key.as_ref() != Some(key)
+                    || existing.start_date != term.start_date
+                    || existing.end_date != term.end_date
+                {
+                    return Err(ServiceError::idempotency_mismatch());
+                }
+                return Ok(Plan::replayed(existing.id, Operation::CreateTerm));
+            }
+        }
+
+        if self.term_repo.find_by_id(&term.id).is_some() {
+            return Err(format!("Term {} already exists", term.id).into());
+        }
+        let mut plan = Plan::new(term.id, Operation::CreateTerm);
+        term.idempotency_key = idempotency_key;
+        plan.terms.push(term);
+        Ok(plan)
     }
 
     pub fn register_sales(
@@ -46,11 +480,127 @@ where
         amount: Money,
         date: NaiveDateTime,
         section_id: Uuid,
-    ) -> Result<Uuid, String> {
-        // 1. Validate Section
-        if self.section_repo.find_by_id(&section_id).is_none() {
-            return Err("Section not found".to_string());
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<Uuid, ServiceError> {
+        let plan = self.plan_registration(amount, date, section_id, idempotency_key)?;
+        self.apply(self.checked(plan, audit)?)
+    }
+
+    pub fn preview_register_sales(
+        &self,
+        amount: Money,
+        date: NaiveDateTime,
+        section_id: Uuid,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<Preview, ServiceError> {
+        self.plan_registration(amount, date, section_id, idempotency_key)
+            .and_then(|plan| self.checked(plan, audit))
+            .map(|plan| self.preview(plan))
+    }
+
+    /// Registers every one of `registrations` or none of them: each is
+    /// checked against the ledger as it stands, as `audit`'s actor, and the
+    /// entries are then written together. Returns the IDs in order, replayed
+    /// ones included. A batch using an idempotency key more than once is
+    /// rejected, since the key could then never be replayed.
+    pub fn register_sales_batch(
+        &mut self,
+        registrations: Vec<Registration>,
+        audit: AuditInfo,
+    ) -> Result<Vec<Uuid>, ServiceError> {
+        let mut keys = HashSet::new();
+        for key in registrations
+            .iter()
+            .filter_map(|r| r.idempotency_key.as_ref())
+        {
+            if !keys.insert(key) {
+                return Err(format!(
+                    "Idempotency key {} is used more than once in the batch",
+                    key
+                )
+                .into());
+            }
+        }
+        let plans = registrations
+            .into_iter()
+            .map(|r| {
+                let plan =
+                    self.plan_registration(r.amount, r.date, r.section_id, r.idempotency_key)?;
+                self.checked(plan, audit.clone())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let ids = plans.iter().map(|p| p.id).collect();
+        self.apply_all(plans)?;
+        Ok(ids)
+    }
+
+    fn plan_registration(
+        &self,
+        amount: Money,
+        date: NaiveDateTime,
+        section_id: Uuid,
+        idempotency_key: Option<IdempotencyKey>,
+    ) -> Result<Plan, ServiceError> {
+        // 0. Replay: return the sale written by the original request
+        if let Some(existing_id) =
+            self.replayed_registration(amount, date, section_id, idempotency_key.as_ref())?
+        {
+            return Ok(Plan::replayed(existing_id, Operation::RegisterSales).on([section_id]));
+        }
+
+        let term = self.check_registration(amount, date, section_id)?;
+
+        // 3. Create Sales
+        let mut sales = Sales::new(
+            self.ids.next_id(),
+            amount,
+            date,
+            section_id,
+            term.id,
+            SalesType::Normal,
+        );
+        sales.idempotency_key = idempotency_key;
+        let mut plan = Plan::new(sales.id, Operation::RegisterSales);
+        plan.sales.push(sales);
+        Ok(plan)
+    }
+
+    fn replayed_registration(
+        &self,
+        amount: Money,
+        date: NaiveDateTime,
+        section_id: Uuid,
+        idempotency_key: Option<&IdempotencyKey>,
+    ) -> Result<Option<Uuid>, ServiceError> {
+        let key = match idempotency_key {
+            Some(key) => key,
+            None => return Ok(None),
+        };
+        let entries = self.sales_repo.find_by_idempotency_key(key);
+        match entries.as_slice() {
+            [] => Ok(None),
+            [existing]
+                if existing.sales_type == SalesType::Normal
+                    && existing.amount == amount
+                    && existing.date == date
+                    && existing.section_id == section_id =>
+            {
+                Ok(Some(existing.id))
+            }
+            _ => Err(ServiceError::idempotency_mismatch()),
         }
+    }
+
+    fn check_registration(
+        &self,
+        amount: Money,
+        date: NaiveDateTime,
+        section_id: Uuid,
+    ) -> Result<Term, ServiceError> {
+        // 1. Validate Section
+        self.check_active(section_id, date, "Section")?;
 
         // 2. Validate Term (Must be open)
         let term = self
@@ -59,19 +609,15 @@ where
             .ok_or("No open term found")?;
 
         if date.date() < term.start_date || date.date() > term.end_date {
-            return Err("Date is outside of the current term".to_string());
+            return Err("Date is outside of the current term".into());
         }
 
         if amount.amount().is_zero() {
-            return Err("Sales amount cannot be zero".to_string());
+            return Err("Sales amount cannot be zero".into());
         }
+        check_postable(amount)?;
 
-        // 3. Create Sales
-        let sales = Sales::new(amount, date, section_id, term.id, SalesType::Normal);
-        let id = sales.id;
-        self.sales_repo.save(sales)?;
-
-        Ok(id)
+        Ok(term)
     }
 
     pub fn transform_sales(
@@ -79,33 +625,77 @@ where
         sales_id: Uuid,
         target_section_id: Uuid,
         date: NaiveDateTime,
-    ) -> Result<Uuid, String> {
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<Uuid, ServiceError> {
+        let plan = self.plan_transform(sales_id, target_section_id, date, idempotency_key)?;
+        self.apply(self.checked(plan, audit)?)
+    }
+
+    pub fn preview_transform_sales(
+        &self,
+        sales_id: Uuid,
+        target_section_id: Uuid,
+        date: NaiveDateTime,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<Preview, ServiceError> {
+        self.plan_transform(sales_id, target_section_id, date, idempotency_key)
+            .and_then(|plan| self.checked(plan, audit))
+            .map(|plan| self.preview(plan))
+    }
+
+    fn plan_transform(
+        &self,
+        sales_id: Uuid,
+        target_section_id: Uuid,
+        date: NaiveDateTime,
+        idempotency_key: Option<IdempotencyKey>,
+    ) -> Result<Plan, ServiceError> {
+        if let Some(key) = &idempotency_key {
+            let entries = self.sales_repo.find_by_idempotency_key(key);
+            if !entries.is_empty() {
+                let matches = entries.len() == 2
+                    && entries.iter().all(|e| {
+                        e.sales_type == SalesType::Adjustment
+                            && e.related_sales_id == Some(sales_id)
+                            && e.date == date
+                    });
+                let sections: Vec<Uuid> = entries.iter().map(|e| e.section_id).collect();
+                return entries
+                    .into_iter()
+                    .find(|e| matches && e.section_id == target_section_id)
+                    .map(|e| Plan::replayed(e.id, Operation::TransferSales).on(sections))
+                    .ok_or_else(ServiceError::idempotency_mismatch);
+            }
+        }
+
         let original_sales = self
             .sales_repo
             .find_by_id(&sales_id)
-            .ok_or("Sales not found")?;
+            .ok_or_else(|| ServiceError::not_found("Sales"))?;
 
-        if self.section_repo.find_by_id(&target_section_id).is_none() {
-            return Err("Target section not found".to_string());
-        }
+        self.check_active(original_sales.section_id, date, "Source section")?;
+        self.check_active(target_section_id, date, "Target section")?;
 
         // Prevent transferring to the same section
         if target_section_id == original_sales.section_id {
-            return Err("Cannot transfer sales to the same section".to_string());
+            return Err("Cannot transfer sales to the same section".into());
         }
 
         // Validate date is within term range
         let term = self
             .term_repo
             .find_by_id(&original_sales.term_id)
-            .ok_or("Term not found")?;
+            .ok_or_else(|| ServiceError::not_found("Term"))?;
 
         if date.date() < term.start_date || date.date() > term.end_date {
-            return Err("Date is outside of the term".to_string());
+            return Err("Date is outside of the term".into());
         }
 
         // Create negative sales for source
         let mut negative_sales = Sales::new(
+            self.ids.next_id(),
             -original_sales.amount,
             date,
             original_sales.section_id,
@@ -113,10 +703,11 @@ where
             SalesType::Adjustment,
         );
         negative_sales.related_sales_id = Some(sales_id);
-        self.sales_repo.save(negative_sales)?;
+        negative_sales.idempotency_key = idempotency_key.clone();
 
         // Create positive sales for target
         let mut positive_sales = Sales::new(
+            self.ids.next_id(),
             original_sales.amount,
             date,
             target_section_id,
@@ -124,381 +715,2542 @@ where
             SalesType::Adjustment,
         );
         positive_sales.related_sales_id = Some(sales_id);
-        let new_id = positive_sales.id;
-        self.sales_repo.save(positive_sales)?;
+        positive_sales.idempotency_key = idempotency_key;
 
-        Ok(new_id)
+        let mut plan = Plan::new(positive_sales.id, Operation::TransferSales);
+        plan.sales.push(negative_sales);
+        plan.sales.push(positive_sales);
+        Ok(plan)
     }
 
-    pub fn close_term(&mut self, term_id: Uuid) -> Result<(), String> {
-        let mut term = self
-            .term_repo
-            .find_by_id(&term_id)
-            .ok_or("Term not found")?;
-        term.close();
-        self.term_repo.save(term)?;
+    /// Closing is naturally idempotent (closing a closed term is a no-op),
+    /// so unlike the other mutating operations it takes no idempotency key.
+    ///
+    /// An open term is only closed once every check on the close checklist
+    /// passes; otherwise the failures are returned. `override_reason` closes
+    /// it regardless, recording the reason and the failed checks in the audit
+    /// log.
+    pub fn close_term(
+        &mut self,
+        term_id: Uuid,
+        override_reason: Option<String>,
+        audit: AuditInfo,
+    ) -> Result<(), CloseError> {
+        let plan = self.plan_close(term_id)?;
+        let audit = self.cleared_to_close(term_id, override_reason, audit)?;
+        self.apply(self.checked(plan, audit)?)?;
         Ok(())
     }
 
-    #[allow(dead_code)]
-    pub fn correct_term(
-        &mut self,
+    pub fn preview_close_term(
+        &self,
         term_id: Uuid,
-        section_id: Uuid,
-        original_amount: Money,
-        correct_amount: Money,
-        date: NaiveDateTime,
-    ) -> Result<(), String> {
+        override_reason: Option<String>,
+        audit: AuditInfo,
+    ) -> Result<Preview, CloseError> {
+        let plan = self.plan_close(term_id)?;
+        let audit = self.cleared_to_close(term_id, override_reason, audit)?;
+        Ok(self.preview(self.checked(plan, audit)?))
+    }
+
+    /// Evaluates the close checklist against the term as it is now.
+    pub fn close_failures(&self, term_id: Uuid) -> Result<Vec<CloseFailure>, ServiceError> {
         let term = self
             .term_repo
             .find_by_id(&term_id)
-            .ok_or("Term not found")?;
-
-        if self.section_repo.find_by_id(&section_id).is_none() {
-            return Err("Section not found".to_string());
+            .ok_or_else(|| ServiceError::not_found("Term"))?;
+        let mut failures = Vec::new();
+        for check in self.checklist.checks() {
+            match check {
+                CloseCheck::IntegrityClean => {
+                    let report = self.reports().term_integrity(term_id)?;
+                    failures.extend(report.violations.into_iter().map(|v| CloseFailure {
+                        check: *check,
+                        message: v.message,
+                        entity_ids: v.entity_ids,
+                    }));
+                }
+                CloseCheck::NoPendingCorrections => match self.checklist.corrections() {
+                    Some(corrections) => {
+                        let mut pending: Vec<_> = corrections?
+                            .into_iter()
+                            .filter(|c| {
+                                c.request.term_id() == term_id
+                                    && matches!(
+                                        c.status,
+                                        CorrectionStatus::Draft | CorrectionStatus::Submitted
+                                    )
+                            })
+                            .collect();
+                        pending.sort_by_key(|c| c.id);
+                        failures.extend(pending.into_iter().map(|c| CloseFailure {
+                            check: *check,
+                            message: format!("Correction {} is {:?}", c.id, c.status),
+                            entity_ids: vec![c.id],
+                        }));
+                    }
+                    None => failures.push(CloseFailure {
+                        check: *check,
+                        message: "No correction store to check for pending corrections".to_string(),
+                        entity_ids: Vec::new(),
+                    }),
+                },
+                CloseCheck::SectionsSubmitted => {
+                    let mut missing: Vec<Section> = self
+                        .section_repo
+                        .list_all()
+                        .into_iter()
+                        .filter(|s| {
+                            s.is_active_on(term.end_date)
+                                && !term.is_submitted(&s.id)
+                                && self
+                                    .section_repo
+                                    .find_children(&s.id)
+                                    .iter()
+                                    .all(|c| !c.is_active_on(term.end_date))
+                        })
+                        .collect();
+                    missing.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+                    failures.extend(missing.into_iter().map(|s| CloseFailure {
+                        check: *check,
+                        message: format!("Section {} has not submitted", s.name),
+                        entity_ids: vec![s.id],
+                    }));
+                }
+                CloseCheck::NoFutureEntries => {
+                    let now = self.clock.now();
+                    let mut future: Vec<Sales> = self
+                        .sales_repo
+                        .find_by_term(&term_id)
+                        .into_iter()
+                        .filter(|s| s.date > now)
+                        .collect();
+                    future.sort_by(|a, b| a.date.cmp(&b.date).then(a.id.cmp(&b.id)));
+                    failures.extend(future.into_iter().map(|s| CloseFailure {
+                        check: *check,
+                        message: format!("Entry is dated in the future ({})", s.date),
+                        entity_ids: vec![s.id],
+                    }));
+                }
+            }
         }
+        Ok(failures)
+    }
 
-        if date.date() < term.start_date || date.date() > term.end_date {
-            return Err("Date is outside of the term".to_string());
+    /// The audit info to close with, or the checks blocking the close. A term
+    /// that is already closed needs no checks.
+    fn cleared_to_close(
+        &self,
+        term_id: Uuid,
+        override_reason: Option<String>,
+        mut audit: AuditInfo,
+    ) -> Result<AuditInfo, CloseError> {
+        if override_reason
+            .as_ref()
+            .is_some_and(|r| r.trim().is_empty())
+        {
+            return Err("An override needs a reason".into());
         }
+        let open = self
+            .term_repo
+            .find_by_id(&term_id)
+            .is_some_and(|t| t.status == TermStatus::Open);
+        if !open {
+            return Ok(audit);
+        }
+        let failures = self.close_failures(term_id)?;
+        if failures.is_empty() {
+            return Ok(audit);
+        }
+        match override_reason {
+            Some(reason) => {
+                let mut checks: Vec<String> = Vec::new();
+                for failure in &failures {
+                    let check = format!("{:?}", failure.check);
+                    if !checks.contains(&check) {
+                        checks.push(check);
+                    }
+                }
+                // The override is appended to the caller's own reason
+                let note = format!(
+                    "despite failed checks ({}): {}",
+                    checks.join(", "),
+                    reason.trim()
+                );
+                let reason = match audit.reason.take() {
+                    Some(given) if !given.trim().is_empty() => {
+                        format!("{}; closed {}", given.trim(), note)
+                    }
+                    _ => format!("Closed {}", note),
+                };
+                Ok(audit.with_reason(Some(reason)))
+            }
+            None => Err(CloseError::Blocked(failures)),
+        }
+    }
 
-        // Even if closed, corrections are allowed but marked as Correction type
-
-        // 1. Create reversal entry (negative of original)
-        let reversal = Sales::new(
-            -original_amount,
-            date,
-            section_id,
-            term.id,
-            SalesType::Correction,
-        );
-        self.sales_repo.save(reversal)?;
-
-        // 2. Create correction entry (new correct amount)
-        let correction = Sales::new(
-            correct_amount,
-            date,
-            section_id,
-            term.id,
-            SalesType::Correction,
-        );
-        self.sales_repo.save(correction)?;
+    /// Records that a section has signed off its figures for the term, as
+    /// required by `CloseCheck::SectionsSubmitted`. Submitting twice writes
+    /// nothing.
+    pub fn submit_section(
+        &mut self,
+        term_id: Uuid,
+        section_id: Uuid,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<(), ServiceError> {
+        let plan = self.plan_submit(term_id, section_id, idempotency_key)?;
+        self.apply(self.checked(plan, audit)?).map(|_| ())
+    }
 
-        Ok(())
+    pub fn preview_submit_section(
+        &self,
+        term_id: Uuid,
+        section_id: Uuid,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<Preview, ServiceError> {
+        self.plan_submit(term_id, section_id, idempotency_key)
+            .and_then(|plan| self.checked(plan, audit))
+            .map(|plan| self.preview(plan))
     }
 
-    pub fn rebalance_term(
-        &mut self,
+    fn plan_submit(
+        &self,
         term_id: Uuid,
-        source_section_id: Uuid,
-        target_section_id: Uuid,
-        amount: Money,
-        date: NaiveDateTime,
-    ) -> Result<(), String> {
-        // Validate that amount is strictly positive
-        if amount.amount().is_sign_negative() || amount.amount().is_zero() {
-            return Err("Rebalance amount must be positive".to_string());
+        section_id: Uuid,
+        idempotency_key: Option<IdempotencyKey>,
+    ) -> Result<Plan, ServiceError> {
+        let change = Change::Submit { section_id };
+        if let Some(key) = &idempotency_key {
+            if let Some(existing) = self.term_repo.find_by_idempotency_key(key) {
+                if existing.id != term_id || existing.change_for(key) != Some(&change) {
+                    return Err(ServiceError::idempotency_mismatch());
+                }
+                return Ok(Plan::replayed(term_id, Operation::SubmitSection).on([section_id]));
+            }
         }
 
-        let term = self
+        let mut term = self
             .term_repo
             .find_by_id(&term_id)
-            .ok_or("Term not found")?;
-
-        if self.section_repo.find_by_id(&source_section_id).is_none() {
-            return Err("Source section not found".to_string());
+            .ok_or_else(|| ServiceError::not_found("Term"))?;
+        if self.section_repo.find_by_id(&section_id).is_none() {
+            return Err(ServiceError::not_found("Section"));
         }
-        if self.section_repo.find_by_id(&target_section_id).is_none() {
-            return Err("Target section not found".to_string());
+        if term.is_submitted(&section_id) {
+            return Ok(Plan::replayed(term.id, Operation::SubmitSection).on([section_id]));
         }
+        term.submit_section(section_id)?;
+        term.keyed_changes
+            .extend(idempotency_key.map(|key| KeyedChange { key, change }));
+        let mut plan = Plan::new(term.id, Operation::SubmitSection);
+        plan.subjects.push(section_id);
+        plan.terms.push(term);
+        Ok(plan)
+    }
 
-        // Prevent rebalancing between the same section
-        if source_section_id == target_section_id {
-            return Err("Cannot rebalance between the same section".to_string());
+    fn plan_close(&self, term_id: Uuid) -> Result<Plan, ServiceError> {
+        let mut term = self
+            .term_repo
+            .find_by_id(&term_id)
+            .ok_or_else(|| ServiceError::not_found("Term"))?;
+        if term.status == TermStatus::Closed {
+            return Ok(Plan::replayed(term.id, Operation::CloseTerm));
         }
+        term.close();
+        let mut plan = Plan::new(term.id, Operation::CloseTerm);
+        plan.terms.push(term);
+        Ok(plan)
+    }
 
-        if date.date() < term.start_date || date.date() > term.end_date {
-            return Err("Date is outside of the term".to_string());
+    /// Renames a section from `effective` on. Reports for earlier dates keep
+    /// the old name. Renaming to the current name writes nothing.
+    pub fn rename_section(
+        &mut self,
+        section_id: Uuid,
+        name: String,
+        effective: NaiveDate,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<(), ServiceError> {
+        let plan = self.plan_rename(section_id, name, effective, idempotency_key)?;
+        self.apply(self.checked(plan, audit)?).map(|_| ())
+    }
+
+    pub fn preview_rename_section(
+        &self,
+        section_id: Uuid,
+        name: String,
+        effective: NaiveDate,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<Preview, ServiceError> {
+        self.plan_rename(section_id, name, effective, idempotency_key)
+            .and_then(|plan| self.checked(plan, audit))
+            .map(|plan| self.preview(plan))
+    }
+
+    fn plan_rename(
+        &self,
+        section_id: Uuid,
+        name: String,
+        effective: NaiveDate,
+        idempotency_key: Option<IdempotencyKey>,
+    ) -> Result<Plan, ServiceError> {
+        let change = Change::Rename {
+            name: name.clone(),
+            effective,
+        };
+        if self.replays_change(idempotency_key.as_ref(), section_id, &change)? {
+            return Ok(Plan::replayed(section_id, Operation::RenameSection).on([section_id]));
+        }
+        let mut section = self
+            .section_repo
+            .find_by_id(&section_id)
+            .ok_or_else(|| ServiceError::not_found("Section"))?;
+        if section.name == name {
+            return Ok(Plan::replayed(section_id, Operation::RenameSection).on([section_id]));
         }
+        section.rename(name, effective)?;
+        section
+            .keyed_changes
+            .extend(idempotency_key.map(|key| KeyedChange { key, change }));
+
+        let mut plan = Plan::new(section_id, Operation::RenameSection);
+        plan.sections.push(section);
+        Ok(plan)
+    }
 
-        // Negative for source
-        let source_correction = Sales::new(
-            -amount,
-            date,
-            source_section_id,
-            term.id,
-            SalesType::Correction,
-        );
-        self.sales_repo.save(source_correction)?;
+    /// Moves a section under `parent_id`, or to the top level, from
+    /// `effective` on. Moving to the current parent writes nothing.
+    pub fn move_section(
+        &mut self,
+        section_id: Uuid,
+        parent_id: Option<Uuid>,
+        effective: NaiveDate,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<(), ServiceError> {
+        let plan = self.plan_move(section_id, parent_id, effective, idempotency_key)?;
+        self.apply(self.checked(plan, audit)?).map(|_| ())
+    }
 
-        // Positive for target
-        let target_correction = Sales::new(
+    pub fn preview_move_section(
+        &self,
+        section_id: Uuid,
+        parent_id: Option<Uuid>,
+        effective: NaiveDate,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<Preview, ServiceError> {
+        self.plan_move(section_id, parent_id, effective, idempotency_key)
+            .and_then(|plan| self.checked(plan, audit))
+            .map(|plan| self.preview(plan))
+    }
+
+    fn plan_move(
+        &self,
+        section_id: Uuid,
+        parent_id: Option<Uuid>,
+        effective: NaiveDate,
+        idempotency_key: Option<IdempotencyKey>,
+    ) -> Result<Plan, ServiceError> {
+        let change = Change::Move {
+            parent_id,
+            effective,
+        };
+        if self.replays_change(idempotency_key.as_ref(), section_id, &change)? {
+            return Ok(Plan::replayed(section_id, Operation::MoveSection).on([section_id]));
+        }
+        let mut section = self
+            .section_repo
+            .find_by_id(&section_id)
+            .ok_or_else(|| ServiceError::not_found("Section"))?;
+        if section.parent_id == parent_id {
+            return Ok(Plan::replayed(section_id, Operation::MoveSection).on([section_id]));
+        }
+
+        if let Some(parent_id) = parent_id {
+            let parent = self
+                .section_repo
+                .find_by_id(&parent_id)
+                .ok_or("Parent section not found")?;
+            if !parent.is_active_on(effective) {
+                return Err("Parent section is inactive on that date".into());
+            }
+            if self
+                .section_repo
+                .find_path(&parent_id)
+                .contains(&section_id)
+            {
+                return Err("Cannot move a section under itself or its descendants".into());
+            }
+        }
+        section.move_to(parent_id, effective)?;
+        section
+            .keyed_changes
+            .extend(idempotency_key.map(|key| KeyedChange { key, change }));
+
+        let mut plan = Plan::new(section_id, Operation::MoveSection);
+        plan.sections.push(section);
+        Ok(plan)
+    }
+
+    /// Stops a section accepting postings dated on or after `effective`. Its
+    /// entries stay in the ledger. Deactivating on the same date again writes
+    /// nothing.
+    pub fn deactivate_section(
+        &mut self,
+        section_id: Uuid,
+        effective: NaiveDate,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<(), ServiceError> {
+        let plan = self.plan_deactivate(section_id, effective, idempotency_key)?;
+        self.apply(self.checked(plan, audit)?).map(|_| ())
+    }
+
+    pub fn preview_deactivate_section(
+        &self,
+        section_id: Uuid,
+        effective: NaiveDate,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<Preview, ServiceError> {
+        self.plan_deactivate(section_id, effective, idempotency_key)
+            .and_then(|plan| self.checked(plan, audit))
+            .map(|plan| self.preview(plan))
+    }
+
+    fn plan_deactivate(
+        &self,
+        section_id: Uuid,
+        effective: NaiveDate,
+        idempotency_key: Option<IdempotencyKey>,
+    ) -> Result<Plan, ServiceError> {
+        let change = Change::Deactivate { effective };
+        if self.replays_change(idempotency_key.as_ref(), section_id, &change)? {
+            return Ok(Plan::replayed(section_id, Operation::DeactivateSection).on([section_id]));
+        }
+        let mut section = self
+            .section_repo
+            .find_by_id(&section_id)
+            .ok_or_else(|| ServiceError::not_found("Section"))?;
+        if section.deactivated_on == Some(effective) && section.merged_into.is_none() {
+            return Ok(Plan::replayed(section_id, Operation::DeactivateSection).on([section_id]));
+        }
+        self.check_can_deactivate(section_id, effective)?;
+        section.deactivate(effective)?;
+        section
+            .keyed_changes
+            .extend(idempotency_key.map(|key| KeyedChange { key, change }));
+
+        let mut plan = Plan::new(section_id, Operation::DeactivateSection);
+        plan.sections.push(section);
+        Ok(plan)
+    }
+
+    /// Deactivates `source_id` from `effective` on and moves its balance in
+    /// the open term to `target_id` with a pair of Adjustment entries dated
+    /// `effective`. Merging into the same target again writes nothing.
+    pub fn merge_sections(
+        &mut self,
+        source_id: Uuid,
+        target_id: Uuid,
+        effective: NaiveDate,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<(), ServiceError> {
+        let plan = self.plan_merge(source_id, target_id, effective, idempotency_key)?;
+        self.apply(self.checked(plan, audit)?).map(|_| ())
+    }
+
+    pub fn preview_merge_sections(
+        &self,
+        source_id: Uuid,
+        target_id: Uuid,
+        effective: NaiveDate,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<Preview, ServiceError> {
+        self.plan_merge(source_id, target_id, effective, idempotency_key)
+            .and_then(|plan| self.checked(plan, audit))
+            .map(|plan| self.preview(plan))
+    }
+
+    fn plan_merge(
+        &self,
+        source_id: Uuid,
+        target_id: Uuid,
+        effective: NaiveDate,
+        idempotency_key: Option<IdempotencyKey>,
+    ) -> Result<Plan, ServiceError> {
+        let change = Change::Merge {
+            target_id,
+            effective,
+        };
+        if self.replays_change(idempotency_key.as_ref(), source_id, &change)? {
+            return Ok(
+                Plan::replayed(source_id, Operation::MergeSections).on([source_id, target_id])
+            );
+        }
+        let mut source = self
+            .section_repo
+            .find_by_id(&source_id)
+            .ok_or_else(|| ServiceError::not_found("Source section"))?;
+        if source.merged_into == Some(target_id) {
+            return Ok(
+                Plan::replayed(source_id, Operation::MergeSections).on([source_id, target_id])
+            );
+        }
+
+        let target = self
+            .section_repo
+            .find_by_id(&target_id)
+            .ok_or("Target section not found")?;
+        if !target.is_active_on(effective) {
+            return Err("Target section is inactive on that date".into());
+        }
+        if self.section_repo.find_path(&target_id).contains(&source_id) {
+            return Err("Cannot merge a section into itself or its descendants".into());
+        }
+        self.check_can_deactivate(source_id, effective)?;
+        source.merge_into(target_id, effective)?;
+        source
+            .keyed_changes
+            .extend(idempotency_key.map(|key| KeyedChange { key, change }));
+
+        let mut plan = Plan::new(source_id, Operation::MergeSections);
+        if let Some(term) = self.term_repo.find_open_term() {
+            let balance = self
+                .sales_repo
+                .find_by_section_and_term(&source_id, &term.id)
+                .iter()
+                .fold(Money::zero(), |acc, s| acc + s.amount);
+            if !balance.amount().is_zero() {
+                if effective < term.start_date || effective > term.end_date {
+                    return Err("Merge date is outside of the open term".into());
+                }
+                let date = effective.and_time(NaiveTime::MIN);
+                let mut outgoing = Sales::new(
+                    self.ids.next_id(),
+                    -balance,
+                    date,
+                    source_id,
+                    term.id,
+                    SalesType::Adjustment,
+                );
+                outgoing.merge_anchor_id = Some(outgoing.id);
+                let mut incoming = Sales::new(
+                    self.ids.next_id(),
+                    balance,
+                    date,
+                    target_id,
+                    term.id,
+                    SalesType::Adjustment,
+                );
+                incoming.merge_anchor_id = Some(outgoing.id);
+                plan.sales.push(outgoing);
+                plan.sales.push(incoming);
+            }
+        }
+        plan.sections.push(source);
+        Ok(plan)
+    }
+
+    /// Whether `key` already made `change` to `section_id`, so the request is
+    /// a retry to replay. A key used for anything else is a conflict.
+    fn replays_change(
+        &self,
+        key: Option<&IdempotencyKey>,
+        section_id: Uuid,
+        change: &Change,
+    ) -> Result<bool, ServiceError> {
+        let Some(key) = key else {
+            return Ok(false);
+        };
+        match self.section_repo.find_by_idempotency_key(key) {
+            None => Ok(false),
+            Some(existing)
+                if existing.id == section_id && existing.change_for(key) == Some(change) =>
+            {
+                Ok(true)
+            }
+            Some(_) => Err(ServiceError::idempotency_mismatch()),
+        }
+    }
+
+    /// A section can only be deactivated once its children are and nothing in
+    /// the open term is posted to it on or after the deactivation date. Closed
+    /// terms cannot take new postings, so their entries are not checked.
+    fn check_can_deactivate(
+        &self,
+        section_id: Uuid,
+        effective: NaiveDate,
+    ) -> Result<(), ServiceError> {
+        if self
+            .section_repo
+            .find_children(&section_id)
+            .iter()
+            .any(|s| s.is_active_on(effective))
+        {
+            return Err("Section has active child sections".into());
+        }
+        if let Some(term) = self.term_repo.find_open_term() {
+            if term.end_date >= effective
+                && self
+                    .sales_repo
+                    .iter_by_date(effective.max(term.start_date), term.end_date)
+                    .any(|s| s.term_id == term.id && s.section_id == section_id)
+            {
+                return Err("Section has entries dated on or after that date".into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a correction; only `CorrectionWorkflow::approve` calls it, so
+    /// corrections reach the ledger through maker-checker approval.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn correct_term(
+        &mut self,
+        term_id: Uuid,
+        section_id: Uuid,
+        original_amount: Money,
+        correct_amount: Money,
+        date: NaiveDateTime,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<(), ServiceError> {
+        let plan = self.plan_correction(
+            term_id,
+            section_id,
+            original_amount,
+            correct_amount,
+            date,
+            idempotency_key,
+        )?;
+        self.apply(self.checked(plan, audit)?).map(|_| ())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn preview_correct_term(
+        &self,
+        term_id: Uuid,
+        section_id: Uuid,
+        original_amount: Money,
+        correct_amount: Money,
+        date: NaiveDateTime,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<Preview, ServiceError> {
+        self.plan_correction(
+            term_id,
+            section_id,
+            original_amount,
+            correct_amount,
+            date,
+            idempotency_key,
+        )
+        .and_then(|plan| self.checked(plan, audit))
+        .map(|plan| self.preview(plan))
+    }
+
+    fn plan_correction(
+        &self,
+        term_id: Uuid,
+        section_id: Uuid,
+        original_amount: Money,
+        correct_amount: Money,
+        date: NaiveDateTime,
+        idempotency_key: Option<IdempotencyKey>,
+    ) -> Result<Plan, ServiceError> {
+        if let Some(key) = &idempotency_key {
+            if self.replayed_correction(
+                key,
+                term_id,
+                date,
+                &[(section_id, -original_amount), (section_id, correct_amount)],
+            )? {
+                return Ok(Plan::replayed(term_id, Operation::CorrectTerm).on([section_id]));
+            }
+        }
+
+        let term = self
+            .term_repo
+            .find_by_id(&term_id)
+            .ok_or_else(|| ServiceError::not_found("Term"))?;
+
+        self.check_active(section_id, date, "Section")?;
+
+        if date.date() < term.start_date || date.date() > term.end_date {
+            return Err("Date is outside of the term".into());
+        }
+        check_postable(original_amount)?;
+        check_postable(correct_amount)?;
+
+        // Even if closed, corrections are allowed but marked as Correction type
+
+        // 1. Create reversal entry (negative of original)
+        let mut reversal = Sales::new(
+            self.ids.next_id(),
+            -original_amount,
+            date,
+            section_id,
+            term.id,
+            SalesType::Correction,
+        );
+        reversal.idempotency_key = idempotency_key.clone();
+
+        // 2. Create correction entry (new correct amount)
+        let mut correction = Sales::new(
+            self.ids.next_id(),
+            correct_amount,
+            date,
+            section_id,
+            term.id,
+            SalesType::Correction,
+        );
+        correction.idempotency_key = idempotency_key;
+
+        let mut plan = Plan::new(term.id, Operation::CorrectTerm);
+        plan.sales.push(reversal);
+        plan.sales.push(correction);
+        Ok(plan)
+    }
+
+    /// Writes a rebalance; like `correct_term`, only reached through
+    /// `CorrectionWorkflow::approve`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn rebalance_term(
+        &mut self,
+        term_id: Uuid,
+        source_section_id: Uuid,
+        target_section_id: Uuid,
+        amount: Money,
+        date: NaiveDateTime,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<(), ServiceError> {
+        let plan = self.plan_rebalance(
+            term_id,
+            source_section_id,
+            target_section_id,
             amount,
             date,
+            idempotency_key,
+        )?;
+        self.apply(self.checked(plan, audit)?).map(|_| ())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn preview_rebalance_term(
+        &self,
+        term_id: Uuid,
+        source_section_id: Uuid,
+        target_section_id: Uuid,
+        amount: Money,
+        date: NaiveDateTime,
+        idempotency_key: Option<IdempotencyKey>,
+        audit: AuditInfo,
+    ) -> Result<Preview, ServiceError> {
+        self.plan_rebalance(
+            term_id,
+            source_section_id,
             target_section_id,
+            amount,
+            date,
+            idempotency_key,
+        )
+        .and_then(|plan| self.checked(plan, audit))
+        .map(|plan| self.preview(plan))
+    }
+
+    fn plan_rebalance(
+        &self,
+        term_id: Uuid,
+        source_section_id: Uuid,
+        target_section_id: Uuid,
+        amount: Money,
+        date: NaiveDateTime,
+        idempotency_key: Option<IdempotencyKey>,
+    ) -> Result<Plan, ServiceError> {
+        if let Some(key) = &idempotency_key {
+            if self.replayed_correction(
+                key,
+                term_id,
+                date,
+                &[(source_section_id, -amount), (target_section_id, amount)],
+            )? {
+                return Ok(Plan::replayed(term_id, Operation::RebalanceTerm)
+                    .on([source_section_id, target_section_id]));
+            }
+        }
+
+        // Validate that amount is strictly positive
+        if amount.amount().is_sign_negative() || amount.amount().is_zero() {
+            return Err("Rebalance amount must be positive".into());
+        }
+        check_postable(amount)?;
+
+        let term = self
+            .term_repo
+            .find_by_id(&term_id)
+            .ok_or_else(|| ServiceError::not_found("Term"))?;
+
+        self.check_active(source_section_id, date, "Source section")?;
+        self.check_active(target_section_id, date, "Target section")?;
+
+        // Prevent rebalancing between the same section
+        if source_section_id == target_section_id {
+            return Err("Cannot rebalance between the same section".into());
+        }
+
+        if date.date() < term.start_date || date.date() > term.end_date {
+            return Err("Date is outside of the term".into());
+        }
+
+        // Negative for source
+        let mut source_correction = Sales::new(
+            self.ids.next_id(),
+            -amount,
+            date,
+            source_section_id,
             term.id,
             SalesType::Correction,
         );
-        self.sales_repo.save(target_correction)?;
+        source_correction.idempotency_key = idempotency_key.clone();
+
+        // Positive for target
+        let mut target_correction = Sales::new(
+            self.ids.next_id(),
+            amount,
+            date,
+            target_section_id,
+            term.id,
+            SalesType::Correction,
+        );
+        target_correction.idempotency_key = idempotency_key;
+
+        let mut plan = Plan::new(term.id, Operation::RebalanceTerm);
+        plan.sales.push(source_correction);
+        plan.sales.push(target_correction);
+        Ok(plan)
+    }
+
+    /// Checks the section exists and accepts postings dated `date`. `label`
+    /// names the section's role in error messages.
+    fn check_active(
+        &self,
+        section_id: Uuid,
+        date: NaiveDateTime,
+        label: &str,
+    ) -> Result<(), ServiceError> {
+        let section = self
+            .section_repo
+            .find_by_id(&section_id)
+            .ok_or_else(|| format!("{} not found", label))?;
+        if !section.is_active_on(date.date()) {
+            return Err(format!("{} is inactive on that date", label).into());
+        }
+        Ok(())
+    }
+
+    /// Checks `actor` may decide on `request` without writing it: the
+    /// operation it performs, on each section it names, as approval checks it.
+    pub fn authorize_request(
+        &self,
+        request: &CorrectionRequest,
+        actor: &ActorId,
+    ) -> Result<(), ServiceError> {
+        let plan = match *request {
+            CorrectionRequest::Correct {
+                term_id,
+                section_id,
+                ..
+            } => Plan::new(term_id, Operation::CorrectTerm).on([section_id]),
+            CorrectionRequest::Rebalance {
+                term_id,
+                source_section_id,
+                target_section_id,
+                ..
+            } => Plan::new(term_id, Operation::RebalanceTerm)
+                .on([source_section_id, target_section_id]),
+        };
+        self.authorize(&plan, actor)
+    }
+
+    /// Authorizes the plan for the acting user, then stamps it with `audit`
+    /// timed by the service's clock.
+    fn checked(&self, plan: Plan, mut audit: AuditInfo) -> Result<Plan, ServiceError> {
+        self.authorize(&plan, &audit.actor)?;
+        audit.timestamp = self.clock.now();
+        plan.stamped(audit, self.ids.as_ref())
+    }
+
+    /// Checks the policy for every section the plan writes to: the parent of
+    /// a new section, a changed section itself (and its new parent when it
+    /// moves), and the section of each sales leg, so a transfer needs
+    /// permission on both its source and target. A replay is checked against
+    /// the sections its request names, so it never returns another actor's
+    /// IDs to someone who could not have made the request.
+    fn authorize(&self, plan: &Plan, actor: &ActorId) -> Result<(), ServiceError> {
+        let loaded;
+        let policy = match &self.grants {
+            Some(load) => {
+                loaded = load()?;
+                loaded.as_ref()
+            }
+            None => self.policy.as_ref(),
+        };
+        let policy = match policy {
+            Some(policy) => policy,
+            None => return Ok(()),
+        };
+
+        let mut sections: Vec<Option<Uuid>> = Vec::new();
+        for section in &plan.sections {
+            match self.section_repo.find_by_id(&section.id) {
+                Some(current) => {
+                    sections.push(Some(section.id));
+                    if current.parent_id != section.parent_id {
+                        sections.push(section.parent_id);
+                    }
+                }
+                None => sections.push(section.parent_id),
+            }
+        }
+        sections.extend(plan.sales.iter().map(|s| Some(s.section_id)));
+        sections.extend(plan.subjects.iter().map(|id| Some(*id)));
+        if sections.is_empty() {
+            sections.push(None);
+        }
+        sections.dedup();
 
+        for section_id in sections {
+            let path = section_id.map_or_else(Vec::new, |id| self.section_repo.find_path(&id));
+            policy
+                .check(actor, plan.operation, &path)
+                .map_err(ServiceError::Forbidden)?;
+        }
         Ok(())
     }
+
+    /// Writes a plan in order: sections, terms, sales legs, then the audit log.
+    fn apply(&mut self, plan: Plan) -> Result<Uuid, ServiceError> {
+        let id = plan.id;
+        self.apply_all(vec![plan])?;
+        Ok(id)
+    }
+
+    /// Applies `plans` as one unit: their sales entries are saved together,
+    /// so either all of them are written or none. The trace still gets one
+    /// step per plan.
+    fn apply_all(&mut self, plans: Vec<Plan>) -> Result<(), ServiceError> {
+        self.check_new_ids(&plans)?;
+        for plan in &plans {
+            for section in &plan.sections {
+                self.section_repo.save(section.clone())?;
+            }
+            for term in &plan.terms {
+                self.term_repo.save(term.clone())?;
+            }
+        }
+        let sales: Vec<Sales> = plans.iter().flat_map(|p| p.sales.clone()).collect();
+        if !sales.is_empty() {
+            self.sales_repo.save_all(sales)?;
+        }
+        for plan in &plans {
+            for record in &plan.records {
+                self.audit_repo.append(record.clone())?;
+            }
+        }
+        if self.trace.is_some() {
+            // The ledger after each plan is the final one without the
+            // entries of the plans that follow it
+            let mut state = self.ledger_state();
+            let mut steps = Vec::new();
+            for plan in plans.iter().rev() {
+                if !plan.replayed {
+                    steps.push((plan.operation, plan.id, state.clone()));
+                }
+                for sales in &plan.sales {
+                    state.entries.remove(&sales.id);
+                }
+            }
+            if let Some(trace) = &mut self.trace {
+                for (operation, id, state) in steps.into_iter().rev() {
+                    trace.record(operation, id, state);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that no sales entry or audit record `plans` would write reuses
+    /// an ID, so a repeated generator (such as the same seed on another run)
+    /// cannot overwrite posted entries.
+    fn check_new_ids(&self, plans: &[Plan]) -> Result<(), ServiceError> {
+        let mut seen = HashSet::new();
+        for plan in plans {
+            for sales in &plan.sales {
+                if !seen.insert(sales.id) || self.sales_repo.find_by_id(&sales.id).is_some() {
+                    return Err(format!("Sales {} already exists", sales.id).into());
+                }
+            }
+            for record in &plan.records {
+                if !seen.insert(record.id) || self.audit_repo.find_by_id(&record.id).is_some() {
+                    return Err(format!("Audit record {} already exists", record.id).into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn ledger_state(&self) -> LedgerState {
+        let terms = self.term_repo.list_all();
+        let entries = terms
+            .iter()
+            .flat_map(|t| self.sales_repo.find_by_term(&t.id))
+            .collect();
+        LedgerState::capture(self.section_repo.list_all(), terms, entries)
+    }
+
+    fn preview(&self, plan: Plan) -> Preview {
+        let mut balances: Vec<BalanceChange> = Vec::new();
+        for sales in &plan.sales {
+            let index = match balances
+                .iter()
+                .position(|b| b.term_id == sales.term_id && b.section_id == sales.section_id)
+            {
+                Some(index) => index,
+                None => {
+                    let before = self
+                        .sales_repo
+                        .find_by_section_and_term(&sales.section_id, &sales.term_id)
+                        .iter()
+                        .fold(Money::zero(), |acc, s| acc + s.amount);
+                    balances.push(BalanceChange {
+                        term_id: sales.term_id,
+                        section_id: sales.section_id,
+                        section_name: self
+                            .section_repo
+                            .find_by_id(&sales.section_id)
+                            .map(|s| s.name),
+                        before,
+                        after: before,
+                        change: Money::zero(),
+                    });
+                    balances.len() - 1
+                }
+            };
+            let balance = &mut balances[index];
+            balance.after = balance.after + sales.amount;
+            balance.change = balance.change + sales.amount;
+        }
+
+        Preview {
+            replayed: plan.replayed,
+            sections: plan.sections,
+            terms: plan.terms,
+            sales: plan.sales,
+            balances,
+        }
+    }
+
+    /// Checks whether a correction request carrying `key` was already applied.
+    /// Returns `Ok(true)` on replay of the same legs, an error if the key was
+    /// used for a different request, and `Ok(false)` if it is unused.
+    fn replayed_correction(
+        &self,
+        key: &IdempotencyKey,
+        term_id: Uuid,
+        date: NaiveDateTime,
+        legs: &[(Uuid, Money)],
+    ) -> Result<bool, ServiceError> {
+        let entries = self.sales_repo.find_by_idempotency_key(key);
+        if entries.is_empty() {
+            return Ok(false);
+        }
+
+        let mut expected = legs.to_vec();
+        expected.sort();
+        let mut actual: Vec<(Uuid, Money)> = entries
+            .iter()
+            .filter(|e| {
+                e.sales_type == SalesType::Correction && e.term_id == term_id && e.date == date
+            })
+            .map(|e| (e.section_id, e.amount))
+            .collect();
+        actual.sort();
+
+        if actual.len() != entries.len() || actual != expected {
+            return Err(ServiceError::idempotency_mismatch());
+        }
+        Ok(true)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::entity::SectionType;
-    use crate::infrastructure::in_memory::{
-        InMemorySalesRepository, InMemorySectionRepository, InMemoryTermRepository,
-    };
+    use crate::domain::entity::{Role, RoleGrant, SectionType, TermStatus};
+    use crate::test_support::{audit, service};
     use chrono::NaiveDate;
     use rust_decimal::Decimal;
     use std::str::FromStr;
 
     #[test]
     fn test_register_sales_success() {
-        let mut service = AccountingService::new(
-            InMemorySectionRepository::new(),
-            InMemoryTermRepository::new(),
-            InMemorySalesRepository::new(),
+        let mut service = service();
+
+        let section = Section::new(
+            service.ids().next_id(),
+            "Test Section".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_id = service.create_section(section, None, audit()).unwrap();
+
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        service.create_term(term, None, audit()).unwrap();
+
+        let amount = Money::new(Decimal::from_str("100.00").unwrap());
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+
+        let result = service.register_sales(amount, date, section_id, None, audit());
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_register_sales_outside_term() {
+        let mut service = service();
+
+        let section = Section::new(
+            service.ids().next_id(),
+            "Test Section".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_id = service.create_section(section, None, audit()).unwrap();
+
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        service.create_term(term, None, audit()).unwrap();
+
+        let amount = Money::new(Decimal::from_str("100.00").unwrap());
+        let date = NaiveDate::from_ymd_opt(2024, 12, 31)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap(); // Outside
+
+        let result = service.register_sales(amount, date, section_id, None, audit());
+        assert!(result.is_err());
+    }
+    #[test]
+    fn test_correct_term_success() {
+        let mut service = service();
+
+        let section = Section::new(
+            service.ids().next_id(),
+            "Test Section".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_id = service.create_section(section, None, audit()).unwrap();
+
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+        service.close_term(term_id, None, audit()).unwrap();
+
+        let original_amount = Money::new(Decimal::from_str("100.00").unwrap());
+        let correct_amount = Money::new(Decimal::from_str("150.00").unwrap());
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+
+        let result = service.correct_term(
+            term_id,
+            section_id,
+            original_amount,
+            correct_amount,
+            date,
+            None,
+            audit(),
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_correct_term_invalid_section() {
+        let mut service = service();
+
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+        let invalid_section_id = Uuid::new_v4();
+
+        let original_amount = Money::new(Decimal::from_str("100.00").unwrap());
+        let correct_amount = Money::new(Decimal::from_str("150.00").unwrap());
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+
+        let result = service.correct_term(
+            term_id,
+            invalid_section_id,
+            original_amount,
+            correct_amount,
+            date,
+            None,
+            audit(),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_rebalance_term_success() {
+        let mut service = service();
+
+        let section_a = Section::new(
+            service.ids().next_id(),
+            "Section A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_a_id = service.create_section(section_a, None, audit()).unwrap();
+        let section_b = Section::new(
+            service.ids().next_id(),
+            "Section B".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_b_id = service.create_section(section_b, None, audit()).unwrap();
+
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+
+        let amount = Money::new(Decimal::from_str("100.00").unwrap());
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+
+        let result = service.rebalance_term(
+            term_id,
+            section_a_id,
+            section_b_id,
+            amount,
+            date,
+            None,
+            audit(),
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_transform_sales_same_section() {
+        let mut service = service();
+
+        let section = Section::new(
+            service.ids().next_id(),
+            "Section A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_id = service.create_section(section, None, audit()).unwrap();
+
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        service.create_term(term, None, audit()).unwrap();
+
+        let amount = Money::new(Decimal::from_str("100.00").unwrap());
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+
+        let sales_id = service
+            .register_sales(amount, date, section_id, None, audit())
+            .unwrap();
+
+        let result = service.transform_sales(sales_id, section_id, date, None, audit());
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err(),
+            ServiceError::Rejected("Cannot transfer sales to the same section".to_string())
+        );
+    }
+
+    #[test]
+    fn test_rebalance_term_same_section() {
+        let mut service = service();
+
+        let section = Section::new(
+            service.ids().next_id(),
+            "Section A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_id = service.create_section(section, None, audit()).unwrap();
+
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+
+        let amount = Money::new(Decimal::from_str("100.00").unwrap());
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+
+        let result =
+            service.rebalance_term(term_id, section_id, section_id, amount, date, None, audit());
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err(),
+            ServiceError::Rejected("Cannot rebalance between the same section".to_string())
+        );
+    }
+
+    #[test]
+    fn test_rebalance_term_negative_amount() {
+        let mut service = service();
+
+        let section_a = Section::new(
+            service.ids().next_id(),
+            "Section A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_a_id = service.create_section(section_a, None, audit()).unwrap();
+        let section_b = Section::new(
+            service.ids().next_id(),
+            "Section B".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_b_id = service.create_section(section_b, None, audit()).unwrap();
+
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+
+        let amount = Money::new(Decimal::from_str("-100.00").unwrap());
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+
+        let result = service.rebalance_term(
+            term_id,
+            section_a_id,
+            section_b_id,
+            amount,
+            date,
+            None,
+            audit(),
+        );
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err(),
+            ServiceError::Rejected("Rebalance amount must be positive".to_string())
         );
+    }
 
-        let section = Section::new("Test Section".to_string(), SectionType::Section, None).unwrap();
-        let section_id = service.create_section(section).unwrap();
+    #[test]
+    fn test_register_sales_idempotent_replay() {
+        let mut service = service();
+
+        let section = Section::new(
+            service.ids().next_id(),
+            "Test Section".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_id = service.create_section(section, None, audit()).unwrap();
 
         let term = Term::new(
+            service.ids().next_id(),
             NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
             NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
         )
         .unwrap();
-        service.create_term(term).unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
 
         let amount = Money::new(Decimal::from_str("100.00").unwrap());
         let date = NaiveDate::from_ymd_opt(2025, 6, 1)
             .unwrap()
             .and_hms_opt(10, 0, 0)
             .unwrap();
+        let key = IdempotencyKey::new("pos-1".to_string()).unwrap();
 
-        let result = service.register_sales(amount, date, section_id);
-        assert!(result.is_ok());
+        let first = service
+            .register_sales(amount, date, section_id, Some(key.clone()), audit())
+            .unwrap();
+        let second = service
+            .register_sales(amount, date, section_id, Some(key.clone()), audit())
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(service.sales_repo.find_by_term(&term_id).len(), 1);
+
+        // Reusing the key for a different request is rejected
+        let other_amount = Money::new(Decimal::from_str("200.00").unwrap());
+        let result =
+            service.register_sales(other_amount, date, section_id, Some(key.clone()), audit());
+        assert_eq!(result.unwrap_err(), ServiceError::idempotency_mismatch());
+
+        // A replay is authorized like the request, so it does not hand the
+        // entry ID to an actor without permission
+        let service = service.with_policy(AccessPolicy::new(Vec::new()));
+        let result = service.preview_register_sales(amount, date, section_id, Some(key), audit());
+        assert!(result.is_err());
     }
 
     #[test]
-    fn test_register_sales_outside_term() {
-        let mut service = AccountingService::new(
-            InMemorySectionRepository::new(),
-            InMemoryTermRepository::new(),
-            InMemorySalesRepository::new(),
-        );
+    fn test_batch_rejects_a_repeated_idempotency_key() {
+        let mut service = service();
+
+        let section = Section::new(
+            service.ids().next_id(),
+            "Test Section".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_id = service.create_section(section, None, audit()).unwrap();
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        let registration = |amount: &str| Registration {
+            amount: Money::new(Decimal::from_str(amount).unwrap()),
+            date,
+            section_id,
+            idempotency_key: Some(IdempotencyKey::new("pos-1".to_string()).unwrap()),
+        };
+        let result =
+            service.register_sales_batch(vec![registration("100"), registration("200")], audit());
+        assert!(matches!(result, Err(ServiceError::Rejected(_))));
+        assert!(service.sales_repo.find_by_term(&term_id).is_empty());
+    }
 
-        let section = Section::new("Test Section".to_string(), SectionType::Section, None).unwrap();
-        let section_id = service.create_section(section).unwrap();
+    #[test]
+    fn test_rebalance_term_idempotent_replay() {
+        let mut service = service();
+
+        let section_a = Section::new(
+            service.ids().next_id(),
+            "Section A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_a_id = service.create_section(section_a, None, audit()).unwrap();
+        let section_b = Section::new(
+            service.ids().next_id(),
+            "Section B".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_b_id = service.create_section(section_b, None, audit()).unwrap();
 
         let term = Term::new(
+            service.ids().next_id(),
             NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
             NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
         )
         .unwrap();
-        service.create_term(term).unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
 
         let amount = Money::new(Decimal::from_str("100.00").unwrap());
-        let date = NaiveDate::from_ymd_opt(2024, 12, 31)
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
             .unwrap()
             .and_hms_opt(10, 0, 0)
-            .unwrap(); // Outside
+            .unwrap();
+        let key = IdempotencyKey::new("rebalance-1".to_string()).unwrap();
+
+        for _ in 0..2 {
+            service
+                .rebalance_term(
+                    term_id,
+                    section_a_id,
+                    section_b_id,
+                    amount,
+                    date,
+                    Some(key.clone()),
+                    audit(),
+                )
+                .unwrap();
+        }
+        assert_eq!(service.sales_repo.find_by_term(&term_id).len(), 2);
 
-        let result = service.register_sales(amount, date, section_id);
-        assert!(result.is_err());
-    }
-    #[test]
-    fn test_correct_term_success() {
-        let mut service = AccountingService::new(
-            InMemorySectionRepository::new(),
-            InMemoryTermRepository::new(),
-            InMemorySalesRepository::new(),
+        // The same legs on another date are a different request
+        let result = service.rebalance_term(
+            term_id,
+            section_a_id,
+            section_b_id,
+            amount,
+            date + chrono::Duration::days(1),
+            Some(key),
+            audit(),
         );
+        assert_eq!(result.unwrap_err(), ServiceError::idempotency_mismatch());
+        assert_eq!(service.sales_repo.find_by_term(&term_id).len(), 2);
+    }
 
-        let section = Section::new("Test Section".to_string(), SectionType::Section, None).unwrap();
-        let section_id = service.create_section(section).unwrap();
+    #[test]
+    fn test_preview_transform_sales_writes_nothing() {
+        let mut service = service();
+
+        let section_a = Section::new(
+            service.ids().next_id(),
+            "Section A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_a_id = service.create_section(section_a, None, audit()).unwrap();
+        let section_b = Section::new(
+            service.ids().next_id(),
+            "Section B".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_b_id = service.create_section(section_b, None, audit()).unwrap();
 
         let term = Term::new(
+            service.ids().next_id(),
             NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
             NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
         )
         .unwrap();
-        let term_id = service.create_term(term).unwrap();
-        service.close_term(term_id).unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
 
-        let original_amount = Money::new(Decimal::from_str("100.00").unwrap());
-        let correct_amount = Money::new(Decimal::from_str("150.00").unwrap());
+        let amount = Money::new(Decimal::from_str("100.00").unwrap());
         let date = NaiveDate::from_ymd_opt(2025, 6, 1)
             .unwrap()
             .and_hms_opt(10, 0, 0)
             .unwrap();
+        let sales_id = service
+            .register_sales(amount, date, section_a_id, None, audit())
+            .unwrap();
 
-        let result =
-            service.correct_term(term_id, section_id, original_amount, correct_amount, date);
-        assert!(result.is_ok());
+        let preview = service
+            .preview_transform_sales(sales_id, section_b_id, date, None, audit())
+            .unwrap();
+        assert!(!preview.replayed);
+        assert_eq!(preview.sales.len(), 2);
+        assert!(
+            preview
+                .sales
+                .iter()
+                .all(|s| s.sales_type == SalesType::Adjustment
+                    && s.related_sales_id == Some(sales_id))
+        );
+
+        assert_eq!(preview.balances.len(), 2);
+        let source = &preview.balances[0];
+        assert_eq!(source.section_id, section_a_id);
+        assert_eq!(source.section_name.as_deref(), Some("Section A"));
+        assert_eq!(source.before, amount);
+        assert_eq!(source.after, Money::zero());
+        assert_eq!(source.change, -amount);
+        let target = &preview.balances[1];
+        assert_eq!(target.section_id, section_b_id);
+        assert_eq!(target.before, Money::zero());
+        assert_eq!(target.after, amount);
+
+        // Nothing was persisted
+        assert_eq!(service.sales_repo.find_by_term(&term_id).len(), 1);
+        assert!(service
+            .preview_transform_sales(sales_id, section_a_id, date, None, audit())
+            .is_err());
     }
 
     #[test]
-    fn test_correct_term_invalid_section() {
-        let mut service = AccountingService::new(
-            InMemorySectionRepository::new(),
-            InMemoryTermRepository::new(),
-            InMemorySalesRepository::new(),
-        );
+    fn test_preview_matches_replay_and_close() {
+        let mut service = service();
+
+        let section_a = Section::new(
+            service.ids().next_id(),
+            "Section A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_a_id = service.create_section(section_a, None, audit()).unwrap();
+        let section_b = Section::new(
+            service.ids().next_id(),
+            "Section B".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_b_id = service.create_section(section_b, None, audit()).unwrap();
 
         let term = Term::new(
+            service.ids().next_id(),
             NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
             NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
         )
         .unwrap();
-        let term_id = service.create_term(term).unwrap();
-        let invalid_section_id = Uuid::new_v4();
+        let term_id = service.create_term(term, None, audit()).unwrap();
 
-        let original_amount = Money::new(Decimal::from_str("100.00").unwrap());
-        let correct_amount = Money::new(Decimal::from_str("150.00").unwrap());
+        let amount = Money::new(Decimal::from_str("100.00").unwrap());
         let date = NaiveDate::from_ymd_opt(2025, 6, 1)
             .unwrap()
             .and_hms_opt(10, 0, 0)
             .unwrap();
+        let key = IdempotencyKey::new("rebalance-1".to_string()).unwrap();
+        service
+            .rebalance_term(
+                term_id,
+                section_a_id,
+                section_b_id,
+                amount,
+                date,
+                Some(key.clone()),
+                audit(),
+            )
+            .unwrap();
 
-        let result = service.correct_term(
-            term_id,
-            invalid_section_id,
-            original_amount,
-            correct_amount,
-            date,
+        let replay = service
+            .preview_rebalance_term(
+                term_id,
+                section_a_id,
+                section_b_id,
+                amount,
+                date,
+                Some(key),
+                audit(),
+            )
+            .unwrap();
+        assert!(replay.replayed);
+        assert!(replay.sales.is_empty() && replay.balances.is_empty());
+
+        let close = service.preview_close_term(term_id, None, audit()).unwrap();
+        assert_eq!(close.terms[0].status, TermStatus::Closed);
+        assert_eq!(
+            service.find_term(&term_id).unwrap().status,
+            TermStatus::Open
         );
-        assert!(result.is_err());
     }
 
     #[test]
-    fn test_rebalance_term_success() {
-        let mut service = AccountingService::new(
-            InMemorySectionRepository::new(),
-            InMemoryTermRepository::new(),
-            InMemorySalesRepository::new(),
-        );
-
-        let section_a = Section::new("Section A".to_string(), SectionType::Section, None).unwrap();
-        let section_a_id = service.create_section(section_a).unwrap();
-        let section_b = Section::new("Section B".to_string(), SectionType::Section, None).unwrap();
-        let section_b_id = service.create_section(section_b).unwrap();
+    fn test_policy_limits_clerk_to_their_division() {
+        let mut service = service();
+
+        let division_x = Section::new(
+            service.ids().next_id(),
+            "Division X".to_string(),
+            SectionType::Division,
+            None,
+        )
+        .unwrap();
+        let division_x_id = service.create_section(division_x, None, audit()).unwrap();
+        let division_y = Section::new(
+            service.ids().next_id(),
+            "Division Y".to_string(),
+            SectionType::Division,
+            None,
+        )
+        .unwrap();
+        let division_y_id = service.create_section(division_y, None, audit()).unwrap();
+        let section_x = Section::new(
+            service.ids().next_id(),
+            "Section X1".to_string(),
+            SectionType::Section,
+            Some(division_x_id),
+        )
+        .unwrap();
+        let section_x_id = service.create_section(section_x, None, audit()).unwrap();
+        let section_x2 = Section::new(
+            service.ids().next_id(),
+            "Section X2".to_string(),
+            SectionType::Section,
+            Some(division_x_id),
+        )
+        .unwrap();
+        let section_x2_id = service.create_section(section_x2, None, audit()).unwrap();
 
         let term = Term::new(
+            service.ids().next_id(),
             NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
             NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
         )
         .unwrap();
-        let term_id = service.create_term(term).unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
 
         let amount = Money::new(Decimal::from_str("100.00").unwrap());
         let date = NaiveDate::from_ymd_opt(2025, 6, 1)
             .unwrap()
             .and_hms_opt(10, 0, 0)
             .unwrap();
+        let in_x = service
+            .register_sales(amount, date, section_x_id, None, audit())
+            .unwrap();
+        let in_y = service
+            .register_sales(amount, date, division_y_id, None, audit())
+            .unwrap();
 
-        let result = service.rebalance_term(term_id, section_a_id, section_b_id, amount, date);
-        assert!(result.is_ok());
+        let clerk = ActorId::new("carol".to_string()).unwrap();
+        let mut service = service.with_policy(AccessPolicy::new(vec![RoleGrant::new(
+            Uuid::new_v4(),
+            clerk.clone(),
+            Role::Clerk,
+            Some(division_x_id),
+        )]));
+        let as_clerk = || AuditInfo::new(clerk.clone(), "test", &SystemClock);
+
+        // Within Division X
+        service
+            .transform_sales(in_x, section_x2_id, date, None, as_clerk())
+            .unwrap();
+        // Out of Division Y, or into it
+        assert!(service
+            .transform_sales(in_y, section_x_id, date, None, as_clerk())
+            .is_err());
+        assert!(service
+            .preview_transform_sales(in_x, division_y_id, date, None, as_clerk())
+            .is_err());
+        // Clerks cannot close terms or rebalance
+        assert!(service.close_term(term_id, None, as_clerk()).is_err());
+        assert!(service
+            .rebalance_term(
+                term_id,
+                section_x_id,
+                section_x2_id,
+                amount,
+                date,
+                None,
+                as_clerk().with_reason(Some("test".to_string())),
+            )
+            .is_err());
+        assert_eq!(service.list_sales(&term_id).len(), 4);
+        assert_eq!(
+            service.find_term(&term_id).unwrap().status,
+            TermStatus::Open
+        );
     }
 
     #[test]
-    fn test_transform_sales_same_section() {
-        let mut service = AccountingService::new(
-            InMemorySectionRepository::new(),
-            InMemoryTermRepository::new(),
-            InMemorySalesRepository::new(),
+    fn test_section_changes_replay_their_idempotency_key() {
+        let mut service = service();
+        let section = Section::new(
+            service.ids().next_id(),
+            "A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_id = service.create_section(section, None, audit()).unwrap();
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+
+        let april = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap();
+        let may = NaiveDate::from_ymd_opt(2025, 5, 1).unwrap();
+        let key = IdempotencyKey::new("rename-1".to_string()).unwrap();
+        service
+            .rename_section(
+                section_id,
+                "B".to_string(),
+                april,
+                Some(key.clone()),
+                audit(),
+            )
+            .unwrap();
+        service
+            .rename_section(section_id, "C".to_string(), may, None, audit())
+            .unwrap();
+
+        // A late retry of the first rename does not undo the second
+        service
+            .rename_section(
+                section_id,
+                "B".to_string(),
+                april,
+                Some(key.clone()),
+                audit(),
+            )
+            .unwrap();
+        assert_eq!(service.find_section(&section_id).unwrap().name, "C");
+
+        // The key cannot be reused for another change or a new section
+        let result = service.move_section(section_id, None, may, Some(key.clone()), audit());
+        assert_eq!(result.unwrap_err(), ServiceError::idempotency_mismatch());
+        let other = Section::new(
+            service.ids().next_id(),
+            "C".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let result = service.create_section(other, Some(key), audit());
+        assert_eq!(result.unwrap_err(), ServiceError::idempotency_mismatch());
+
+        let key = IdempotencyKey::new("submit-1".to_string()).unwrap();
+        service
+            .submit_section(term_id, section_id, Some(key.clone()), audit())
+            .unwrap();
+        service
+            .submit_section(term_id, section_id, Some(key.clone()), audit())
+            .unwrap();
+        let result = service.submit_section(term_id, Uuid::new_v4(), Some(key), audit());
+        assert_eq!(result.unwrap_err(), ServiceError::idempotency_mismatch());
+        assert_eq!(
+            service.find_term(&term_id).unwrap().submitted_sections,
+            vec![section_id]
         );
+    }
+
+    #[test]
+    fn test_rename_and_move_are_effective_dated() {
+        let mut service = service();
+
+        let division_x = Section::new(
+            service.ids().next_id(),
+            "Division X".to_string(),
+            SectionType::Division,
+            None,
+        )
+        .unwrap();
+        let division_x_id = service.create_section(division_x, None, audit()).unwrap();
+        let division_y = Section::new(
+            service.ids().next_id(),
+            "Division Y".to_string(),
+            SectionType::Division,
+            None,
+        )
+        .unwrap();
+        let division_y_id = service.create_section(division_y, None, audit()).unwrap();
+        let section = Section::new(
+            service.ids().next_id(),
+            "Tokyo".to_string(),
+            SectionType::Section,
+            Some(division_x_id),
+        )
+        .unwrap();
+        let section_id = service.create_section(section, None, audit()).unwrap();
 
-        let section = Section::new("Section A".to_string(), SectionType::Section, None).unwrap();
-        let section_id = service.create_section(section).unwrap();
+        let april = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap();
+        let march = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+        service
+            .rename_section(section_id, "Tokyo East".to_string(), april, None, audit())
+            .unwrap();
+        service
+            .move_section(section_id, Some(division_y_id), april, None, audit())
+            .unwrap();
+        // Cannot move a division under its own section, or record changes out of order
+        assert!(service
+            .move_section(division_y_id, Some(section_id), april, None, audit())
+            .is_err());
+        assert!(service
+            .rename_section(section_id, "Tokyo".to_string(), march, None, audit())
+            .is_err());
+
+        let section = service.find_section(&section_id).unwrap();
+        assert_eq!(section.name, "Tokyo East");
+        assert_eq!(section.name_on(march), "Tokyo");
+        assert_eq!(section.name_on(april), "Tokyo East");
+        assert_eq!(section.parent_on(march), Some(division_x_id));
+        assert_eq!(section.parent_on(april), Some(division_y_id));
+
+        let history = service.audit_log().history(section_id);
+        let actions: Vec<AuditAction> = history.iter().map(|r| r.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                AuditAction::Created,
+                AuditAction::Renamed,
+                AuditAction::Moved
+            ]
+        );
+    }
 
+    #[test]
+    fn test_merge_transfers_open_balance_and_blocks_postings() {
+        let mut service = service();
+
+        let section_a = Section::new(
+            service.ids().next_id(),
+            "Section A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_a_id = service.create_section(section_a, None, audit()).unwrap();
+        let section_b = Section::new(
+            service.ids().next_id(),
+            "Section B".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_b_id = service.create_section(section_b, None, audit()).unwrap();
         let term = Term::new(
+            service.ids().next_id(),
             NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
             NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
         )
         .unwrap();
-        service.create_term(term).unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
 
         let amount = Money::new(Decimal::from_str("100.00").unwrap());
-        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
+        let june = NaiveDate::from_ymd_opt(2025, 6, 1)
             .unwrap()
             .and_hms_opt(10, 0, 0)
             .unwrap();
+        service
+            .register_sales(amount, june, section_a_id, None, audit())
+            .unwrap();
 
-        let sales_id = service.register_sales(amount, date, section_id).unwrap();
+        // Entries after the merge date would be stranded
+        let may = NaiveDate::from_ymd_opt(2025, 5, 1).unwrap();
+        assert!(service
+            .merge_sections(section_a_id, section_b_id, may, None, audit())
+            .is_err());
 
-        let result = service.transform_sales(sales_id, section_id, date);
-        assert!(result.is_err());
+        let july = NaiveDate::from_ymd_opt(2025, 7, 1).unwrap();
+        let preview = service
+            .preview_merge_sections(section_a_id, section_b_id, july, None, audit())
+            .unwrap();
+        assert_eq!(preview.balances.len(), 2);
+        service
+            .merge_sections(section_a_id, section_b_id, july, None, audit())
+            .unwrap();
+        // Replay writes nothing
+        service
+            .merge_sections(section_a_id, section_b_id, july, None, audit())
+            .unwrap();
+
+        let summary = service.reports().term_summary(term_id).unwrap();
+        let total = |id: Uuid| {
+            summary
+                .sections
+                .iter()
+                .find(|s| s.section_id == id)
+                .unwrap()
+                .total
+        };
+        assert!(total(section_a_id).amount().is_zero());
+        assert_eq!(total(section_b_id), amount);
+        assert_eq!(service.list_sales(&term_id).len(), 3);
+
+        // Both legs name the merged section's leg, so they can be paired
+        let legs: Vec<Sales> = service
+            .list_sales(&term_id)
+            .into_iter()
+            .filter(|s| s.sales_type == SalesType::Adjustment)
+            .collect();
+        let outgoing = legs.iter().find(|s| s.section_id == section_a_id).unwrap();
+        let incoming = legs.iter().find(|s| s.section_id == section_b_id).unwrap();
+        assert_eq!(outgoing.merge_anchor_id, Some(outgoing.id));
+        assert_eq!(incoming.merge_anchor_id, Some(outgoing.id));
+        let lineage = service.lineage().lineage(incoming.id).unwrap();
+        assert_eq!(lineage.root_id, outgoing.id);
+        assert_eq!(lineage.edges, vec![(outgoing.id, incoming.id)]);
+
+        let merged = service.find_section(&section_a_id).unwrap();
+        assert_eq!(merged.merged_into, Some(section_b_id));
+        // History before the merge date can still be posted; later dates cannot
+        assert!(service
+            .register_sales(amount, june, section_a_id, None, audit())
+            .is_ok());
+        let august = NaiveDate::from_ymd_opt(2025, 8, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        assert!(service
+            .register_sales(amount, august, section_a_id, None, audit())
+            .is_err());
+    }
+
+    #[test]
+    fn test_deactivation_only_checks_the_open_term() {
+        let mut service = service();
+
+        let section_a = Section::new(
+            service.ids().next_id(),
+            "Section A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_a_id = service.create_section(section_a, None, audit()).unwrap();
+        let section_b = Section::new(
+            service.ids().next_id(),
+            "Section B".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_b_id = service.create_section(section_b, None, audit()).unwrap();
+        let last_year = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let last_year_id = service.create_term(last_year, None, audit()).unwrap();
+        let amount = Money::new(Decimal::from(100));
+        let december = NaiveDate::from_ymd_opt(2024, 12, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        for section_id in [section_a_id, section_b_id] {
+            service
+                .register_sales(amount, december, section_id, None, audit())
+                .unwrap();
+        }
+        service.close_term(last_year_id, None, audit()).unwrap();
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        service.create_term(term, None, audit()).unwrap();
+        let june = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        service
+            .register_sales(amount, june, section_a_id, None, audit())
+            .unwrap();
+
+        // An entry in the open term blocks; entries in a closed term do not
+        let november = NaiveDate::from_ymd_opt(2024, 11, 1).unwrap();
         assert_eq!(
-            result.unwrap_err(),
-            "Cannot transfer sales to the same section"
+            service
+                .preview_deactivate_section(section_a_id, november, None, audit())
+                .unwrap_err(),
+            ServiceError::Rejected("Section has entries dated on or after that date".to_string())
         );
+        service
+            .deactivate_section(section_b_id, november, None, audit())
+            .unwrap();
     }
 
     #[test]
-    fn test_rebalance_term_same_section() {
-        let mut service = AccountingService::new(
-            InMemorySectionRepository::new(),
-            InMemoryTermRepository::new(),
-            InMemorySalesRepository::new(),
-        );
+    fn test_section_codes_are_unique() {
+        let mut service = service();
+        let code = SectionCode::new("JP-TKY".to_string()).unwrap();
+
+        let division = Section::new(
+            service.ids().next_id(),
+            "Japan".to_string(),
+            SectionType::Division,
+            None,
+        )
+        .unwrap()
+        .with_code(code.clone());
+        let division_id = service.create_section(division, None, audit()).unwrap();
+        let duplicate = Section::new(
+            service.ids().next_id(),
+            "Tokyo".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap()
+        .with_code(code.clone());
+        assert!(service.create_section(duplicate, None, audit()).is_err());
+
+        let section = Section::new(
+            service.ids().next_id(),
+            "Tokyo".to_string(),
+            SectionType::Section,
+            Some(division_id),
+        )
+        .unwrap()
+        .with_code(SectionCode::new("JP-TKY-01".to_string()).unwrap());
+        let section_id = service.create_section(section, None, audit()).unwrap();
 
-        let section = Section::new("Section A".to_string(), SectionType::Section, None).unwrap();
-        let section_id = service.create_section(section).unwrap();
+        assert_eq!(
+            service.find_section_by_code(&code).map(|s| s.id),
+            Some(division_id)
+        );
+        let children: Vec<Uuid> = service
+            .find_children(&division_id)
+            .iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(children, vec![section_id]);
+    }
 
+    #[test]
+    fn test_close_checklist_blocks_until_overridden() {
+        use crate::domain::entity::{CorrectionRequest, PendingCorrection};
+        use crate::domain::repository::PendingCorrectionRepository;
+        use crate::infrastructure::in_memory::InMemoryPendingCorrectionRepository;
+
+        let mut service = service();
+        let dept = Section::new(
+            service.ids().next_id(),
+            "Dept".to_string(),
+            SectionType::Department,
+            None,
+        )
+        .unwrap();
+        let dept_id = service.create_section(dept, None, audit()).unwrap();
+        let leaf = Section::new(
+            service.ids().next_id(),
+            "Leaf".to_string(),
+            SectionType::Section,
+            Some(dept_id),
+        )
+        .unwrap();
+        let leaf_id = service.create_section(leaf, None, audit()).unwrap();
         let term = Term::new(
+            service.ids().next_id(),
             NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
             NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
         )
         .unwrap();
-        let term_id = service.create_term(term).unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+
+        let mut corrections = InMemoryPendingCorrectionRepository::new();
+        let draft = PendingCorrection::new(
+            Uuid::new_v4(),
+            CorrectionRequest::Correct {
+                term_id,
+                section_id: leaf_id,
+                original_amount: Money::new(Decimal::from(10)),
+                correct_amount: Money::new(Decimal::from(12)),
+                date: NaiveDate::from_ymd_opt(2025, 6, 1)
+                    .unwrap()
+                    .and_hms_opt(10, 0, 0)
+                    .unwrap(),
+            },
+            ActorId::new("maker".to_string()).unwrap(),
+            "typo".to_string(),
+        )
+        .unwrap();
+        let draft_id = draft.id;
+        corrections.save(draft).unwrap();
+        let mut service =
+            service.with_close_checklist(CloseChecklist::standard().with_corrections(corrections));
+
+        // Only the leaf section has to submit; the draft correction is pending
+        let Err(CloseError::Blocked(failures)) = service.close_term(term_id, None, audit()) else {
+            panic!("close should be blocked");
+        };
+        let blocking: Vec<_> = failures
+            .iter()
+            .map(|f| (f.check, f.entity_ids.clone()))
+            .collect();
+        assert_eq!(
+            blocking,
+            vec![
+                (CloseCheck::NoPendingCorrections, vec![draft_id]),
+                (CloseCheck::SectionsSubmitted, vec![leaf_id]),
+            ]
+        );
 
-        let amount = Money::new(Decimal::from_str("100.00").unwrap());
-        let date = NaiveDate::from_ymd_opt(2025, 6, 1)
-            .unwrap()
-            .and_hms_opt(10, 0, 0)
+        service
+            .submit_section(term_id, leaf_id, None, audit())
+            .unwrap();
+        let again = service
+            .preview_submit_section(term_id, leaf_id, None, audit())
+            .unwrap();
+        assert!(again.replayed);
+        assert_eq!(service.close_failures(term_id).unwrap().len(), 1);
+
+        assert!(matches!(
+            service.close_term(term_id, Some(" ".to_string()), audit()),
+            Err(CloseError::Rejected(_))
+        ));
+        service
+            .close_term(term_id, Some("approved offline".to_string()), audit())
             .unwrap();
-
-        let result = service.rebalance_term(term_id, section_id, section_id, amount, date);
-        assert!(result.is_err());
         assert_eq!(
-            result.unwrap_err(),
-            "Cannot rebalance between the same section"
+            service.find_term(&term_id).unwrap().status,
+            TermStatus::Closed
+        );
+        let closed = service
+            .audit_log()
+            .history(term_id)
+            .into_iter()
+            .find(|r| r.action == AuditAction::Closed)
+            .unwrap();
+        assert_eq!(
+            closed.audit.reason.as_deref(),
+            Some("test; closed despite failed checks (NoPendingCorrections): approved offline")
         );
+
+        // A closed term is left alone, whatever the checklist says
+        assert!(service.close_term(term_id, None, audit()).is_ok());
     }
 
     #[test]
-    fn test_rebalance_term_negative_amount() {
-        let mut service = AccountingService::new(
-            InMemorySectionRepository::new(),
-            InMemoryTermRepository::new(),
-            InMemorySalesRepository::new(),
+    fn test_close_reads_corrections_filed_after_startup() {
+        use crate::domain::entity::{CorrectionRequest, PendingCorrection};
+        use crate::domain::repository::PendingCorrectionRepository;
+        use crate::infrastructure::file_store::FilePendingCorrectionRepository;
+
+        let dir = std::env::temp_dir().join(format!("accounting-close-{}", Uuid::new_v4()));
+        let store = dir.clone();
+        let mut service = service().with_close_checklist(
+            CloseChecklist::new(&[CloseCheck::NoPendingCorrections])
+                .with_corrections_from(move || FilePendingCorrectionRepository::open(&store)),
+        );
+        let section = Section::new(
+            service.ids().next_id(),
+            "Tokyo".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_id = service.create_section(section, None, audit()).unwrap();
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+        assert!(service.close_failures(term_id).unwrap().is_empty());
+
+        // Another process files a correction while this service is running
+        let draft = PendingCorrection::new(
+            Uuid::new_v4(),
+            CorrectionRequest::Correct {
+                term_id,
+                section_id,
+                original_amount: Money::new(Decimal::from(10)),
+                correct_amount: Money::new(Decimal::from(12)),
+                date: NaiveDate::from_ymd_opt(2025, 6, 1)
+                    .unwrap()
+                    .and_hms_opt(10, 0, 0)
+                    .unwrap(),
+            },
+            ActorId::new("maker".to_string()).unwrap(),
+            "typo".to_string(),
+        )
+        .unwrap();
+        let draft_id = draft.id;
+        FilePendingCorrectionRepository::open(&dir)
+            .unwrap()
+            .save(draft)
+            .unwrap();
+
+        let Err(CloseError::Blocked(failures)) = service.close_term(term_id, None, audit()) else {
+            panic!("close should be blocked");
+        };
+        assert_eq!(failures[0].entity_ids, vec![draft_id]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_revoking_every_grant_leaves_the_store_closed() {
+        use crate::domain::repository::RoleGrantRepository;
+        use crate::infrastructure::in_memory::InMemoryRoleGrantRepository;
+
+        let mut grants = InMemoryRoleGrantRepository::new();
+        let mut boss = RoleGrant::new(
+            Uuid::new_v4(),
+            ActorId::new("boss".to_string()).unwrap(),
+            Role::Controller,
+            None,
         );
+        boss.revoke().unwrap();
+        grants.save(boss).unwrap();
+
+        let mut service = service().with_policy(AccessPolicy::load(&grants).unwrap());
+        let section = Section::new(
+            service.ids().next_id(),
+            "X".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let mallory = AuditInfo::new(
+            ActorId::new("mallory".to_string()).unwrap(),
+            "test",
+            &SystemClock,
+        );
+        assert!(matches!(
+            service.create_section(section, None, mallory),
+            Err(ServiceError::Forbidden(_))
+        ));
+    }
 
-        let section_a = Section::new("Section A".to_string(), SectionType::Section, None).unwrap();
-        let section_a_id = service.create_section(section_a).unwrap();
-        let section_b = Section::new("Section B".to_string(), SectionType::Section, None).unwrap();
-        let section_b_id = service.create_section(section_b).unwrap();
+    #[test]
+    fn test_policy_reads_grants_made_after_startup() {
+        use crate::domain::repository::RoleGrantRepository;
+        use crate::infrastructure::file_store::FileRoleGrantRepository;
+
+        let dir = std::env::temp_dir().join(format!("accounting-grants-{}", Uuid::new_v4()));
+        let store = dir.clone();
+        let mut service = service().with_policy_from(move || FileRoleGrantRepository::open(&store));
+        let section =
+            |id| Section::new(id, "Tokyo".to_string(), SectionType::Section, None).unwrap();
+
+        // Nothing granted yet, so anyone may write
+        let first = section(service.ids().next_id());
+        assert!(service.create_section(first, None, audit()).is_ok());
+
+        // Another process grants the first role while this service is running
+        let controller = ActorId::new("dave".to_string()).unwrap();
+        FileRoleGrantRepository::open(&dir)
+            .unwrap()
+            .save(RoleGrant::new(
+                Uuid::new_v4(),
+                controller.clone(),
+                Role::Controller,
+                None,
+            ))
+            .unwrap();
+
+        let second = section(service.ids().next_id());
+        assert!(matches!(
+            service.create_section(second, None, audit()),
+            Err(ServiceError::Forbidden(_))
+        ));
+        let third = section(service.ids().next_id());
+        let as_controller = AuditInfo::new(controller, "test", &SystemClock);
+        assert!(service.create_section(third, None, as_controller).is_ok());
 
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_closing_a_closed_term_writes_nothing() {
+        let mut service = service().with_trace();
         let term = Term::new(
+            service.ids().next_id(),
             NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
             NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
         )
         .unwrap();
-        let term_id = service.create_term(term).unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
+        service.close_term(term_id, None, audit()).unwrap();
+        let records = service.audit_log().history(term_id).len();
+        let steps = service.trace().unwrap().steps().len();
+
+        service.close_term(term_id, None, audit()).unwrap();
+        let preview = service.preview_close_term(term_id, None, audit()).unwrap();
+        assert!(preview.replayed);
+        assert!(preview.terms.is_empty());
+        assert_eq!(service.audit_log().history(term_id).len(), records);
+        assert_eq!(service.trace().unwrap().steps().len(), steps);
+    }
 
-        let amount = Money::new(Decimal::from_str("-100.00").unwrap());
+    #[test]
+    fn test_seeded_ids_and_fixed_clock_make_runs_repeatable() {
+        use super::super::export::{ExportFormat, ExportScope};
+        use crate::domain::provider::{FixedClock, SequentialIds};
+        use std::sync::Arc;
+
+        let start = NaiveDate::from_ymd_opt(2025, 6, 30)
+            .unwrap()
+            .and_hms_opt(18, 0, 0)
+            .unwrap();
+
+        let run = || {
+            let ids = Arc::new(SequentialIds::new());
+            let clock = Arc::new(FixedClock::new(start));
+            let mut service = service()
+                .with_id_generator(ids.clone())
+                .with_clock(clock.clone());
+
+            let section_a = Section::new(
+                service.ids().next_id(),
+                "Section A".to_string(),
+                SectionType::Section,
+                None,
+            )
+            .unwrap();
+            let a = service.create_section(section_a, None, audit()).unwrap();
+            let section_b = Section::new(
+                service.ids().next_id(),
+                "Section B".to_string(),
+                SectionType::Section,
+                None,
+            )
+            .unwrap();
+            let b = service.create_section(section_b, None, audit()).unwrap();
+            let term = Term::new(
+                service.ids().next_id(),
+                NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+            )
+            .unwrap();
+            let term_id = service.create_term(term, None, audit()).unwrap();
+
+            let date = NaiveDate::from_ymd_opt(2025, 6, 1)
+                .unwrap()
+                .and_hms_opt(10, 0, 0)
+                .unwrap();
+            let amount = Money::new(Decimal::from_str("100.00").unwrap());
+            let sale = service
+                .register_sales(amount, date, a, None, audit())
+                .unwrap();
+            clock.advance(chrono::Duration::hours(1));
+            service
+                .transform_sales(sale, b, date, None, audit())
+                .unwrap();
+
+            let mut ledger = Vec::new();
+            service
+                .exporter()
+                .export(ExportScope::Term(term_id), ExportFormat::Csv, &mut ledger)
+                .unwrap();
+            let log: Vec<(Uuid, Uuid, NaiveDateTime)> = service
+                .audit_log()
+                .query(&Default::default())
+                .into_iter()
+                .map(|r| (r.id, r.entity_id, r.audit.timestamp))
+                .collect();
+            (sale, String::from_utf8(ledger).unwrap(), log)
+        };
+
+        let (sale, ledger, log) = run();
+        assert_eq!(run(), (sale, ledger.clone(), log.clone()));
+
+        // Each section and the term take an ID, then one for their audit record
+        assert_eq!(sale, Uuid::from_u128(7));
+        assert!(ledger.contains(&Uuid::from_u128(7).to_string()));
+        assert_eq!(log[0], (Uuid::from_u128(2), Uuid::from_u128(1), start));
+        assert_eq!(log.len(), 6);
+        assert_eq!(log[5].2, start + chrono::Duration::hours(1));
+    }
+
+    #[test]
+    fn test_amounts_beyond_the_entry_limit_are_rejected() {
+        let mut service = service();
+        let section = Section::new(
+            service.ids().next_id(),
+            "Section A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_id = service.create_section(section, None, audit()).unwrap();
+        let other = Section::new(
+            service.ids().next_id(),
+            "Section B".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let other_id = service.create_section(other, None, audit()).unwrap();
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service.create_term(term, None, audit()).unwrap();
         let date = NaiveDate::from_ymd_opt(2025, 6, 1)
             .unwrap()
             .and_hms_opt(10, 0, 0)
             .unwrap();
 
-        let result = service.rebalance_term(term_id, section_a_id, section_b_id, amount, date);
-        assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Rebalance amount must be positive");
+        let limit = Money::new(Decimal::from(Money::MAX_ENTRY));
+        let over = Money::new(Decimal::MAX);
+        let fine = Money::new(Decimal::new(1, 28));
+        assert!(service
+            .register_sales(limit, date, section_id, None, audit())
+            .is_ok());
+        assert!(service
+            .register_sales(-over, date, section_id, None, audit())
+            .is_err());
+        assert!(service
+            .register_sales(fine, date, section_id, None, audit())
+            .is_err());
+        assert!(service
+            .rebalance_term(term_id, section_id, other_id, over, date, None, audit())
+            .is_err());
+        assert!(service
+            .correct_term(term_id, section_id, limit, fine, date, None, audit())
+            .is_err());
+        assert_eq!(service.list_sales(&term_id).len(), 1);
+
+        // An extreme attempt leaves nothing behind that blocks later postings
+        let normal = Money::new(Decimal::new(10000, 2));
+        service
+            .register_sales(normal, date, section_id, None, audit())
+            .unwrap();
+        service
+            .rebalance_term(term_id, section_id, other_id, normal, date, None, audit())
+            .unwrap();
+        assert_eq!(service.list_sales(&term_id).len(), 4);
+
+        // Every report adds up without overflowing
+        let reports = service.reports();
+        assert!(reports.term_summary(term_id).is_ok());
+        assert!(reports.term_movement(term_id).is_ok());
+        assert!(reports.term_integrity(term_id).unwrap().is_consistent());
+        assert!(reports
+            .term_rollup(term_id, super::super::report::Structure::AsOf)
+            .is_ok());
     }
 }
@@ -0,0 +1,392 @@
+use super::authorization::Operation;
+use crate::domain::entity::{Sales, SalesType, Section, Term, TermStatus};
+use crate::domain::value_object::Money;
+use chrono::{Datelike, NaiveDate};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+use uuid::Uuid;
+
+/// What the TLA+ specification in `spec/tla` knows about a section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionState {
+    pub parent_id: Option<Uuid>,
+    pub deactivated_on: Option<NaiveDate>,
+    pub merged_into: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TermState {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub status: TermStatus,
+    pub submitted_sections: Vec<Uuid>,
+}
+
+/// A sales entry as the specification sees it: dated by day, without audit
+/// details or idempotency keys.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryState {
+    pub term_id: Uuid,
+    pub section_id: Uuid,
+    pub amount: Money,
+    pub sales_type: SalesType,
+    pub related_sales_id: Option<Uuid>,
+    pub date: NaiveDate,
+}
+
+/// The part of the ledger the specification models.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LedgerState {
+    pub sections: BTreeMap<Uuid, SectionState>,
+    pub terms: BTreeMap<Uuid, TermState>,
+    pub entries: BTreeMap<Uuid, EntryState>,
+}
+
+impl LedgerState {
+    pub fn capture(sections: Vec<Section>, terms: Vec<Term>, entries: Vec<Sales>) -> Self {
+        Self {
+            sections: sections
+                .into_iter()
+                .map(|s| {
+                    let state = SectionState {
+                        parent_id: s.parent_id,
+                        deactivated_on: s.deactivated_on,
+                        merged_into: s.merged_into,
+                    };
+                    (s.id, state)
+                })
+                .collect(),
+            terms: terms
+                .into_iter()
+                .map(|t| {
+                    let state = TermState {
+                        start_date: t.start_date,
+                        end_date: t.end_date,
+                        status: t.status,
+                        submitted_sections: t.submitted_sections,
+                    };
+                    (t.id, state)
+                })
+                .collect(),
+            entries: entries
+                .into_iter()
+                .map(|s| {
+                    let state = EntryState {
+                        term_id: s.term_id,
+                        section_id: s.section_id,
+                        amount: s.amount,
+                        sales_type: s.sales_type,
+                        related_sales_id: s.related_sales_id,
+                        date: s.date.date(),
+                    };
+                    (s.id, state)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// One applied operation: what ran, the ID it returned, and the ledger after.
+#[derive(Debug, Clone)]
+pub struct TraceStep {
+    pub action: Operation,
+    pub subject: Uuid,
+    pub state: LedgerState,
+}
+
+/// The operations a service applied since tracing started, for validating
+/// against the TLA+ specification with TLC. Replayed requests write nothing
+/// and are not recorded.
+#[derive(Debug, Clone)]
+pub struct TraceLog {
+    initial: LedgerState,
+    steps: Vec<TraceStep>,
+}
+
+impl TraceLog {
+    pub fn new(initial: LedgerState) -> Self {
+        Self {
+            initial,
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, action: Operation, subject: Uuid, state: LedgerState) {
+        self.steps.push(TraceStep {
+            action,
+            subject,
+            state,
+        });
+    }
+
+    pub fn initial(&self) -> &LedgerState {
+        &self.initial
+    }
+
+    pub fn steps(&self) -> &[TraceStep] {
+        &self.steps
+    }
+
+    /// Writes the trace as a TLA+ module named `module` defining `TraceInit`
+    /// and `TraceLog`, which `AccountingTrace.tla` replays. IDs become small
+    /// integers in order of appearance (0 stands for none), dates become
+    /// `YYYYMMDD` numbers and amounts integers in units of
+    /// 10^-`AmountScale`.
+    pub fn write_tla<W: Write>(&self, module: &str, mut writer: W) -> io::Result<()> {
+        let states = std::iter::once(&self.initial).chain(self.steps.iter().map(|s| &s.state));
+        let scale = states
+            .clone()
+            .flat_map(|state| state.entries.values())
+            .map(|e| e.amount.amount().scale())
+            .max()
+            .unwrap_or(0);
+        let mut ids = Ids::default();
+        for state in states {
+            ids.assign(state);
+        }
+
+        let rule = "-".repeat(4);
+        writeln!(writer, "{} MODULE {} {}", rule, module, rule)?;
+        writeln!(
+            writer,
+            "\\* Written by accounting_spec; replayed by AccountingTrace.tla"
+        )?;
+        writeln!(writer, "EXTENDS Integers, TLC")?;
+        writeln!(writer)?;
+        writeln!(writer, "AmountScale == {}", scale)?;
+        writeln!(writer)?;
+        writeln!(writer, "TraceInit == {}", ids.state(&self.initial, scale))?;
+        writeln!(writer)?;
+        writeln!(writer, "TraceLog == <<")?;
+        for (index, step) in self.steps.iter().enumerate() {
+            let separator = if index + 1 < self.steps.len() {
+                ","
+            } else {
+                ""
+            };
+            writeln!(
+                writer,
+                "    [action |-> \"{:?}\", subject |-> {}, state |-> {}]{}",
+                step.action,
+                ids.any(step.subject),
+                ids.state(&step.state, scale),
+                separator
+            )?;
+        }
+        writeln!(writer, ">>")?;
+        writeln!(writer, "{}", "=".repeat(4))
+    }
+}
+
+/// Small integer IDs for the sections, terms and entries of a trace.
+#[derive(Default)]
+struct Ids {
+    sections: HashMap<Uuid, usize>,
+    terms: HashMap<Uuid, usize>,
+    entries: HashMap<Uuid, usize>,
+}
+
+impl Ids {
+    fn assign(&mut self, state: &LedgerState) {
+        for (ids, keys) in [
+            (
+                &mut self.sections,
+                state.sections.keys().collect::<Vec<_>>(),
+            ),
+            (&mut self.terms, state.terms.keys().collect()),
+            (&mut self.entries, state.entries.keys().collect()),
+        ] {
+            for key in keys {
+                let next = ids.len() + 1;
+                ids.entry(*key).or_insert(next);
+            }
+        }
+    }
+
+    fn of(ids: &HashMap<Uuid, usize>, id: Option<Uuid>) -> usize {
+        id.and_then(|id| ids.get(&id).copied()).unwrap_or(0)
+    }
+
+    fn any(&self, id: Uuid) -> usize {
+        [&self.sections, &self.terms, &self.entries]
+            .into_iter()
+            .find_map(|ids| ids.get(&id).copied())
+            .unwrap_or(0)
+    }
+
+    fn state(&self, state: &LedgerState, scale: u32) -> String {
+        let sections = function(state.sections.iter().map(|(id, s)| {
+            (
+                self.sections[id],
+                format!(
+                    "[parent |-> {}, deactivated |-> {}, merged |-> {}]",
+                    Self::of(&self.sections, s.parent_id),
+                    s.deactivated_on.map_or(0, day),
+                    Self::of(&self.sections, s.merged_into)
+                ),
+            )
+        }));
+        let terms = function(state.terms.iter().map(|(id, t)| {
+            let mut submitted: Vec<usize> = t
+                .submitted_sections
+                .iter()
+                .map(|s| Self::of(&self.sections, Some(*s)))
+                .collect();
+            submitted.sort_unstable();
+            (
+                self.terms[id],
+                format!(
+                    "[start |-> {}, end |-> {}, status |-> \"{:?}\", submitted |-> {}]",
+                    day(t.start_date),
+                    day(t.end_date),
+                    t.status,
+                    set(submitted.iter().map(|s| s.to_string()))
+                ),
+            )
+        }));
+        let mut entries: Vec<(usize, &EntryState)> = state
+            .entries
+            .iter()
+            .map(|(id, e)| (self.entries[id], e))
+            .collect();
+        entries.sort_by_key(|(id, _)| *id);
+        let entries = set(entries.into_iter().map(|(id, e)| {
+            format!(
+                "[id |-> {}, term |-> {}, section |-> {}, amount |-> {}, type |-> \"{:?}\", related |-> {}, date |-> {}]",
+                id,
+                Self::of(&self.terms, Some(e.term_id)),
+                Self::of(&self.sections, Some(e.section_id)),
+                units(e.amount, scale),
+                e.sales_type,
+                Self::of(&self.entries, e.related_sales_id),
+                day(e.date)
+            )
+        }));
+        format!(
+            "[sections |-> {}, terms |-> {}, entries |-> {}]",
+            sections, terms, entries
+        )
+    }
+}
+
+/// A TLA+ function from small IDs, `<<>>` when empty.
+fn function(pairs: impl Iterator<Item = (usize, String)>) -> String {
+    let mut pairs: Vec<(usize, String)> = pairs.collect();
+    if pairs.is_empty() {
+        return "<<>>".to_string();
+    }
+    pairs.sort_by_key(|(id, _)| *id);
+    let pairs: Vec<String> = pairs
+        .into_iter()
+        .map(|(id, value)| format!("{} :> {}", id, value))
+        .collect();
+    format!("({})", pairs.join(" @@ "))
+}
+
+fn set(items: impl Iterator<Item = String>) -> String {
+    format!("{{{}}}", items.collect::<Vec<_>>().join(", "))
+}
+
+fn day(date: NaiveDate) -> i64 {
+    i64::from(date.year()) * 10_000 + i64::from(date.month()) * 100 + i64::from(date.day())
+}
+
+fn units(amount: Money, scale: u32) -> i128 {
+    let mut amount = amount.amount();
+    amount.rescale(scale);
+    amount.mantissa()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::entity::{Section, SectionType, Term};
+    use crate::domain::value_object::Money;
+    use crate::test_support::{audit, service};
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_trace_module_lists_each_applied_operation() {
+        let mut service = service();
+        let a = Section::new(
+            service.ids().next_id(),
+            "A".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let a = service.create_section(a, None, audit()).unwrap();
+        let mut service = service.with_trace();
+
+        let b = Section::new(
+            service.ids().next_id(),
+            "B".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let b = service.create_section(b, None, audit()).unwrap();
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        service.create_term(term, None, audit()).unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 6, 15)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        let sale = service
+            .register_sales(Money::new(Decimal::new(12345, 2)), date, a, None, audit())
+            .unwrap();
+        service
+            .transform_sales(sale, b, date, None, audit())
+            .unwrap();
+        // Moving to the current parent is a replay and is not recorded
+        service
+            .move_section(
+                b,
+                None,
+                NaiveDate::from_ymd_opt(2025, 7, 1).unwrap(),
+                None,
+                audit(),
+            )
+            .unwrap();
+
+        let trace = service.trace().unwrap();
+        assert_eq!(trace.initial().sections.len(), 1);
+        assert_eq!(trace.steps().len(), 4);
+        let mut module = Vec::new();
+        trace.write_tla("Trace", &mut module).unwrap();
+        let module = String::from_utf8(module).unwrap();
+
+        let lines: Vec<&str> = module.lines().collect();
+        assert_eq!(lines[0], "---- MODULE Trace ----");
+        assert_eq!(*lines.last().unwrap(), "====");
+        assert!(module.contains("AmountScale == 2"));
+        assert!(module.contains(
+            "TraceInit == [sections |-> (1 :> [parent |-> 0, deactivated |-> 0, merged |-> 0]), \
+             terms |-> <<>>, entries |-> {}]"
+        ));
+        let actions: Vec<&str> = lines
+            .iter()
+            .filter_map(|l| l.trim().strip_prefix("[action |-> \""))
+            .map(|l| l.split('"').next().unwrap())
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                "CreateSection",
+                "CreateTerm",
+                "RegisterSales",
+                "TransferSales"
+            ]
+        );
+        assert!(module.contains(
+            "[id |-> 1, term |-> 1, section |-> 1, amount |-> 12345, type |-> \"Normal\", \
+             related |-> 0, date |-> 20250615]"
+        ));
+        assert!(module.contains("amount |-> -12345, type |-> \"Adjustment\", related |-> 1"));
+    }
+}
@@ -0,0 +1,78 @@
+use accounting_spec::application::approval::CorrectionWorkflow;
+use accounting_spec::application::checklist::CloseChecklist;
+use accounting_spec::application::service::AccountingService;
+use accounting_spec::domain::provider::{FixedClock, SeededIds};
+use accounting_spec::infrastructure::file_store::{
+    FileAuditLogRepository, FilePendingCorrectionRepository, FileRoleGrantRepository,
+    FileSalesRepository, FileSectionRepository, FileTermRepository,
+};
+use accounting_spec::interface::http::ApiServer;
+use chrono::NaiveDateTime;
+use clap::Parser;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[derive(Debug, Parser)]
+#[command(name = "server", about = "JSON HTTP API over the accounting ledger")]
+struct Args {
+    /// Address to listen on. Callers name themselves in the unauthenticated
+    /// `X-Actor` header, so keep this on a local or trusted interface.
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    addr: String,
+
+    /// Directory holding the ledger files
+    #[arg(long, env = "ACCOUNTING_STORE", default_value = "accounting-data")]
+    store: PathBuf,
+
+    /// Draw new IDs from a sequence fixed by this seed instead of random ones.
+    /// Writes that draw an ID the store already holds are rejected
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Use this time (`YYYY-MM-DDTHH:MM:SS`) instead of the clock for audit
+    /// timestamps and the close checks
+    #[arg(long)]
+    now: Option<NaiveDateTime>,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    // Shared across requests so a seeded sequence does not restart.
+    let ids = args.seed.map(|seed| Arc::new(SeededIds::new(seed)));
+
+    // Opened afresh for every request so the server sees what the CLI wrote
+    // while it was running.
+    let open = || {
+        let store = &args.store;
+        let mut service = AccountingService::new(
+            FileSectionRepository::open(store)?,
+            FileTermRepository::open(store)?,
+            FileSalesRepository::open(store)?,
+            FileAuditLogRepository::open(store)?,
+        )
+        .with_close_checklist(CloseChecklist::standard().with_corrections_from({
+            let store = store.clone();
+            move || FilePendingCorrectionRepository::open(&store)
+        }))
+        .with_policy_from({
+            let store = store.clone();
+            move || FileRoleGrantRepository::open(&store)
+        });
+        if let Some(ids) = &ids {
+            service = service.with_id_generator(Arc::clone(ids));
+        }
+        if let Some(now) = args.now {
+            service = service.with_clock(FixedClock::new(now));
+        }
+        let workflow = CorrectionWorkflow::new(FilePendingCorrectionRepository::open(store)?);
+        Ok((service, workflow))
+    };
+    // Fail at startup rather than on the first request when the store is
+    // unreadable.
+    open()?;
+
+    let server = ApiServer::bind(&args.addr)?;
+    println!("Listening on http://{}", args.addr);
+    server.serve(open);
+    Ok(())
+}
@@ -0,0 +1,1459 @@
+use crate::repl;
+use accounting_spec::application::approval::CorrectionWorkflow;
+use accounting_spec::application::audit::AuditQuery;
+use accounting_spec::application::authorization::AccessPolicy;
+use accounting_spec::application::checklist::{CloseChecklist, CloseError, CloseFailure};
+use accounting_spec::application::export::{ExportFormat, ExportScope, LedgerExporter};
+use accounting_spec::application::preview::Preview;
+use accounting_spec::application::reference::{parse_date_time, resolve_section};
+use accounting_spec::application::render::{RenderFormat, Table, ToTable};
+use accounting_spec::application::report::{Interval, Structure};
+use accounting_spec::application::service::AccountingService;
+use accounting_spec::domain::entity::{
+    CorrectionRequest, CorrectionStatus, Role, RoleGrant, Section, SectionType, Term,
+};
+use accounting_spec::domain::provider::{Clock, FixedClock, SeededIds, SystemClock};
+use accounting_spec::domain::repository::{RoleGrantRepository, TermRepository};
+use accounting_spec::domain::value_object::{
+    ActorId, AuditInfo, IdempotencyKey, Money, SectionCode,
+};
+use accounting_spec::infrastructure::file_store::{
+    FileAuditLogRepository, FilePendingCorrectionRepository, FileRoleGrantRepository,
+    FileSalesLedger, FileSalesRepository, FileSectionRepository, FileTermRepository,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub type Service = AccountingService<
+    FileSectionRepository,
+    FileTermRepository,
+    FileSalesRepository,
+    FileAuditLogRepository,
+>;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "accounting_spec",
+    about = "Sales ledger with sections, terms and corrections"
+)]
+pub struct Cli {
+    /// Directory holding the ledger files
+    #[arg(
+        long,
+        global = true,
+        env = "ACCOUNTING_STORE",
+        default_value = "accounting-data"
+    )]
+    pub store: PathBuf,
+
+    /// Print results as JSON instead of tables
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// How tables are printed; Markdown and HTML suit reports to share
+    #[arg(long, global = true, value_enum, default_value = "text")]
+    pub format: FormatKind,
+
+    /// Show the entries a command would write and the balance changes,
+    /// without writing anything
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Who is running the command, recorded in the audit log (defaults to
+    /// the login name). Taken on trust: anyone who can write the store can
+    /// act as anyone
+    #[arg(long, global = true, env = "ACCOUNTING_ACTOR")]
+    pub actor: Option<String>,
+
+    /// Correlation ID recorded in the audit log with every change
+    #[arg(long, global = true)]
+    pub correlation_id: Option<String>,
+
+    /// Write the changes made as a TLA+ trace module (name the file
+    /// `Trace.tla` to check it with `spec/tla/AccountingTrace.tla`)
+    #[arg(long, global = true, value_name = "FILE")]
+    pub trace: Option<PathBuf>,
+
+    /// Draw new IDs from a sequence fixed by this seed instead of random
+    /// ones, to repeat a run exactly. Each command sharing a store needs its
+    /// own seed: a command drawing an ID the store already holds is rejected
+    #[arg(long, global = true)]
+    pub seed: Option<u64>,
+
+    /// Use this time instead of the clock for audit timestamps, the close
+    /// checks and default effective dates
+    #[arg(long, global = true, value_parser = parse_date_arg)]
+    pub now: Option<NaiveDateTime>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Manage sections
+    #[command(subcommand)]
+    Section(SectionCommand),
+    /// Manage terms
+    #[command(subcommand)]
+    Term(TermCommand),
+    /// Register, transfer and list sales
+    #[command(subcommand)]
+    Sales(SalesCommand),
+    /// File a correction for approval; see `approval`
+    Correct(CorrectArgs),
+    /// File a rebalance for approval; see `approval`
+    Rebalance(RebalanceArgs),
+    /// Maker-checker approval of corrections and rebalances
+    #[command(subcommand)]
+    Approval(ApprovalCommand),
+    /// Grant and revoke roles; enforced once the first role is granted
+    #[command(subcommand)]
+    Role(RoleCommand),
+    /// Recorded changes, oldest first
+    Audit {
+        /// Only changes to this section, term or sale
+        #[arg(long)]
+        entity: Option<Uuid>,
+        /// Only changes made by this actor
+        #[arg(long)]
+        by: Option<String>,
+        #[arg(long)]
+        correlation_id: Option<String>,
+    },
+    /// Per-section totals for a term
+    Report {
+        /// Term ID (defaults to the open term)
+        #[arg(long)]
+        term: Option<Uuid>,
+        /// Roll totals up the section hierarchy as it was during the term
+        #[arg(long)]
+        rollup: bool,
+        /// Roll up under the current hierarchy instead
+        #[arg(long, requires = "rollup")]
+        restate: bool,
+        /// Compare with another term, such as the prior one or the same term last year
+        #[arg(long, value_name = "BASE_TERM", conflicts_with = "rollup")]
+        compare: Option<Uuid>,
+        /// Write the comparison as CSV
+        #[arg(long, requires = "compare")]
+        csv: bool,
+        /// Roll-forward from normal sales to the closing balance, flagging
+        /// sections whose transfers do not tie out
+        #[arg(long, conflicts_with_all = ["rollup", "compare"])]
+        movement: bool,
+    },
+    /// Sales totals per day, week or month, with empty buckets shown as zero
+    Series {
+        #[arg(value_enum)]
+        interval: IntervalKind,
+        /// Only this section, by ID, code or name
+        #[arg(long)]
+        section: Option<String>,
+        /// Include the sections below `--section`
+        #[arg(long, requires = "section")]
+        subtree: bool,
+        /// Term ID (defaults to the open term)
+        #[arg(long, conflicts_with = "from")]
+        term: Option<Uuid>,
+        /// Start of a date range, across terms
+        #[arg(long, requires = "to")]
+        from: Option<NaiveDate>,
+        /// End of the date range, inclusive
+        #[arg(long, requires = "from")]
+        to: Option<NaiveDate>,
+    },
+    /// Write the ledger entries of a term or date range, oldest first
+    Export(ExportArgs),
+    /// Interactive shell with history, completion and correction drafts
+    Shell,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// Term ID (defaults to the open term)
+    #[arg(long, conflicts_with = "from")]
+    pub term: Option<Uuid>,
+    /// Start of a date range, across terms
+    #[arg(long, requires = "to")]
+    pub from: Option<NaiveDate>,
+    /// End of the date range, inclusive
+    #[arg(long, requires = "from")]
+    pub to: Option<NaiveDate>,
+    #[arg(
+        id = "export_format",
+        value_name = "FORMAT",
+        value_enum,
+        default_value = "csv"
+    )]
+    pub format: ExportKind,
+    /// Write to this file instead of standard output
+    #[arg(long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SectionCommand {
+    Add {
+        name: String,
+        /// Unique code, such as JP-TKY-01
+        #[arg(long)]
+        code: Option<String>,
+        #[arg(long = "type", value_enum, default_value = "section")]
+        section_type: SectionKind,
+        /// Parent section, by ID, code or name
+        #[arg(long)]
+        parent: Option<String>,
+        #[arg(long)]
+        key: Option<String>,
+    },
+    /// Rename a section; reports before the effective date keep the old name
+    Rename {
+        /// Section, by ID, code or name
+        section: String,
+        name: String,
+        /// First day of the new name (defaults to today)
+        #[arg(long)]
+        effective: Option<NaiveDate>,
+        #[arg(long)]
+        key: Option<String>,
+    },
+    /// Move a section under another parent
+    Move {
+        /// Section, by ID, code or name
+        section: String,
+        /// New parent section, by ID, code or name
+        #[arg(long, required_unless_present = "top_level")]
+        parent: Option<String>,
+        /// Make it a top-level section
+        #[arg(long, conflicts_with = "parent")]
+        top_level: bool,
+        /// First day under the new parent (defaults to today)
+        #[arg(long)]
+        effective: Option<NaiveDate>,
+        #[arg(long)]
+        key: Option<String>,
+    },
+    /// Stop a section accepting postings, keeping its history
+    Deactivate {
+        /// Section, by ID, code or name
+        section: String,
+        /// First day without postings (defaults to today)
+        #[arg(long)]
+        effective: Option<NaiveDate>,
+        #[arg(long)]
+        key: Option<String>,
+    },
+    /// Deactivate a section and move its open-term balance to another
+    Merge {
+        /// Section to close down, by ID, code or name
+        section: String,
+        /// Section taking over, by ID, code or name
+        #[arg(long)]
+        into: String,
+        /// First day of the merged structure (defaults to today)
+        #[arg(long)]
+        effective: Option<NaiveDate>,
+        #[arg(long)]
+        key: Option<String>,
+    },
+    List,
+    /// Show a section and everything below it, indented by depth
+    Tree {
+        /// Root of the tree, by ID, code or name (defaults to all top-level sections)
+        section: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SectionKind {
+    Department,
+    Division,
+    Section,
+}
+
+impl From<SectionKind> for SectionType {
+    fn from(kind: SectionKind) -> Self {
+        match kind {
+            SectionKind::Department => SectionType::Department,
+            SectionKind::Division => SectionType::Division,
+            SectionKind::Section => SectionType::Section,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum FormatKind {
+    Text,
+    Markdown,
+    Html,
+}
+
+impl From<FormatKind> for RenderFormat {
+    fn from(kind: FormatKind) -> Self {
+        match kind {
+            FormatKind::Text => RenderFormat::Text,
+            FormatKind::Markdown => RenderFormat::Markdown,
+            FormatKind::Html => RenderFormat::Html,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ExportKind {
+    Csv,
+    Json,
+}
+
+impl From<ExportKind> for ExportFormat {
+    fn from(kind: ExportKind) -> Self {
+        match kind {
+            ExportKind::Csv => ExportFormat::Csv,
+            ExportKind::Json => ExportFormat::Json,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum IntervalKind {
+    Day,
+    Week,
+    Month,
+}
+
+impl From<IntervalKind> for Interval {
+    fn from(kind: IntervalKind) -> Self {
+        match kind {
+            IntervalKind::Day => Interval::Day,
+            IntervalKind::Week => Interval::Week,
+            IntervalKind::Month => Interval::Month,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TermCommand {
+    Add {
+        start: NaiveDate,
+        end: NaiveDate,
+        #[arg(long)]
+        key: Option<String>,
+    },
+    /// Close a term once every pre-close check passes
+    Close {
+        term: Uuid,
+        /// Close despite failed checks, recording why
+        #[arg(long = "override", value_name = "REASON")]
+        override_reason: Option<String>,
+    },
+    /// Sign off a section's figures for a term
+    Submit {
+        /// Section, by ID, code or name
+        section: String,
+        /// Term ID (defaults to the open term)
+        #[arg(long)]
+        term: Option<Uuid>,
+        #[arg(long)]
+        key: Option<String>,
+    },
+    /// Check the ledger of a term is consistent; fails if it is not
+    Check {
+        /// Term ID (defaults to the open term)
+        term: Option<Uuid>,
+    },
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SalesCommand {
+    Register {
+        /// Section, by ID, code or name
+        #[arg(long)]
+        section: String,
+        #[arg(long)]
+        amount: Decimal,
+        /// `YYYY-MM-DD` or `YYYY-MM-DDTHH:MM:SS`
+        #[arg(long, value_parser = parse_date_arg)]
+        date: NaiveDateTime,
+        #[arg(long)]
+        key: Option<String>,
+    },
+    Transfer {
+        sales: Uuid,
+        /// Target section, by ID, code or name
+        #[arg(long)]
+        to: String,
+        #[arg(long, value_parser = parse_date_arg)]
+        date: NaiveDateTime,
+        #[arg(long)]
+        key: Option<String>,
+    },
+    List {
+        /// Term ID (defaults to the open term)
+        #[arg(long)]
+        term: Option<Uuid>,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct CorrectArgs {
+    #[arg(long)]
+    pub term: Uuid,
+    #[arg(long)]
+    pub section: String,
+    #[arg(long)]
+    pub original: Decimal,
+    #[arg(long)]
+    pub correct: Decimal,
+    #[arg(long, value_parser = parse_date_arg)]
+    pub date: NaiveDateTime,
+    /// Why the correction is needed; recorded in the audit log
+    #[arg(long)]
+    pub reason: String,
+}
+
+#[derive(Debug, Args)]
+pub struct RebalanceArgs {
+    #[arg(long)]
+    pub term: Uuid,
+    #[arg(long)]
+    pub from: String,
+    #[arg(long)]
+    pub to: String,
+    #[arg(long)]
+    pub amount: Decimal,
+    #[arg(long, value_parser = parse_date_arg)]
+    pub date: NaiveDateTime,
+    /// Why the rebalance is needed; recorded in the audit log
+    #[arg(long)]
+    pub reason: String,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ApprovalCommand {
+    /// Draft a correction; nothing is written until it is approved
+    Correct(CorrectArgs),
+    /// Draft a rebalance; nothing is written until it is approved
+    Rebalance(RebalanceArgs),
+    /// Submit your draft for approval
+    Submit { id: Uuid },
+    /// Approve a submitted request and write its entries
+    Approve { id: Uuid },
+    /// Reject a submitted request
+    Reject {
+        id: Uuid,
+        #[arg(long)]
+        reason: String,
+    },
+    /// Show the entries approval would write
+    Show { id: Uuid },
+    List {
+        #[arg(long, value_enum)]
+        status: Option<StatusKind>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum StatusKind {
+    Draft,
+    Submitted,
+    Approved,
+    Rejected,
+}
+
+impl From<StatusKind> for CorrectionStatus {
+    fn from(kind: StatusKind) -> Self {
+        match kind {
+            StatusKind::Draft => CorrectionStatus::Draft,
+            StatusKind::Submitted => CorrectionStatus::Submitted,
+            StatusKind::Approved => CorrectionStatus::Approved,
+            StatusKind::Rejected => CorrectionStatus::Rejected,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum RoleCommand {
+    Grant {
+        /// Who receives the role
+        #[arg(value_name = "ACTOR")]
+        grantee: String,
+        #[arg(long, value_enum)]
+        role: RoleKind,
+        /// Limit the role to this section and its descendants, by ID, code or name
+        #[arg(long)]
+        section: Option<String>,
+    },
+    Revoke {
+        grant: Uuid,
+    },
+    List,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum RoleKind {
+    Clerk,
+    Accountant,
+    Controller,
+    Auditor,
+}
+
+impl From<RoleKind> for Role {
+    fn from(kind: RoleKind) -> Self {
+        match kind {
+            RoleKind::Clerk => Role::Clerk,
+            RoleKind::Accountant => Role::Accountant,
+            RoleKind::Controller => Role::Controller,
+            RoleKind::Auditor => Role::Auditor,
+        }
+    }
+}
+
+fn parse_date_arg(value: &str) -> Result<NaiveDateTime, String> {
+    parse_date_time(value).ok_or_else(|| format!("invalid date: {}", value))
+}
+
+pub fn open_service(store: &Path) -> Result<Service, String> {
+    // Corrections are read at each close, so ones filed earlier in a shell
+    // count
+    let service = AccountingService::new(
+        FileSectionRepository::open(store)?,
+        FileTermRepository::open(store)?,
+        FileSalesRepository::open(store)?,
+        FileAuditLogRepository::open(store)?,
+    )
+    .with_close_checklist(CloseChecklist::standard().with_corrections_from({
+        let store = store.to_path_buf();
+        move || FilePendingCorrectionRepository::open(&store)
+    }));
+    Ok(
+        match AccessPolicy::load(&FileRoleGrantRepository::open(store)?) {
+            Some(policy) => service.with_policy(policy),
+            None => service,
+        },
+    )
+}
+
+/// Global options shared by every command of one invocation.
+pub struct Context {
+    pub store: PathBuf,
+    pub output: Output,
+    pub dry_run: bool,
+    pub actor: Option<String>,
+    pub correlation_id: Option<String>,
+    /// Recorded as the audit source of every change
+    pub source: &'static str,
+    /// Default effective date of reorganizations
+    pub today: NaiveDate,
+    /// The service's clock, for audit timestamps
+    pub clock: Arc<dyn Clock>,
+}
+
+impl Context {
+    pub fn actor(&self) -> Result<ActorId, String> {
+        let actor = self
+            .actor
+            .clone()
+            .or_else(|| std::env::var("USER").ok())
+            .or_else(|| std::env::var("USERNAME").ok())
+            .ok_or("Pass --actor (or set ACCOUNTING_ACTOR) to identify yourself")?;
+        Ok(ActorId::new(actor)?)
+    }
+
+    pub fn audit(&self, reason: Option<String>) -> Result<AuditInfo, String> {
+        Ok(
+            AuditInfo::new(self.actor()?, self.source, self.clock.as_ref())
+                .with_reason(reason)
+                .with_correlation_id(self.correlation_id.clone()),
+        )
+    }
+}
+
+pub fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    // Opening the service would load the whole ledger
+    let command = match cli.command {
+        Command::Export(args) => return export(&cli.store, args),
+        command => command,
+    };
+    let mut service = open_service(&cli.store)?;
+    if cli.trace.is_some() {
+        service = service.with_trace();
+    }
+    if let Some(seed) = cli.seed {
+        service = service.with_id_generator(SeededIds::new(seed));
+    }
+    let clock: Arc<dyn Clock> = match cli.now {
+        Some(now) => Arc::new(FixedClock::new(now)),
+        None => Arc::new(SystemClock),
+    };
+    service = service.with_clock(clock.clone());
+    let context = Context {
+        store: cli.store,
+        output: Output {
+            json: cli.json,
+            format: cli.format.into(),
+        },
+        dry_run: cli.dry_run,
+        actor: cli.actor,
+        correlation_id: cli.correlation_id,
+        source: "cli",
+        today: cli
+            .now
+            .map_or_else(|| chrono::Local::now().date_naive(), |now| now.date()),
+        clock,
+    };
+
+    let result = match command {
+        Command::Shell => repl::run(
+            &mut service,
+            &Context {
+                source: "shell",
+                ..context
+            },
+        ),
+        command => execute(&mut service, command, &context),
+    };
+    // Changes applied before a failure are still part of the trace
+    if let (Some(path), Some(trace)) = (&cli.trace, service.trace()) {
+        let module = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or("The trace file needs a name")?;
+        trace.write_tla(module, std::fs::File::create(path)?)?;
+    }
+    result
+}
+
+/// Writes the ledger straight from the store files, reading each entry as it
+/// is written rather than through the service.
+pub fn export(store: &Path, args: ExportArgs) -> Result<(), Box<dyn Error>> {
+    let sections = FileSectionRepository::open(store)?;
+    let terms = FileTermRepository::open(store)?;
+    let ledger = FileSalesLedger::open(store)?;
+    let scope = match (args.from, args.to, args.term) {
+        (Some(from), Some(to), _) => ExportScope::DateRange { from, to },
+        (_, _, Some(term)) => ExportScope::Term(term),
+        _ => ExportScope::Term(
+            terms
+                .find_open_term()
+                .ok_or("No open term found; pass --term")?
+                .id,
+        ),
+    };
+
+    let exporter = LedgerExporter::new(&sections, &terms, &ledger);
+    let format = args.format.into();
+    match args.output {
+        Some(path) => exporter.export(scope, format, BufWriter::new(File::create(path)?))?,
+        None => exporter.export(scope, format, std::io::stdout().lock())?,
+    };
+    match ledger.take_error() {
+        Some(error) => Err(error.into()),
+        None => Ok(()),
+    }
+}
+
+pub fn execute(
+    service: &mut Service,
+    command: Command,
+    context: &Context,
+) -> Result<(), Box<dyn Error>> {
+    let output = &context.output;
+    let dry_run = context.dry_run;
+    match command {
+        Command::Section(SectionCommand::Add {
+            name,
+            code,
+            section_type,
+            parent,
+            key,
+        }) => {
+            let parent_id = parent.map(|p| resolve_section(service, &p)).transpose()?;
+            let mut section = Section::new(
+                service.ids().next_id(),
+                name,
+                section_type.into(),
+                parent_id,
+            )?;
+            if let Some(code) = code {
+                section = section.with_code(SectionCode::new(code)?);
+            }
+            let key = idempotency_key(key)?;
+            if dry_run {
+                let preview = service.preview_create_section(section, key, context.audit(None)?)?;
+                return show_preview(service, output, &preview);
+            }
+            let id = service.create_section(section, key, context.audit(None)?)?;
+            output.id(id);
+        }
+        Command::Section(SectionCommand::Rename {
+            section,
+            name,
+            effective,
+            key,
+        }) => {
+            let section_id = resolve_section(service, &section)?;
+            let effective = effective.unwrap_or(context.today);
+            let key = idempotency_key(key)?;
+            let audit = context.audit(None)?;
+            if dry_run {
+                let preview =
+                    service.preview_rename_section(section_id, name, effective, key, audit)?;
+                return show_preview(service, output, &preview);
+            }
+            service.rename_section(section_id, name, effective, key, audit)?;
+            output.done(&format!("Renamed {} from {}", section_id, effective));
+        }
+        Command::Section(SectionCommand::Move {
+            section,
+            parent,
+            top_level: _,
+            effective,
+            key,
+        }) => {
+            let section_id = resolve_section(service, &section)?;
+            let parent_id = parent.map(|p| resolve_section(service, &p)).transpose()?;
+            let effective = effective.unwrap_or(context.today);
+            let key = idempotency_key(key)?;
+            let audit = context.audit(None)?;
+            if dry_run {
+                let preview =
+                    service.preview_move_section(section_id, parent_id, effective, key, audit)?;
+                return show_preview(service, output, &preview);
+            }
+            service.move_section(section_id, parent_id, effective, key, audit)?;
+            output.done(&format!("Moved {} from {}", section_id, effective));
+        }
+        Command::Section(SectionCommand::Deactivate {
+            section,
+            effective,
+            key,
+        }) => {
+            let section_id = resolve_section(service, &section)?;
+            let effective = effective.unwrap_or(context.today);
+            let key = idempotency_key(key)?;
+            let audit = context.audit(None)?;
+            if dry_run {
+                let preview =
+                    service.preview_deactivate_section(section_id, effective, key, audit)?;
+                return show_preview(service, output, &preview);
+            }
+            service.deactivate_section(section_id, effective, key, audit)?;
+            output.done(&format!("Deactivated {} from {}", section_id, effective));
+        }
+        Command::Section(SectionCommand::Merge {
+            section,
+            into,
+            effective,
+            key,
+        }) => {
+            let source_id = resolve_section(service, &section)?;
+            let target_id = resolve_section(service, &into)?;
+            let effective = effective.unwrap_or(context.today);
+            let key = idempotency_key(key)?;
+            let audit = context.audit(None)?;
+            if dry_run {
+                let preview =
+                    service.preview_merge_sections(source_id, target_id, effective, key, audit)?;
+                return show_preview(service, output, &preview);
+            }
+            service.merge_sections(source_id, target_id, effective, key, audit)?;
+            output.done(&format!("Merged {} into {}", source_id, target_id));
+        }
+        Command::Section(SectionCommand::List) => {
+            let mut sections = service.list_sections();
+            sections.sort_by(|a, b| a.name.cmp(&b.name));
+            output.table(
+                &sections,
+                &["ID", "Code", "Name", "Type", "Parent", "Inactive From"],
+                sections
+                    .iter()
+                    .map(|s| {
+                        vec![
+                            s.id.to_string(),
+                            s.code.as_ref().map(ToString::to_string).unwrap_or_default(),
+                            s.name.clone(),
+                            format!("{:?}", s.section_type),
+                            section_label(service, s.parent_id),
+                            s.deactivated_on.map(|d| d.to_string()).unwrap_or_default(),
+                        ]
+                    })
+                    .collect(),
+            );
+        }
+        Command::Section(SectionCommand::Tree { section }) => {
+            let roots = match section {
+                Some(section) => vec![resolve_section(service, &section)?],
+                None => {
+                    let mut roots: Vec<_> = service
+                        .list_sections()
+                        .into_iter()
+                        .filter(|s| s.parent_id.is_none())
+                        .collect();
+                    roots.sort_by(|a, b| a.name.cmp(&b.name));
+                    roots.into_iter().map(|s| s.id).collect()
+                }
+            };
+            let hierarchy = service.hierarchy();
+            let mut sections = Vec::new();
+            for root in roots {
+                sections.extend(hierarchy.subtree(root)?);
+            }
+            let mut table = Table::new(&["Name", "Code", "Type", "ID"]);
+            for s in &sections {
+                table.push_nested(
+                    vec![
+                        s.name.clone(),
+                        s.code.as_ref().map(ToString::to_string).unwrap_or_default(),
+                        format!("{:?}", s.section_type),
+                        s.id.to_string(),
+                    ],
+                    hierarchy.depth(s.id)?,
+                );
+            }
+            output.print(&sections, &table);
+        }
+        Command::Term(TermCommand::Add { start, end, key }) => {
+            let term = Term::new(service.ids().next_id(), start, end)?;
+            let key = idempotency_key(key)?;
+            if dry_run {
+                let preview = service.preview_create_term(term, key, context.audit(None)?)?;
+                return show_preview(service, output, &preview);
+            }
+            let id = service.create_term(term, key, context.audit(None)?)?;
+            output.id(id);
+        }
+        Command::Term(TermCommand::Close {
+            term,
+            override_reason,
+        }) => {
+            let result = if dry_run {
+                service
+                    .preview_close_term(term, override_reason, context.audit(None)?)
+                    .map(Some)
+            } else {
+                service
+                    .close_term(term, override_reason, context.audit(None)?)
+                    .map(|_| None)
+            };
+            match result {
+                Ok(Some(preview)) => return show_preview(service, output, &preview),
+                Ok(None) => output.done(&format!("Closed term {}", term)),
+                Err(CloseError::Blocked(failures)) => {
+                    output.print(&failures, &close_failures_table(&failures));
+                    return Err(format!(
+                        "Term {} cannot be closed: {} check(s) failed; pass --override <REASON> to close anyway",
+                        term,
+                        failures.len()
+                    )
+                    .into());
+                }
+                Err(error) => return Err(error.to_string().into()),
+            }
+        }
+        Command::Term(TermCommand::Submit { section, term, key }) => {
+            let term_id = term_or_open(service, term)?;
+            let section_id = resolve_section(service, &section)?;
+            let key = idempotency_key(key)?;
+            if dry_run {
+                let preview = service.preview_submit_section(
+                    term_id,
+                    section_id,
+                    key,
+                    context.audit(None)?,
+                )?;
+                return show_preview(service, output, &preview);
+            }
+            service.submit_section(term_id, section_id, key, context.audit(None)?)?;
+            output.done(&format!(
+                "Submitted {} for term {}",
+                section_label(service, Some(section_id)),
+                term_id
+            ));
+        }
+        Command::Term(TermCommand::Check { term }) => {
+            let term_id = term_or_open(service, term)?;
+            let report = service.reports().term_integrity(term_id)?;
+            output.report(&report);
+            if !report.is_consistent() {
+                return Err(format!("Term {} is not consistent", term_id).into());
+            }
+        }
+        Command::Term(TermCommand::List) => {
+            let mut terms = service.list_terms();
+            terms.sort_by_key(|t| t.start_date);
+            output.table(
+                &terms,
+                &["ID", "Start", "End", "Status"],
+                terms
+                    .iter()
+                    .map(|t| {
+                        vec![
+                            t.id.to_string(),
+                            t.start_date.to_string(),
+                            t.end_date.to_string(),
+                            format!("{:?}", t.status),
+                        ]
+                    })
+                    .collect(),
+            );
+        }
+        Command::Sales(SalesCommand::Register {
+            section,
+            amount,
+            date,
+            key,
+        }) => {
+            let section_id = resolve_section(service, &section)?;
+            let amount = Money::new(amount);
+            let key = idempotency_key(key)?;
+            if dry_run {
+                let preview = service.preview_register_sales(
+                    amount,
+                    date,
+                    section_id,
+                    key,
+                    context.audit(None)?,
+                )?;
+                return show_preview(service, output, &preview);
+            }
+            let id = service.register_sales(amount, date, section_id, key, context.audit(None)?)?;
+            output.id(id);
+        }
+        Command::Sales(SalesCommand::Transfer {
+            sales,
+            to,
+            date,
+            key,
+        }) => {
+            let target_id = resolve_section(service, &to)?;
+            let key = idempotency_key(key)?;
+            if dry_run {
+                let preview = service.preview_transform_sales(
+                    sales,
+                    target_id,
+                    date,
+                    key,
+                    context.audit(None)?,
+                )?;
+                return show_preview(service, output, &preview);
+            }
+            let id = service.transform_sales(sales, target_id, date, key, context.audit(None)?)?;
+            output.id(id);
+        }
+        Command::Sales(SalesCommand::List { term }) => {
+            let term_id = term_or_open(service, term)?;
+            let mut sales = service.list_sales(&term_id);
+            sales.sort_by(|a, b| a.date.cmp(&b.date).then(a.id.cmp(&b.id)));
+            output.table(
+                &sales,
+                &["ID", "Date", "Section", "Type", "Amount", "Related"],
+                sales
+                    .iter()
+                    .map(|s| {
+                        vec![
+                            s.id.to_string(),
+                            s.date.to_string(),
+                            section_label(service, Some(s.section_id)),
+                            format!("{:?}", s.sales_type),
+                            s.amount.to_string(),
+                            s.related_sales_id
+                                .map(|id| id.to_string())
+                                .unwrap_or_default(),
+                        ]
+                    })
+                    .collect(),
+            );
+        }
+        Command::Correct(args) => {
+            let request = correct_request(service, &args)?;
+            file_correction(service, request, args.reason, context)?;
+        }
+        Command::Rebalance(args) => {
+            let request = rebalance_request(service, &args)?;
+            file_correction(service, request, args.reason, context)?;
+        }
+        Command::Series {
+            interval,
+            section,
+            subtree,
+            term,
+            from,
+            to,
+        } => {
+            let scope = match (from, to) {
+                (Some(from), Some(to)) => ExportScope::DateRange { from, to },
+                _ => ExportScope::Term(term_or_open(service, term)?),
+            };
+            let section_id = section.map(|s| resolve_section(service, &s)).transpose()?;
+            let series =
+                service
+                    .reports()
+                    .sales_series(scope, section_id, subtree, interval.into())?;
+            output.report(&series);
+        }
+        Command::Audit {
+            entity,
+            by,
+            correlation_id,
+        } => {
+            let query = AuditQuery {
+                actor: by.map(ActorId::new).transpose()?,
+                entity_id: entity,
+                correlation_id,
+                ..AuditQuery::default()
+            };
+            let records = service.audit_log().query(&query);
+            output.table(
+                &records,
+                &[
+                    "Time", "Entity", "ID", "Action", "Actor", "Source", "Reason",
+                ],
+                records
+                    .iter()
+                    .map(|r| {
+                        vec![
+                            r.audit.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+                            format!("{:?}", r.entity),
+                            r.entity_id.to_string(),
+                            format!("{:?}", r.action),
+                            r.audit.actor.to_string(),
+                            r.audit.source.clone(),
+                            r.audit.reason.clone().unwrap_or_default(),
+                        ]
+                    })
+                    .collect(),
+            );
+        }
+        Command::Report {
+            term,
+            movement: true,
+            ..
+        } => {
+            let term_id = term_or_open(service, term)?;
+            output.report(&service.reports().term_movement(term_id)?);
+        }
+        Command::Report {
+            term,
+            compare: Some(base_term_id),
+            csv,
+            ..
+        } => {
+            let term_id = term_or_open(service, term)?;
+            let comparison = service.reports().term_comparison(base_term_id, term_id)?;
+            if csv {
+                comparison
+                    .write(ExportFormat::Csv, std::io::stdout().lock())
+                    .map_err(|e| e.to_string())?;
+                return Ok(());
+            }
+            output.report(&comparison);
+        }
+        Command::Report {
+            term,
+            rollup: true,
+            restate,
+            ..
+        } => {
+            let term_id = term_or_open(service, term)?;
+            let structure = if restate {
+                Structure::Restated
+            } else {
+                Structure::AsOf
+            };
+            output.report(&service.reports().term_rollup(term_id, structure)?);
+        }
+        Command::Report { term, .. } => {
+            let term_id = term_or_open(service, term)?;
+            output.report(&service.reports().term_summary(term_id)?);
+        }
+        Command::Approval(command) => approval(service, command, context)?,
+        Command::Role(command) => role(service, command, context)?,
+        Command::Export(args) => export(&context.store, args)?,
+        Command::Shell => return Err("Already in the shell".into()),
+    }
+    Ok(())
+}
+
+fn approval(
+    service: &mut Service,
+    command: ApprovalCommand,
+    context: &Context,
+) -> Result<(), Box<dyn Error>> {
+    let output = &context.output;
+    let mut workflow =
+        CorrectionWorkflow::new(FilePendingCorrectionRepository::open(&context.store)?);
+
+    match command {
+        ApprovalCommand::Correct(args) => {
+            let request = correct_request(service, &args)?;
+            output.id(workflow.draft(service, request, context.actor()?, args.reason)?);
+        }
+        ApprovalCommand::Rebalance(args) => {
+            let request = rebalance_request(service, &args)?;
+            output.id(workflow.draft(service, request, context.actor()?, args.reason)?);
+        }
+        ApprovalCommand::Submit { id } => {
+            workflow.submit(service, id, &context.actor()?)?;
+            output.done(&format!("Submitted {}", id));
+        }
+        ApprovalCommand::Approve { id } => {
+            if context.dry_run {
+                return show_preview(service, output, &workflow.preview(service, id)?);
+            }
+            workflow.approve(service, id, context.actor()?, context.source)?;
+            output.done(&format!("Approved {}", id));
+        }
+        ApprovalCommand::Reject { id, reason } => {
+            workflow.reject(service, id, context.actor()?, reason)?;
+            output.done(&format!("Rejected {}", id));
+        }
+        ApprovalCommand::Show { id } => {
+            show_preview(service, output, &workflow.preview(service, id)?)?;
+        }
+        ApprovalCommand::List { status } => {
+            let mut corrections = workflow.list(status.map(Into::into));
+            corrections.sort_by_key(|c| c.request.term_id());
+            output.table(
+                &corrections,
+                &["ID", "Status", "Request", "Term", "Maker", "Checker"],
+                corrections
+                    .iter()
+                    .map(|c| {
+                        let request = match &c.request {
+                            CorrectionRequest::Correct { .. } => "Correct",
+                            CorrectionRequest::Rebalance { .. } => "Rebalance",
+                        };
+                        vec![
+                            c.id.to_string(),
+                            format!("{:?}", c.status),
+                            request.to_string(),
+                            c.request.term_id().to_string(),
+                            c.maker.to_string(),
+                            c.checker
+                                .as_ref()
+                                .map(ToString::to_string)
+                                .unwrap_or_default(),
+                        ]
+                    })
+                    .collect(),
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn correct_request(
+    service: &Service,
+    args: &CorrectArgs,
+) -> Result<CorrectionRequest, Box<dyn Error>> {
+    Ok(CorrectionRequest::Correct {
+        term_id: args.term,
+        section_id: resolve_section(service, &args.section)?,
+        original_amount: Money::new(args.original),
+        correct_amount: Money::new(args.correct),
+        date: args.date,
+    })
+}
+
+pub fn rebalance_request(
+    service: &Service,
+    args: &RebalanceArgs,
+) -> Result<CorrectionRequest, Box<dyn Error>> {
+    Ok(CorrectionRequest::Rebalance {
+        term_id: args.term,
+        source_section_id: resolve_section(service, &args.from)?,
+        target_section_id: resolve_section(service, &args.to)?,
+        amount: Money::new(args.amount),
+        date: args.date,
+    })
+}
+
+/// Drafts and submits a correction in one step and prints the request ID;
+/// its entries are written once a different actor approves it. With
+/// `--dry-run`, shows what approval would write instead.
+fn file_correction(
+    service: &Service,
+    request: CorrectionRequest,
+    reason: String,
+    context: &Context,
+) -> Result<(), Box<dyn Error>> {
+    let mut workflow =
+        CorrectionWorkflow::new(FilePendingCorrectionRepository::open(&context.store)?);
+    if context.dry_run {
+        let preview = workflow.preview_draft(service, &request, context.actor()?, reason)?;
+        return show_preview(service, &context.output, &preview);
+    }
+    let maker = context.actor()?;
+    let id = workflow.draft(service, request, maker.clone(), reason)?;
+    workflow.submit(service, id, &maker)?;
+    context.output.id(id);
+    Ok(())
+}
+
+fn role(service: &Service, command: RoleCommand, context: &Context) -> Result<(), Box<dyn Error>> {
+    let output = &context.output;
+    let mut grants = FileRoleGrantRepository::open(&context.store)?;
+
+    match command {
+        RoleCommand::Grant {
+            grantee,
+            role,
+            section,
+        } => {
+            let scope = section.map(|s| resolve_section(service, &s)).transpose()?;
+            let grant = RoleGrant::new(
+                service.ids().next_id(),
+                ActorId::new(grantee)?,
+                role.into(),
+                scope,
+            );
+            AccessPolicy::check_grant(&grants, &context.actor()?, &grant)?;
+            if grants.find_by_id(&grant.id).is_some() {
+                return Err(format!("Role grant {} already exists", grant.id).into());
+            }
+            let id = grant.id;
+            grants.save(grant)?;
+            output.id(id);
+        }
+        RoleCommand::Revoke { grant } => {
+            let mut revoked = grants.find_by_id(&grant).ok_or("Role grant not found")?;
+            AccessPolicy::check_revoke(&grants, &context.actor()?, &revoked)?;
+            revoked.revoke()?;
+            grants.save(revoked)?;
+            output.done(&format!("Revoked {}", grant));
+        }
+        RoleCommand::List => {
+            let mut all: Vec<RoleGrant> = grants
+                .list_all()
+                .into_iter()
+                .filter(|g| !g.revoked)
+                .collect();
+            all.sort_by(|a, b| a.actor.as_str().cmp(b.actor.as_str()));
+            output.table(
+                &all,
+                &["ID", "Actor", "Role", "Section"],
+                all.iter()
+                    .map(|g| {
+                        vec![
+                            g.id.to_string(),
+                            g.actor.to_string(),
+                            format!("{:?}", g.role),
+                            g.scope.map_or_else(
+                                || "(all)".to_string(),
+                                |id| section_label(service, Some(id)),
+                            ),
+                        ]
+                    })
+                    .collect(),
+            );
+        }
+    }
+    Ok(())
+}
+
+fn show_preview(
+    service: &Service,
+    output: &Output,
+    preview: &Preview,
+) -> Result<(), Box<dyn Error>> {
+    if output.json {
+        output.table(preview, &[], Vec::new());
+        return Ok(());
+    }
+
+    if preview.replayed {
+        println!("Dry run: already applied by an earlier request; nothing would be written");
+        return Ok(());
+    }
+    println!("Dry run: nothing was written");
+    for section in &preview.sections {
+        let verb = match service.find_section(&section.id) {
+            Some(_) => "update",
+            None => "create",
+        };
+        println!("Would {} section {} ({})", verb, section.name, section.id);
+    }
+    for term in &preview.terms {
+        println!(
+            "Would save term {} ({} to {}, {:?})",
+            term.id, term.start_date, term.end_date, term.status
+        );
+    }
+    if !preview.sales.is_empty() {
+        output.table(
+            &preview.sales,
+            &["ID", "Date", "Section", "Type", "Amount"],
+            preview
+                .sales
+                .iter()
+                .map(|s| {
+                    vec![
+                        s.id.to_string(),
+                        s.date.to_string(),
+                        section_label(service, Some(s.section_id)),
+                        format!("{:?}", s.sales_type),
+                        s.amount.to_string(),
+                    ]
+                })
+                .collect(),
+        );
+        println!();
+        output.table(
+            &preview.balances,
+            &["Section", "Before", "After", "Change"],
+            preview
+                .balances
+                .iter()
+                .map(|b| {
+                    vec![
+                        section_label(service, Some(b.section_id)),
+                        b.before.to_string(),
+                        b.after.to_string(),
+                        b.change.to_string(),
+                    ]
+                })
+                .collect(),
+        );
+    }
+    Ok(())
+}
+
+/// The checks that blocked a close, one row per failure.
+fn close_failures_table(failures: &[CloseFailure]) -> Table {
+    let mut table =
+        Table::new(&["Check", "Failure", "IDs"]).with_title("Close blocked".to_string());
+    for failure in failures {
+        let ids: Vec<String> = failure.entity_ids.iter().map(|id| id.to_string()).collect();
+        table.push(vec![
+            format!("{:?}", failure.check),
+            failure.message.clone(),
+            ids.join(" "),
+        ]);
+    }
+    table
+}
+
+pub fn term_or_open(service: &Service, term: Option<Uuid>) -> Result<Uuid, String> {
+    match term {
+        Some(id) => Ok(id),
+        None => service
+            .find_open_term()
+            .map(|t| t.id)
+            .ok_or_else(|| "No open term found; pass --term".to_string()),
+    }
+}
+
+pub fn section_label(service: &Service, id: Option<Uuid>) -> String {
+    match id {
+        Some(id) => service
+            .find_section(&id)
+            .map(|s| s.name)
+            .unwrap_or_else(|| id.to_string()),
+        None => String::new(),
+    }
+}
+
+fn idempotency_key(key: Option<String>) -> Result<Option<IdempotencyKey>, String> {
+    key.map(|k| IdempotencyKey::new(k).map_err(|e| e.to_string()))
+        .transpose()
+}
+
+pub struct Output {
+    pub json: bool,
+    pub format: RenderFormat,
+}
+
+impl Output {
+    pub fn id(&self, id: Uuid) {
+        if self.json {
+            println!("{}", serde_json::json!({ "id": id }));
+        } else {
+            println!("{}", id);
+        }
+    }
+
+    pub fn done(&self, message: &str) {
+        if self.json {
+            println!("{}", serde_json::json!({ "status": "ok" }));
+        } else {
+            println!("{}", message);
+        }
+    }
+
+    /// Prints `value` as JSON, or `rows` as a table in the chosen format.
+    pub fn table<V: Serialize + ?Sized>(
+        &self,
+        value: &V,
+        headers: &[&str],
+        rows: Vec<Vec<String>>,
+    ) {
+        let mut table = Table::new(headers);
+        for row in rows {
+            table.push(row);
+        }
+        self.print(value, &table);
+    }
+
+    /// Prints a report as JSON, or as its titled table in the chosen format.
+    pub fn report<R: ToTable + Serialize>(&self, report: &R) {
+        self.print(report, &report.to_table());
+    }
+
+    /// Prints `value` as JSON, or `table` in the chosen format.
+    pub fn print<V: Serialize + ?Sized>(&self, value: &V, table: &Table) {
+        if self.json {
+            match serde_json::to_string_pretty(value) {
+                Ok(text) => println!("{}", text),
+                Err(e) => eprintln!("error: {}", e),
+            }
+            return;
+        }
+        print!("{}", self.format.renderer().render(table));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_sales_register() {
+        let cli = Cli::try_parse_from([
+            "accounting_spec",
+            "--json",
+            "sales",
+            "register",
+            "--section",
+            "Tokyo",
+            "--amount",
+            "100.50",
+            "--date",
+            "2025-06-01",
+        ])
+        .unwrap();
+        assert!(cli.json);
+        match cli.command {
+            Command::Sales(SalesCommand::Register { amount, date, .. }) => {
+                assert_eq!(amount, Decimal::from_str("100.50").unwrap());
+                assert_eq!(date.to_string(), "2025-06-01 00:00:00");
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+}
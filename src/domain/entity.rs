@@ -1,4 +1,4 @@
-use super::value_object::Money;
+use super::value_object::{ActorId, AuditInfo, IdempotencyKey, Money, SectionCode};
 use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -13,13 +13,76 @@ pub enum SectionType {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Section {
     pub id: Uuid,
+    /// Unique across sections when set
+    #[serde(default)]
+    pub code: Option<SectionCode>,
     pub name: String,
     pub section_type: SectionType,
     pub parent_id: Option<Uuid>,
+    pub idempotency_key: Option<IdempotencyKey>,
+    /// Audit info of the latest change; the full history is in the audit log
+    pub audit: Option<AuditInfo>,
+    /// Earlier names and parents, oldest first
+    #[serde(default)]
+    pub history: Vec<SectionVersion>,
+    /// First day on which the section no longer accepts postings
+    #[serde(default)]
+    pub deactivated_on: Option<NaiveDate>,
+    /// The section that took over this one's balances when it was merged
+    #[serde(default)]
+    pub merged_into: Option<Uuid>,
+    /// Changes made under an idempotency key, oldest first
+    #[serde(default)]
+    pub keyed_changes: Vec<KeyedChange>,
+}
+
+/// A change made under an idempotency key, kept on the record it changed so
+/// a retry is answered without applying the change again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyedChange {
+    pub key: IdempotencyKey,
+    pub change: Change,
+}
+
+/// A requested change to an existing section or term.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Change {
+    Rename {
+        name: String,
+        effective: NaiveDate,
+    },
+    Move {
+        parent_id: Option<Uuid>,
+        effective: NaiveDate,
+    },
+    Deactivate {
+        effective: NaiveDate,
+    },
+    Merge {
+        target_id: Uuid,
+        effective: NaiveDate,
+    },
+    Submit {
+        section_id: Uuid,
+    },
+}
+
+/// The change recorded under `key`, if any.
+fn change_for<'a>(changes: &'a [KeyedChange], key: &IdempotencyKey) -> Option<&'a Change> {
+    changes.iter().find(|c| c.key == *key).map(|c| &c.change)
+}
+
+/// The name and parent a section had before `until` (exclusive).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SectionVersion {
+    pub until: NaiveDate,
+    pub name: String,
+    pub parent_id: Option<Uuid>,
 }
 
 impl Section {
     pub fn new(
+        id: Uuid,
         name: String,
         section_type: SectionType,
         parent_id: Option<Uuid>,
@@ -28,12 +91,117 @@ impl Section {
             return Err("Section name cannot be empty");
         }
         Ok(Self {
-            id: Uuid::new_v4(),
+            id,
+            code: None,
             name,
             section_type,
             parent_id,
+            idempotency_key: None,
+            audit: None,
+            history: Vec::new(),
+            deactivated_on: None,
+            merged_into: None,
+            keyed_changes: Vec::new(),
         })
     }
+
+    pub fn with_code(mut self, code: SectionCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Whether `key` created the section or made one of its changes.
+    pub fn holds_key(&self, key: &IdempotencyKey) -> bool {
+        self.idempotency_key.as_ref() == Some(key) || self.change_for(key).is_some()
+    }
+
+    pub fn change_for(&self, key: &IdempotencyKey) -> Option<&Change> {
+        change_for(&self.keyed_changes, key)
+    }
+
+    /// Renames the section from `effective` on; earlier dates keep the old name.
+    pub fn rename(&mut self, name: String, effective: NaiveDate) -> Result<(), &'static str> {
+        if name.trim().is_empty() {
+            return Err("Section name cannot be empty");
+        }
+        self.record_version(effective)?;
+        self.name = name;
+        Ok(())
+    }
+
+    /// Moves the section under `parent_id` (or to the top level) from
+    /// `effective` on; earlier dates keep the old parent.
+    pub fn move_to(
+        &mut self,
+        parent_id: Option<Uuid>,
+        effective: NaiveDate,
+    ) -> Result<(), &'static str> {
+        if parent_id == Some(self.id) {
+            return Err("A section cannot be its own parent");
+        }
+        self.record_version(effective)?;
+        self.parent_id = parent_id;
+        Ok(())
+    }
+
+    pub fn deactivate(&mut self, effective: NaiveDate) -> Result<(), &'static str> {
+        if self.deactivated_on.is_some() {
+            return Err("Section is already deactivated");
+        }
+        self.check_effective(effective)?;
+        self.deactivated_on = Some(effective);
+        Ok(())
+    }
+
+    /// Deactivates the section from `effective` on, recording `target` as
+    /// the section that took over its balances.
+    pub fn merge_into(&mut self, target: Uuid, effective: NaiveDate) -> Result<(), &'static str> {
+        if target == self.id {
+            return Err("Cannot merge a section into itself");
+        }
+        self.deactivate(effective)?;
+        self.merged_into = Some(target);
+        Ok(())
+    }
+
+    pub fn is_active_on(&self, date: NaiveDate) -> bool {
+        self.deactivated_on.is_none_or(|d| date < d)
+    }
+
+    pub fn name_on(&self, date: NaiveDate) -> &str {
+        self.version_on(date).map_or(&self.name, |v| &v.name)
+    }
+
+    pub fn parent_on(&self, date: NaiveDate) -> Option<Uuid> {
+        self.version_on(date)
+            .map_or(self.parent_id, |v| v.parent_id)
+    }
+
+    fn version_on(&self, date: NaiveDate) -> Option<&SectionVersion> {
+        self.history.iter().find(|v| date < v.until)
+    }
+
+    /// Changes are recorded in date order, so a change cannot predate the
+    /// latest one already recorded.
+    fn check_effective(&self, effective: NaiveDate) -> Result<(), &'static str> {
+        if self.deactivated_on.is_some_and(|d| effective >= d) {
+            return Err("Section is deactivated on that date");
+        }
+        if self.history.last().is_some_and(|v| effective < v.until) {
+            return Err("Section changes must be dated on or after the latest change");
+        }
+        Ok(())
+    }
+
+    fn record_version(&mut self, effective: NaiveDate) -> Result<(), &'static str> {
+        self.check_effective(effective)?;
+        self.history.push(SectionVersion {
+            until: effective,
+            name: self.name.clone(),
+            parent_id: self.parent_id,
+        });
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -48,24 +216,61 @@ pub struct Term {
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
     pub status: TermStatus,
+    /// Sections that have signed off their figures for the term
+    #[serde(default)]
+    pub submitted_sections: Vec<Uuid>,
+    pub idempotency_key: Option<IdempotencyKey>,
+    /// Changes made under an idempotency key, oldest first
+    #[serde(default)]
+    pub keyed_changes: Vec<KeyedChange>,
+    /// Audit info of the latest change; the full history is in the audit log
+    pub audit: Option<AuditInfo>,
 }
 
 impl Term {
-    pub fn new(start_date: NaiveDate, end_date: NaiveDate) -> Result<Self, &'static str> {
+    pub fn new(id: Uuid, start_date: NaiveDate, end_date: NaiveDate) -> Result<Self, &'static str> {
         if start_date > end_date {
             return Err("Term start date must be before or equal to end date");
         }
         Ok(Self {
-            id: Uuid::new_v4(),
+            id,
             start_date,
             end_date,
             status: TermStatus::Open,
+            submitted_sections: Vec::new(),
+            idempotency_key: None,
+            keyed_changes: Vec::new(),
+            audit: None,
         })
     }
 
+    /// Whether `key` created the term or made one of its changes.
+    pub fn holds_key(&self, key: &IdempotencyKey) -> bool {
+        self.idempotency_key.as_ref() == Some(key) || self.change_for(key).is_some()
+    }
+
+    pub fn change_for(&self, key: &IdempotencyKey) -> Option<&Change> {
+        change_for(&self.keyed_changes, key)
+    }
+
     pub fn close(&mut self) {
         self.status = TermStatus::Closed;
     }
+
+    pub fn is_submitted(&self, section_id: &Uuid) -> bool {
+        self.submitted_sections.contains(section_id)
+    }
+
+    /// Records that a section has signed off its figures for the term.
+    pub fn submit_section(&mut self, section_id: Uuid) -> Result<(), &'static str> {
+        if self.status == TermStatus::Closed {
+            return Err("Term is closed");
+        }
+        if !self.is_submitted(&section_id) {
+            self.submitted_sections.push(section_id);
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -84,10 +289,16 @@ pub struct Sales {
     pub term_id: Uuid,
     pub sales_type: SalesType,
     pub related_sales_id: Option<Uuid>, // For adjustments/allocations
+    /// For the two legs of a merge: the ID of the leg in the merged section
+    #[serde(default)]
+    pub merge_anchor_id: Option<Uuid>,
+    pub idempotency_key: Option<IdempotencyKey>,
+    pub audit: Option<AuditInfo>,
 }
 
 impl Sales {
     pub fn new(
+        id: Uuid,
         amount: Money,
         date: NaiveDateTime,
         section_id: Uuid,
@@ -95,13 +306,213 @@ impl Sales {
         sales_type: SalesType,
     ) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id,
             amount,
             date,
             section_id,
             term_id,
             sales_type,
             related_sales_id: None,
+            merge_anchor_id: None,
+            idempotency_key: None,
+            audit: None,
+        }
+    }
+}
+
+/// The ledger change a pending correction applies once approved.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CorrectionRequest {
+    Correct {
+        term_id: Uuid,
+        section_id: Uuid,
+        original_amount: Money,
+        correct_amount: Money,
+        date: NaiveDateTime,
+    },
+    Rebalance {
+        term_id: Uuid,
+        source_section_id: Uuid,
+        target_section_id: Uuid,
+        amount: Money,
+        date: NaiveDateTime,
+    },
+}
+
+impl CorrectionRequest {
+    pub fn term_id(&self) -> Uuid {
+        match self {
+            CorrectionRequest::Correct { term_id, .. }
+            | CorrectionRequest::Rebalance { term_id, .. } => *term_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CorrectionStatus {
+    Draft,
+    Submitted,
+    Approved,
+    Rejected,
+}
+
+/// A correction awaiting maker-checker approval:
+/// Draft -> Submitted (by the maker) -> Approved | Rejected (by a different checker).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingCorrection {
+    pub id: Uuid,
+    pub request: CorrectionRequest,
+    pub status: CorrectionStatus,
+    pub maker: ActorId,
+    /// Why the maker asks for the correction; carried onto the entries
+    pub reason: String,
+    pub checker: Option<ActorId>,
+    pub rejection_reason: Option<String>,
+}
+
+impl PendingCorrection {
+    pub fn new(
+        id: Uuid,
+        request: CorrectionRequest,
+        maker: ActorId,
+        reason: String,
+    ) -> Result<Self, &'static str> {
+        if reason.trim().is_empty() {
+            return Err("A reason is required for corrections");
+        }
+        Ok(Self {
+            id,
+            request,
+            status: CorrectionStatus::Draft,
+            maker,
+            reason,
+            checker: None,
+            rejection_reason: None,
+        })
+    }
+
+    pub fn submit(&mut self, actor: &ActorId) -> Result<(), &'static str> {
+        if self.status != CorrectionStatus::Draft {
+            return Err("Only draft corrections can be submitted");
+        }
+        if *actor != self.maker {
+            return Err("Only the maker can submit a correction");
+        }
+        self.status = CorrectionStatus::Submitted;
+        Ok(())
+    }
+
+    pub fn approve(&mut self, checker: ActorId) -> Result<(), &'static str> {
+        self.check_decision(&checker)?;
+        self.status = CorrectionStatus::Approved;
+        self.checker = Some(checker);
+        Ok(())
+    }
+
+    pub fn reject(&mut self, checker: ActorId, reason: String) -> Result<(), &'static str> {
+        self.check_decision(&checker)?;
+        if reason.trim().is_empty() {
+            return Err("Rejection reason cannot be empty");
+        }
+        self.status = CorrectionStatus::Rejected;
+        self.checker = Some(checker);
+        self.rejection_reason = Some(reason);
+        Ok(())
+    }
+
+    fn check_decision(&self, checker: &ActorId) -> Result<(), &'static str> {
+        if self.status != CorrectionStatus::Submitted {
+            return Err("Only submitted corrections can be approved or rejected");
+        }
+        if *checker == self.maker {
+            return Err("The checker must be a different person from the maker");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditedEntity {
+    Section,
+    Term,
+    Sales,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditAction {
+    Created,
+    Closed,
+    Renamed,
+    Moved,
+    Deactivated,
+    Merged,
+    Submitted,
+}
+
+/// One entry of the append-only audit log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub id: Uuid,
+    pub entity: AuditedEntity,
+    pub entity_id: Uuid,
+    pub action: AuditAction,
+    pub audit: AuditInfo,
+}
+
+impl AuditRecord {
+    pub fn new(
+        id: Uuid,
+        entity: AuditedEntity,
+        entity_id: Uuid,
+        action: AuditAction,
+        audit: AuditInfo,
+    ) -> Self {
+        Self {
+            id,
+            entity,
+            entity_id,
+            action,
+            audit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Role {
+    Clerk,
+    Accountant,
+    Controller,
+    Auditor,
+}
+
+/// Grants `role` to `actor` within the subtree rooted at `scope`, or across
+/// every section when `scope` is `None`. Revoked grants are kept so the
+/// append-only store can record the revocation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoleGrant {
+    pub id: Uuid,
+    pub actor: ActorId,
+    pub role: Role,
+    pub scope: Option<Uuid>,
+    pub revoked: bool,
+}
+
+impl RoleGrant {
+    pub fn new(id: Uuid, actor: ActorId, role: Role, scope: Option<Uuid>) -> Self {
+        Self {
+            id,
+            actor,
+            role,
+            scope,
+            revoked: false,
+        }
+    }
+
+    pub fn revoke(&mut self) -> Result<(), &'static str> {
+        if self.revoked {
+            return Err("Role grant is already revoked");
         }
+        self.revoked = true;
+        Ok(())
     }
 }
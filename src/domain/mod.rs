@@ -1,3 +1,4 @@
 pub mod entity;
+pub mod provider;
 pub mod repository;
 pub mod value_object;
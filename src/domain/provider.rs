@@ -0,0 +1,165 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use uuid::{Builder, Uuid, Variant, Version};
+
+/// Where new entities get their IDs.
+pub trait IdGenerator: Send + Sync {
+    fn next_id(&self) -> Uuid;
+}
+
+impl<G: IdGenerator + ?Sized> IdGenerator for Arc<G> {
+    fn next_id(&self) -> Uuid {
+        (**self).next_id()
+    }
+}
+
+/// Random (version 4) IDs; what the service draws by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomIds;
+
+impl IdGenerator for RandomIds {
+    fn next_id(&self) -> Uuid {
+        Uuid::new_v4()
+    }
+}
+
+/// IDs counting up from 1 (`00000000-0000-0000-0000-000000000001`), easy to
+/// read in test output.
+#[derive(Debug, Default)]
+pub struct SequentialIds {
+    issued: AtomicU64,
+}
+
+impl SequentialIds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IdGenerator for SequentialIds {
+    fn next_id(&self) -> Uuid {
+        let n = self.issued.fetch_add(1, Ordering::Relaxed) + 1;
+        Uuid::from_u128(u128::from(n))
+    }
+}
+
+/// Version 4 IDs from a pseudo-random sequence fixed by `seed`, so a run can
+/// be repeated with the same IDs while they still look like random ones.
+#[derive(Debug)]
+pub struct SeededIds {
+    state: Mutex<u64>,
+}
+
+impl SeededIds {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: Mutex::new(seed),
+        }
+    }
+
+    /// splitmix64
+    fn next_u64(state: &mut u64) -> u64 {
+        *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl IdGenerator for SeededIds {
+    fn next_id(&self) -> Uuid {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let high = Self::next_u64(&mut state);
+        let low = Self::next_u64(&mut state);
+        let bytes = ((u128::from(high) << 64) | u128::from(low)).to_be_bytes();
+        Builder::from_bytes(bytes)
+            .set_variant(Variant::RFC4122)
+            .set_version(Version::Random)
+            .build()
+    }
+}
+
+/// Where the service gets the current time, in UTC.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> NaiveDateTime;
+
+    fn today(&self) -> NaiveDate {
+        self.now().date()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> NaiveDateTime {
+        (**self).now()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        Utc::now().naive_utc()
+    }
+}
+
+/// A clock that only moves when told to.
+#[derive(Debug)]
+pub struct FixedClock {
+    now: Mutex<NaiveDateTime>,
+}
+
+impl FixedClock {
+    pub fn new(now: NaiveDateTime) -> Self {
+        Self {
+            now: Mutex::new(now),
+        }
+    }
+
+    pub fn set(&self, now: NaiveDateTime) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let mut now = self.now.lock().unwrap_or_else(|e| e.into_inner());
+        *now += by;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> NaiveDateTime {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic_providers_repeat() {
+        let sequential = SequentialIds::new();
+        assert_eq!(sequential.next_id(), Uuid::from_u128(1));
+        assert_eq!(sequential.next_id(), Uuid::from_u128(2));
+
+        let (a, b) = (SeededIds::new(7), SeededIds::new(7));
+        let first: Vec<Uuid> = (0..3).map(|_| a.next_id()).collect();
+        let second: Vec<Uuid> = (0..3).map(|_| b.next_id()).collect();
+        assert_eq!(first, second);
+        assert_ne!(first[0], first[1]);
+        assert_eq!(first[0].get_version(), Some(Version::Random));
+        assert_ne!(SeededIds::new(8).next_id(), first[0]);
+
+        let start = NaiveDate::from_ymd_opt(2025, 4, 1)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        let clock = FixedClock::new(start);
+        assert_eq!(clock.now(), start);
+        clock.advance(Duration::minutes(5));
+        assert_eq!(clock.now(), start + Duration::minutes(5));
+        assert_eq!(clock.today(), start.date());
+    }
+}
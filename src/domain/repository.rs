@@ -1,22 +1,79 @@
-use super::entity::{Sales, Section, Term};
+use super::entity::{
+    AuditRecord, CorrectionStatus, PendingCorrection, RoleGrant, Sales, Section, Term,
+};
+use super::value_object::IdempotencyKey;
+use super::value_object::{ActorId, SectionCode};
+use chrono::NaiveDate;
 use uuid::Uuid;
 
 pub trait SectionRepository {
+    /// Fails if another section already has the same code.
     fn save(&mut self, section: Section) -> Result<(), String>;
     fn find_by_id(&self, id: &Uuid) -> Option<Section>;
+    fn find_by_code(&self, code: &SectionCode) -> Option<Section>;
+    fn find_by_name(&self, name: &str) -> Vec<Section>;
+    /// Returns the sections whose current parent is `parent_id`.
+    fn find_children(&self, parent_id: &Uuid) -> Vec<Section>;
+    /// IDs from the root section down to `id` under the current hierarchy,
+    /// or empty if `id` is unknown. Implementations keep these materialized
+    /// so that deep trees need no repeated parent lookups.
+    fn find_path(&self, id: &Uuid) -> Vec<Uuid>;
+    /// Returns the sections below `id` at any depth.
+    fn find_descendants(&self, id: &Uuid) -> Vec<Section>;
+    fn list_all(&self) -> Vec<Section>;
+    /// Returns the section created or changed by the request carrying `key`.
+    fn find_by_idempotency_key(&self, key: &IdempotencyKey) -> Option<Section>;
 }
 
 pub trait TermRepository {
     fn save(&mut self, term: Term) -> Result<(), String>;
     fn find_by_id(&self, id: &Uuid) -> Option<Term>;
     fn find_open_term(&self) -> Option<Term>;
+    fn list_all(&self) -> Vec<Term>;
+    /// Returns the term created or changed by the request carrying `key`.
+    fn find_by_idempotency_key(&self, key: &IdempotencyKey) -> Option<Term>;
 }
 
 pub trait SalesRepository {
     fn save(&mut self, sales: Sales) -> Result<(), String>;
+    /// Saves every one of `sales`, or none of them when it fails.
+    fn save_all(&mut self, sales: Vec<Sales>) -> Result<(), String>;
     fn find_by_id(&self, id: &Uuid) -> Option<Sales>;
     #[allow(dead_code)]
     fn find_by_term(&self, term_id: &Uuid) -> Vec<Sales>;
     #[allow(dead_code)]
     fn find_by_section_and_term(&self, section_id: &Uuid, term_id: &Uuid) -> Vec<Sales>;
+    /// Returns the entries dated within `[from, to]`, both ends inclusive.
+    fn find_by_date_range(&self, from: NaiveDate, to: NaiveDate) -> Vec<Sales>;
+    /// Yields the entries dated within `[from, to]` ordered by date, then by
+    /// ID, reading each one as it is requested.
+    fn iter_by_date(&self, from: NaiveDate, to: NaiveDate) -> Box<dyn Iterator<Item = Sales> + '_>;
+    /// Returns the entries whose `related_sales_id` points at `sales_id`.
+    fn find_related(&self, sales_id: &Uuid) -> Vec<Sales>;
+    /// Returns every entry written by the request carrying `key`.
+    fn find_by_idempotency_key(&self, key: &IdempotencyKey) -> Vec<Sales>;
+}
+
+pub trait PendingCorrectionRepository {
+    fn save(&mut self, correction: PendingCorrection) -> Result<(), String>;
+    fn find_by_id(&self, id: &Uuid) -> Option<PendingCorrection>;
+    fn find_by_status(&self, status: &CorrectionStatus) -> Vec<PendingCorrection>;
+    fn list_all(&self) -> Vec<PendingCorrection>;
+}
+
+/// Append-only store of audit records.
+pub trait AuditLogRepository {
+    fn append(&mut self, record: AuditRecord) -> Result<(), String>;
+    /// Returns every record in the order it was appended.
+    fn list_all(&self) -> Vec<AuditRecord>;
+    fn find_by_id(&self, id: &Uuid) -> Option<AuditRecord>;
+    fn find_by_entity(&self, entity_id: &Uuid) -> Vec<AuditRecord>;
+}
+
+pub trait RoleGrantRepository {
+    fn save(&mut self, grant: RoleGrant) -> Result<(), String>;
+    fn find_by_id(&self, id: &Uuid) -> Option<RoleGrant>;
+    /// Returns the grants held by `actor` that have not been revoked.
+    fn find_by_actor(&self, actor: &ActorId) -> Vec<RoleGrant>;
+    fn list_all(&self) -> Vec<RoleGrant>;
 }
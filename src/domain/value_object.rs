@@ -1,3 +1,5 @@
+use super::provider::Clock;
+use chrono::NaiveDateTime;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -18,6 +20,22 @@ impl Money {
     pub fn zero() -> Self {
         Self(Decimal::ZERO)
     }
+
+    /// Whether a single entry of this amount can be posted: at most
+    /// `MAX_ENTRY` in magnitude and `MAX_SCALE` decimal places. Every entry is
+    /// then a whole number of ten-thousandths below 10^19, so balances of any
+    /// realistic number of entries stay exact, well inside the 96-bit
+    /// mantissa of a `Decimal`.
+    pub fn is_postable(&self) -> bool {
+        self.0.abs() <= Decimal::from(Self::MAX_ENTRY)
+            && self.0.normalize().scale() <= Self::MAX_SCALE
+    }
+
+    /// Largest magnitude of a single entry, one quadrillion.
+    pub const MAX_ENTRY: i64 = 1_000_000_000_000_000;
+
+    /// Most decimal places a single entry may carry.
+    pub const MAX_SCALE: u32 = 4;
 }
 
 impl fmt::Display for Money {
@@ -26,6 +44,9 @@ impl fmt::Display for Money {
     }
 }
 
+/// Panics on overflow and rounds past 28 digits, like `Decimal`. Sums of
+/// ledger entries do neither, since the service only posts amounts for which
+/// `Money::is_postable` holds and the file store refuses to load others.
 impl std::ops::Add for Money {
     type Output = Self;
     fn add(self, rhs: Self) -> Self::Output {
@@ -65,6 +86,115 @@ impl AllocationRatio {
     }
 }
 
+/// Client-supplied key identifying a single logical request, so that a
+/// retried request can be recognised and answered with the original result.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct IdempotencyKey(String);
+
+impl IdempotencyKey {
+    pub fn new(key: String) -> Result<Self, &'static str> {
+        if key.trim().is_empty() {
+            return Err("Idempotency key cannot be empty");
+        }
+        Ok(Self(key))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for IdempotencyKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Stable code a source system uses for a section, such as "JP-TKY-01".
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SectionCode(String);
+
+impl SectionCode {
+    pub fn new(code: String) -> Result<Self, &'static str> {
+        if code.trim().is_empty() {
+            return Err("Section code cannot be empty");
+        }
+        if code.chars().any(char::is_whitespace) {
+            return Err("Section code cannot contain whitespace");
+        }
+        Ok(Self(code))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SectionCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Identity of the person (or system) performing an operation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ActorId(String);
+
+impl ActorId {
+    pub fn new(id: String) -> Result<Self, &'static str> {
+        if id.trim().is_empty() {
+            return Err("Actor ID cannot be empty");
+        }
+        Ok(Self(id))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ActorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Who made a change, when, from where and why. `correlation_id` ties
+/// together the changes made by one logical operation across systems.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditInfo {
+    pub actor: ActorId,
+    pub timestamp: NaiveDateTime,
+    pub source: String,
+    pub reason: Option<String>,
+    pub correlation_id: Option<String>,
+}
+
+impl AuditInfo {
+    /// Audit info stamped with `clock`'s time. AccountingService restamps it
+    /// from its own clock when it writes a change.
+    pub fn new(actor: ActorId, source: &str, clock: &dyn Clock) -> Self {
+        Self {
+            actor,
+            timestamp: clock.now(),
+            source: source.to_string(),
+            reason: None,
+            correlation_id: None,
+        }
+    }
+
+    /// Sets the reason; blank reasons are treated as absent.
+    pub fn with_reason(mut self, reason: Option<String>) -> Self {
+        self.reason = reason.filter(|r| !r.trim().is_empty());
+        self
+    }
+
+    pub fn with_correlation_id(mut self, correlation_id: Option<String>) -> Self {
+        self.correlation_id = correlation_id.filter(|c| !c.trim().is_empty());
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -80,6 +210,17 @@ mod tests {
         assert_eq!(-m1, Money::new(Decimal::from_str("-100.00").unwrap()));
     }
 
+    #[test]
+    fn test_is_postable() {
+        let money = |amount: &str| Money::new(Decimal::from_str(amount).unwrap());
+        assert!(money("100.00").is_postable());
+        assert!(money("-0.0050").is_postable());
+        assert!(money("-1000000000000000").is_postable());
+        assert!(!money("1000000000000000.0001").is_postable());
+        assert!(!money("0.00001").is_postable());
+        assert!(!Money::new(Decimal::MAX).is_postable());
+    }
+
     #[test]
     fn test_allocation_ratio() {
         assert!(AllocationRatio::new(Decimal::from_str("0.5").unwrap()).is_ok());
@@ -88,4 +229,17 @@ mod tests {
         assert!(AllocationRatio::new(Decimal::from_str("1.1").unwrap()).is_err());
         assert!(AllocationRatio::new(Decimal::from_str("-0.1").unwrap()).is_err());
     }
+
+    #[test]
+    fn test_idempotency_key() {
+        assert!(IdempotencyKey::new("pos-42".to_string()).is_ok());
+        assert!(IdempotencyKey::new("  ".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_section_code() {
+        assert!(SectionCode::new("JP-TKY-01".to_string()).is_ok());
+        assert!(SectionCode::new("".to_string()).is_err());
+        assert!(SectionCode::new("JP TKY".to_string()).is_err());
+    }
 }
@@ -0,0 +1,628 @@
+// Append-only JSON Lines storage: every save appends the full record, and on
+// open the file is replayed so the last line written for an ID wins.
+
+use super::in_memory::{
+    InMemoryAuditLogRepository, InMemoryPendingCorrectionRepository, InMemoryRoleGrantRepository,
+    InMemorySalesRepository, InMemorySectionRepository, InMemoryTermRepository,
+};
+use crate::domain::entity::{
+    AuditRecord, CorrectionStatus, PendingCorrection, RoleGrant, Sales, Section, Term,
+};
+use crate::domain::repository::{
+    AuditLogRepository, PendingCorrectionRepository, RoleGrantRepository, SalesRepository,
+    SectionRepository, TermRepository,
+};
+use crate::domain::value_object::{ActorId, IdempotencyKey, SectionCode};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+const SECTIONS_FILE: &str = "sections.jsonl";
+const TERMS_FILE: &str = "terms.jsonl";
+const SALES_FILE: &str = "sales.jsonl";
+const CORRECTIONS_FILE: &str = "corrections.jsonl";
+const AUDIT_FILE: &str = "audit.jsonl";
+const ROLES_FILE: &str = "roles.jsonl";
+
+fn load<R: DeserializeOwned>(path: &Path) -> Result<Vec<R>, String> {
+    let mut records = Vec::new();
+    scan(path, |_, record| {
+        records.push(record);
+        Ok(())
+    })?;
+    Ok(records)
+}
+
+/// Calls `f` with each record in `path` and the offset its line starts at.
+fn scan<R: DeserializeOwned>(
+    path: &Path,
+    mut f: impl FnMut(u64, R) -> Result<(), String>,
+) -> Result<(), String> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(format!("Failed to open {}: {}", path.display(), e)),
+    };
+
+    let mut reader = BufReader::new(file);
+    let mut line = String::new();
+    let mut offset = 0;
+    for number in 1.. {
+        line.clear();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        if !line.trim().is_empty() {
+            let record = serde_json::from_str(&line)
+                .map_err(|e| format!("{}:{}: {}", path.display(), number, e))?;
+            f(offset, record).map_err(|e| format!("{}:{}: {}", path.display(), number, e))?;
+        }
+        offset += read as u64;
+    }
+    Ok(())
+}
+
+/// Refuses entries the service would not have posted, so that a corrupt or
+/// hand-edited line cannot make later sums overflow.
+fn check_postable(sales: &Sales) -> Result<(), String> {
+    if !sales.amount.is_postable() {
+        return Err(format!(
+            "Amount {} of sale {} is out of range",
+            sales.amount, sales.id
+        ));
+    }
+    Ok(())
+}
+
+fn append<R: Serialize>(path: &Path, record: &R) -> Result<(), String> {
+    append_all(path, std::slice::from_ref(record))
+}
+
+/// Appends `records` in a single write, so a failure to serialize any of them
+/// writes none.
+fn append_all<R: Serialize>(path: &Path, records: &[R]) -> Result<(), String> {
+    let mut lines = String::new();
+    for record in records {
+        lines.push_str(&serde_json::to_string(record).map_err(|e| e.to_string())?);
+        lines.push('\n');
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(lines.as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn prepare(dir: &Path, file_name: &str) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create store {}: {}", dir.display(), e))?;
+    Ok(dir.join(file_name))
+}
+
+pub struct FileSectionRepository {
+    path: PathBuf,
+    inner: InMemorySectionRepository,
+}
+
+impl FileSectionRepository {
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        let path = prepare(dir, SECTIONS_FILE)?;
+        let mut inner = InMemorySectionRepository::new();
+        for section in load::<Section>(&path)? {
+            inner.save(section)?;
+        }
+        Ok(Self { path, inner })
+    }
+}
+
+impl SectionRepository for FileSectionRepository {
+    fn save(&mut self, section: Section) -> Result<(), String> {
+        self.inner.check_code(&section)?;
+        append(&self.path, &section)?;
+        self.inner.save(section)
+    }
+
+    fn find_by_id(&self, id: &Uuid) -> Option<Section> {
+        self.inner.find_by_id(id)
+    }
+
+    fn find_by_code(&self, code: &SectionCode) -> Option<Section> {
+        self.inner.find_by_code(code)
+    }
+
+    fn find_children(&self, parent_id: &Uuid) -> Vec<Section> {
+        self.inner.find_children(parent_id)
+    }
+
+    fn find_path(&self, id: &Uuid) -> Vec<Uuid> {
+        self.inner.find_path(id)
+    }
+
+    fn find_descendants(&self, id: &Uuid) -> Vec<Section> {
+        self.inner.find_descendants(id)
+    }
+
+    fn find_by_name(&self, name: &str) -> Vec<Section> {
+        self.inner.find_by_name(name)
+    }
+
+    fn list_all(&self) -> Vec<Section> {
+        self.inner.list_all()
+    }
+
+    fn find_by_idempotency_key(&self, key: &IdempotencyKey) -> Option<Section> {
+        self.inner.find_by_idempotency_key(key)
+    }
+}
+
+pub struct FileTermRepository {
+    path: PathBuf,
+    inner: InMemoryTermRepository,
+}
+
+impl FileTermRepository {
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        let path = prepare(dir, TERMS_FILE)?;
+        let mut inner = InMemoryTermRepository::new();
+        for term in load::<Term>(&path)? {
+            inner.save(term)?;
+        }
+        Ok(Self { path, inner })
+    }
+}
+
+impl TermRepository for FileTermRepository {
+    fn save(&mut self, term: Term) -> Result<(), String> {
+        append(&self.path, &term)?;
+        self.inner.save(term)
+    }
+
+    fn find_by_id(&self, id: &Uuid) -> Option<Term> {
+        self.inner.find_by_id(id)
+    }
+
+    fn find_open_term(&self) -> Option<Term> {
+        self.inner.find_open_term()
+    }
+
+    fn list_all(&self) -> Vec<Term> {
+        self.inner.list_all()
+    }
+
+    fn find_by_idempotency_key(&self, key: &IdempotencyKey) -> Option<Term> {
+        self.inner.find_by_idempotency_key(key)
+    }
+}
+
+pub struct FileSalesRepository {
+    path: PathBuf,
+    inner: InMemorySalesRepository,
+}
+
+impl FileSalesRepository {
+    /// Replays the whole of `sales.jsonl` into memory, so the ledger must fit
+    /// in memory and opening takes time in proportion to the file. Reads are
+    /// then served from that copy; exports can use [`FileSalesLedger`]
+    /// instead.
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        let path = prepare(dir, SALES_FILE)?;
+        let mut inner = InMemorySalesRepository::new();
+        scan(&path, |_, sales: Sales| {
+            check_postable(&sales)?;
+            inner.save(sales)
+        })?;
+        Ok(Self { path, inner })
+    }
+}
+
+impl SalesRepository for FileSalesRepository {
+    fn save(&mut self, sales: Sales) -> Result<(), String> {
+        append(&self.path, &sales)?;
+        self.inner.save(sales)
+    }
+
+    fn save_all(&mut self, sales: Vec<Sales>) -> Result<(), String> {
+        append_all(&self.path, &sales)?;
+        self.inner.save_all(sales)
+    }
+
+    fn find_by_id(&self, id: &Uuid) -> Option<Sales> {
+        self.inner.find_by_id(id)
+    }
+
+    fn find_by_term(&self, term_id: &Uuid) -> Vec<Sales> {
+        self.inner.find_by_term(term_id)
+    }
+
+    fn find_by_section_and_term(&self, section_id: &Uuid, term_id: &Uuid) -> Vec<Sales> {
+        self.inner.find_by_section_and_term(section_id, term_id)
+    }
+
+    fn find_by_date_range(&self, from: NaiveDate, to: NaiveDate) -> Vec<Sales> {
+        self.inner.find_by_date_range(from, to)
+    }
+
+    fn iter_by_date(&self, from: NaiveDate, to: NaiveDate) -> Box<dyn Iterator<Item = Sales> + '_> {
+        self.inner.iter_by_date(from, to)
+    }
+
+    fn find_related(&self, sales_id: &Uuid) -> Vec<Sales> {
+        self.inner.find_related(sales_id)
+    }
+
+    fn find_by_idempotency_key(&self, key: &IdempotencyKey) -> Vec<Sales> {
+        self.inner.find_by_idempotency_key(key)
+    }
+}
+
+/// Read-only view of `sales.jsonl` for exports. Opening it reads the file once
+/// to note where the last line of each entry starts; entries are then read
+/// back one at a time as they are requested, so the ledger is never held in
+/// memory. Writes are refused.
+pub struct FileSalesLedger {
+    path: PathBuf,
+    /// Offset of the last line written for each entry, keyed by `(date, id)`
+    by_date: BTreeMap<(NaiveDateTime, Uuid), u64>,
+    /// The first read that failed after opening, which ends the iteration
+    error: RefCell<Option<String>>,
+}
+
+impl FileSalesLedger {
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        let path = dir.join(SALES_FILE);
+        let mut by_date = BTreeMap::new();
+        let mut dates = HashMap::new();
+        scan(&path, |offset, sales: Sales| {
+            check_postable(&sales)?;
+            if let Some(previous) = dates.insert(sales.id, sales.date) {
+                by_date.remove(&(previous, sales.id));
+            }
+            by_date.insert((sales.date, sales.id), offset);
+            Ok(())
+        })?;
+        Ok(Self {
+            path,
+            by_date,
+            error: RefCell::new(None),
+        })
+    }
+
+    /// Why an iteration ended early, if a read failed since the last call.
+    pub fn take_error(&self) -> Option<String> {
+        self.error.borrow_mut().take()
+    }
+
+    fn read_at(&self, reader: &mut Option<BufReader<File>>, offset: u64) -> Result<Sales, String> {
+        let failed = |e: std::io::Error| format!("Failed to read {}: {}", self.path.display(), e);
+        let reader = match reader {
+            Some(reader) => reader,
+            None => reader.insert(BufReader::new(File::open(&self.path).map_err(failed)?)),
+        };
+        reader.seek(SeekFrom::Start(offset)).map_err(failed)?;
+        let mut line = String::new();
+        reader.read_line(&mut line).map_err(failed)?;
+        serde_json::from_str(&line).map_err(|e| format!("{}: {}", self.path.display(), e))
+    }
+
+    fn entries(&self) -> impl Iterator<Item = Sales> + '_ {
+        self.iter_by_date(NaiveDate::MIN, NaiveDate::MAX)
+    }
+
+    fn read_only(&self) -> String {
+        format!("{} is opened read-only", self.path.display())
+    }
+}
+
+impl SalesRepository for FileSalesLedger {
+    fn save(&mut self, _sales: Sales) -> Result<(), String> {
+        Err(self.read_only())
+    }
+
+    fn save_all(&mut self, _sales: Vec<Sales>) -> Result<(), String> {
+        Err(self.read_only())
+    }
+
+    fn find_by_id(&self, id: &Uuid) -> Option<Sales> {
+        self.entries().find(|s| s.id == *id)
+    }
+
+    fn find_by_term(&self, term_id: &Uuid) -> Vec<Sales> {
+        self.entries().filter(|s| s.term_id == *term_id).collect()
+    }
+
+    fn find_by_section_and_term(&self, section_id: &Uuid, term_id: &Uuid) -> Vec<Sales> {
+        self.entries()
+            .filter(|s| s.section_id == *section_id && s.term_id == *term_id)
+            .collect()
+    }
+
+    fn find_by_date_range(&self, from: NaiveDate, to: NaiveDate) -> Vec<Sales> {
+        self.iter_by_date(from, to).collect()
+    }
+
+    fn iter_by_date(&self, from: NaiveDate, to: NaiveDate) -> Box<dyn Iterator<Item = Sales> + '_> {
+        let start = (NaiveDateTime::new(from, NaiveTime::MIN), Uuid::nil());
+        let mut reader = None;
+        Box::new(
+            self.by_date
+                .range(start..)
+                .take_while(move |((date, _), _)| date.date() <= to)
+                .map_while(
+                    move |(_, &offset)| match self.read_at(&mut reader, offset) {
+                        Ok(sales) => Some(sales),
+                        Err(e) => {
+                            *self.error.borrow_mut() = Some(e);
+                            None
+                        }
+                    },
+                ),
+        )
+    }
+
+    fn find_related(&self, sales_id: &Uuid) -> Vec<Sales> {
+        self.entries()
+            .filter(|s| s.related_sales_id == Some(*sales_id))
+            .collect()
+    }
+
+    fn find_by_idempotency_key(&self, key: &IdempotencyKey) -> Vec<Sales> {
+        self.entries()
+            .filter(|s| s.idempotency_key.as_ref() == Some(key))
+            .collect()
+    }
+}
+
+pub struct FilePendingCorrectionRepository {
+    path: PathBuf,
+    inner: InMemoryPendingCorrectionRepository,
+}
+
+impl FilePendingCorrectionRepository {
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        let path = prepare(dir, CORRECTIONS_FILE)?;
+        let mut inner = InMemoryPendingCorrectionRepository::new();
+        for correction in load::<PendingCorrection>(&path)? {
+            inner.save(correction)?;
+        }
+        Ok(Self { path, inner })
+    }
+}
+
+impl PendingCorrectionRepository for FilePendingCorrectionRepository {
+    fn save(&mut self, correction: PendingCorrection) -> Result<(), String> {
+        append(&self.path, &correction)?;
+        self.inner.save(correction)
+    }
+
+    fn find_by_id(&self, id: &Uuid) -> Option<PendingCorrection> {
+        self.inner.find_by_id(id)
+    }
+
+    fn find_by_status(&self, status: &CorrectionStatus) -> Vec<PendingCorrection> {
+        self.inner.find_by_status(status)
+    }
+
+    fn list_all(&self) -> Vec<PendingCorrection> {
+        self.inner.list_all()
+    }
+}
+
+pub struct FileAuditLogRepository {
+    path: PathBuf,
+    inner: InMemoryAuditLogRepository,
+}
+
+impl FileAuditLogRepository {
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        let path = prepare(dir, AUDIT_FILE)?;
+        let mut inner = InMemoryAuditLogRepository::new();
+        for record in load::<AuditRecord>(&path)? {
+            inner.append(record)?;
+        }
+        Ok(Self { path, inner })
+    }
+}
+
+impl AuditLogRepository for FileAuditLogRepository {
+    fn append(&mut self, record: AuditRecord) -> Result<(), String> {
+        append(&self.path, &record)?;
+        self.inner.append(record)
+    }
+
+    fn list_all(&self) -> Vec<AuditRecord> {
+        self.inner.list_all()
+    }
+
+    fn find_by_id(&self, id: &Uuid) -> Option<AuditRecord> {
+        self.inner.find_by_id(id)
+    }
+
+    fn find_by_entity(&self, entity_id: &Uuid) -> Vec<AuditRecord> {
+        self.inner.find_by_entity(entity_id)
+    }
+}
+
+pub struct FileRoleGrantRepository {
+    path: PathBuf,
+    inner: InMemoryRoleGrantRepository,
+}
+
+impl FileRoleGrantRepository {
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        let path = prepare(dir, ROLES_FILE)?;
+        let mut inner = InMemoryRoleGrantRepository::new();
+        for grant in load::<RoleGrant>(&path)? {
+            inner.save(grant)?;
+        }
+        Ok(Self { path, inner })
+    }
+}
+
+impl RoleGrantRepository for FileRoleGrantRepository {
+    fn save(&mut self, grant: RoleGrant) -> Result<(), String> {
+        append(&self.path, &grant)?;
+        self.inner.save(grant)
+    }
+
+    fn find_by_id(&self, id: &Uuid) -> Option<RoleGrant> {
+        self.inner.find_by_id(id)
+    }
+
+    fn find_by_actor(&self, actor: &ActorId) -> Vec<RoleGrant> {
+        self.inner.find_by_actor(actor)
+    }
+
+    fn list_all(&self) -> Vec<RoleGrant> {
+        self.inner.list_all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entity::{SalesType, SectionType, TermStatus};
+    use crate::domain::value_object::Money;
+    use rust_decimal::Decimal;
+
+    fn temp_store(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("accounting-store-{}-{}", name, Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_records_survive_reopen() {
+        let dir = temp_store("reopen");
+
+        let section = Section::new(
+            Uuid::new_v4(),
+            "Tokyo".to_string(),
+            SectionType::Section,
+            None,
+        )
+        .unwrap();
+        let section_id = section.id;
+        let mut term = Term::new(
+            Uuid::new_v4(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = term.id;
+
+        {
+            let mut sections = FileSectionRepository::open(&dir).unwrap();
+            sections.save(section).unwrap();
+            let mut terms = FileTermRepository::open(&dir).unwrap();
+            terms.save(term.clone()).unwrap();
+            // A later save of the same term supersedes the earlier line
+            term.close();
+            terms.save(term).unwrap();
+        }
+
+        let sections = FileSectionRepository::open(&dir).unwrap();
+        assert_eq!(sections.find_by_id(&section_id).unwrap().name, "Tokyo");
+        let terms = FileTermRepository::open(&dir).unwrap();
+        assert_eq!(terms.list_all().len(), 1);
+        assert_eq!(
+            terms.find_by_id(&term_id).unwrap().status,
+            TermStatus::Closed
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_line_is_reported() {
+        let dir = temp_store("corrupt");
+        fs::write(dir.join(SALES_FILE), "{not json}\n").unwrap();
+
+        let result = FileSalesRepository::open(&dir);
+        assert!(result.is_err());
+        assert!(result.err().unwrap().contains("sales.jsonl:1"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_ledger_reads_entries_in_date_order_from_the_file() {
+        let dir = temp_store("ledger");
+        let (section_id, term_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let on = |day| {
+            NaiveDate::from_ymd_opt(2025, 6, day)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap()
+        };
+        let entry = |amount: i64, day| {
+            Sales::new(
+                Uuid::new_v4(),
+                Money::new(Decimal::from(amount)),
+                on(day),
+                section_id,
+                term_id,
+                SalesType::Normal,
+            )
+        };
+        let (late, early) = (entry(300, 20), entry(100, 10));
+        let mut moved = entry(200, 1);
+        {
+            let mut sales = FileSalesRepository::open(&dir).unwrap();
+            sales.save_all(vec![late.clone(), early.clone()]).unwrap();
+            sales.save(moved.clone()).unwrap();
+            // The last line written for an entry is the one read back
+            moved.date = on(15);
+            sales.save(moved.clone()).unwrap();
+        }
+
+        let mut ledger = FileSalesLedger::open(&dir).unwrap();
+        let ids: Vec<Uuid> = ledger
+            .iter_by_date(on(1).date(), on(30).date())
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(ids, vec![early.id, moved.id, late.id]);
+        assert_eq!(ledger.find_by_id(&moved.id).unwrap().date, on(15));
+        assert_eq!(ledger.find_by_term(&term_id).len(), 3);
+        assert!(ledger.take_error().is_none());
+        assert!(ledger.save(entry(1, 1)).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_unpostable_amount_is_reported() {
+        let dir = temp_store("unpostable");
+        let mut sales = Sales::new(
+            Uuid::new_v4(),
+            Money::new(Decimal::MAX),
+            NaiveDate::from_ymd_opt(2025, 6, 1)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            SalesType::Normal,
+        );
+        let line = serde_json::to_string(&sales).unwrap();
+        sales.amount = Money::new(Decimal::from(100));
+        let valid = serde_json::to_string(&sales).unwrap();
+        fs::write(dir.join(SALES_FILE), format!("{}\n{}\n", valid, line)).unwrap();
+
+        let result = FileSalesRepository::open(&dir);
+        assert!(result.err().unwrap().contains("sales.jsonl:2: Amount"));
+        assert!(FileSalesLedger::open(&dir).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
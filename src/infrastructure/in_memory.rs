@@ -1,28 +1,148 @@
-use crate::domain::entity::{Sales, Section, Term, TermStatus};
-use crate::domain::repository::{SalesRepository, SectionRepository, TermRepository};
-use std::collections::HashMap;
+use crate::domain::entity::{
+    AuditRecord, CorrectionStatus, PendingCorrection, RoleGrant, Sales, Section, Term, TermStatus,
+};
+use crate::domain::repository::{
+    AuditLogRepository, PendingCorrectionRepository, RoleGrantRepository, SalesRepository,
+    SectionRepository, TermRepository,
+};
+use crate::domain::value_object::{ActorId, IdempotencyKey, SectionCode};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use uuid::Uuid;
 
 #[derive(Default)]
 pub struct InMemorySectionRepository {
     storage: HashMap<Uuid, Section>,
+    /// Materialized path of every section, root first
+    paths: HashMap<Uuid, Vec<Uuid>>,
+    /// IDs of the sections directly below each parent
+    children: HashMap<Uuid, BTreeSet<Uuid>>,
 }
 
 impl InMemorySectionRepository {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Checks no other section holds `section`'s code.
+    pub fn check_code(&self, section: &Section) -> Result<(), String> {
+        match &section.code {
+            Some(code) if self.find_by_code(code).is_some_and(|s| s.id != section.id) => {
+                Err(format!("Section code {} is already in use", code))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Recomputes the cached paths of `id` and everything below it. Parents
+    /// are handled before their children, and a child saved before its
+    /// parent is picked up when the parent arrives.
+    fn refresh_paths(&mut self, id: Uuid) {
+        let mut pending = VecDeque::from([id]);
+        let mut seen = HashSet::new();
+        while let Some(id) = pending.pop_front() {
+            if !seen.insert(id) {
+                continue;
+            }
+            let mut path = self
+                .storage
+                .get(&id)
+                .and_then(|s| s.parent_id)
+                .and_then(|parent_id| self.paths.get(&parent_id))
+                .cloned()
+                .unwrap_or_default();
+            if path.contains(&id) {
+                path.clear();
+            }
+            path.push(id);
+            self.paths.insert(id, path);
+            pending.extend(self.children.get(&id).into_iter().flatten());
+        }
+    }
 }
 
 impl SectionRepository for InMemorySectionRepository {
     fn save(&mut self, section: Section) -> Result<(), String> {
-        self.storage.insert(section.id, section);
+        self.check_code(&section)?;
+        let id = section.id;
+        let old_parent = self.storage.get(&id).map(|old| old.parent_id);
+        let moved = old_parent != Some(section.parent_id);
+        if moved {
+            if let Some(parent_id) = old_parent.flatten() {
+                if let Some(siblings) = self.children.get_mut(&parent_id) {
+                    siblings.remove(&id);
+                }
+            }
+            if let Some(parent_id) = section.parent_id {
+                self.children.entry(parent_id).or_default().insert(id);
+            }
+        }
+        self.storage.insert(id, section);
+        if moved {
+            self.refresh_paths(id);
+        }
         Ok(())
     }
 
     fn find_by_id(&self, id: &Uuid) -> Option<Section> {
         self.storage.get(id).cloned()
     }
+
+    fn find_by_code(&self, code: &SectionCode) -> Option<Section> {
+        self.storage
+            .values()
+            .find(|s| s.code.as_ref() == Some(code))
+            .cloned()
+    }
+
+    fn find_children(&self, parent_id: &Uuid) -> Vec<Section> {
+        self.children
+            .get(parent_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.storage.get(id).cloned())
+            .collect()
+    }
+
+    fn find_path(&self, id: &Uuid) -> Vec<Uuid> {
+        self.paths.get(id).cloned().unwrap_or_default()
+    }
+
+    fn find_descendants(&self, id: &Uuid) -> Vec<Section> {
+        let mut descendants = Vec::new();
+        let mut seen = HashSet::from([*id]);
+        let mut pending: VecDeque<Uuid> = self
+            .children
+            .get(id)
+            .into_iter()
+            .flatten()
+            .copied()
+            .collect();
+        while let Some(child_id) = pending.pop_front() {
+            if !seen.insert(child_id) {
+                continue;
+            }
+            descendants.extend(self.storage.get(&child_id).cloned());
+            pending.extend(self.children.get(&child_id).into_iter().flatten());
+        }
+        descendants
+    }
+
+    fn find_by_name(&self, name: &str) -> Vec<Section> {
+        self.storage
+            .values()
+            .filter(|s| s.name == name)
+            .cloned()
+            .collect()
+    }
+
+    fn list_all(&self) -> Vec<Section> {
+        self.storage.values().cloned().collect()
+    }
+
+    fn find_by_idempotency_key(&self, key: &IdempotencyKey) -> Option<Section> {
+        self.storage.values().find(|s| s.holds_key(key)).cloned()
+    }
 }
 
 #[derive(Default)]
@@ -53,11 +173,21 @@ impl TermRepository for InMemoryTermRepository {
             .find(|t| t.status == TermStatus::Open)
             .cloned()
     }
+
+    fn list_all(&self) -> Vec<Term> {
+        self.storage.values().cloned().collect()
+    }
+
+    fn find_by_idempotency_key(&self, key: &IdempotencyKey) -> Option<Term> {
+        self.storage.values().find(|t| t.holds_key(key)).cloned()
+    }
 }
 
 #[derive(Default)]
 pub struct InMemorySalesRepository {
     storage: HashMap<Uuid, Sales>,
+    /// Every entry keyed by `(date, id)`, for date-ordered reads
+    by_date: BTreeSet<(NaiveDateTime, Uuid)>,
 }
 
 impl InMemorySalesRepository {
@@ -68,7 +198,19 @@ impl InMemorySalesRepository {
 
 impl SalesRepository for InMemorySalesRepository {
     fn save(&mut self, sales: Sales) -> Result<(), String> {
-        self.storage.insert(sales.id, sales);
+        self.by_date.insert((sales.date, sales.id));
+        if let Some(previous) = self.storage.insert(sales.id, sales) {
+            if self.storage[&previous.id].date != previous.date {
+                self.by_date.remove(&(previous.date, previous.id));
+            }
+        }
+        Ok(())
+    }
+
+    fn save_all(&mut self, sales: Vec<Sales>) -> Result<(), String> {
+        for entry in sales {
+            self.save(entry)?;
+        }
         Ok(())
     }
 
@@ -93,4 +235,179 @@ impl SalesRepository for InMemorySalesRepository {
             .cloned()
             .collect()
     }
+
+    fn find_by_date_range(&self, from: NaiveDate, to: NaiveDate) -> Vec<Sales> {
+        self.iter_by_date(from, to).collect()
+    }
+
+    fn iter_by_date(&self, from: NaiveDate, to: NaiveDate) -> Box<dyn Iterator<Item = Sales> + '_> {
+        let start = (NaiveDateTime::new(from, NaiveTime::MIN), Uuid::nil());
+        Box::new(
+            self.by_date
+                .range(start..)
+                .take_while(move |(date, _)| date.date() <= to)
+                .filter_map(|(_, id)| self.storage.get(id).cloned()),
+        )
+    }
+
+    fn find_related(&self, sales_id: &Uuid) -> Vec<Sales> {
+        self.storage
+            .values()
+            .filter(|s| s.related_sales_id == Some(*sales_id))
+            .cloned()
+            .collect()
+    }
+
+    fn find_by_idempotency_key(&self, key: &IdempotencyKey) -> Vec<Sales> {
+        self.storage
+            .values()
+            .filter(|s| s.idempotency_key.as_ref() == Some(key))
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub struct InMemoryPendingCorrectionRepository {
+    storage: HashMap<Uuid, PendingCorrection>,
+}
+
+impl InMemoryPendingCorrectionRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PendingCorrectionRepository for InMemoryPendingCorrectionRepository {
+    fn save(&mut self, correction: PendingCorrection) -> Result<(), String> {
+        self.storage.insert(correction.id, correction);
+        Ok(())
+    }
+
+    fn find_by_id(&self, id: &Uuid) -> Option<PendingCorrection> {
+        self.storage.get(id).cloned()
+    }
+
+    fn find_by_status(&self, status: &CorrectionStatus) -> Vec<PendingCorrection> {
+        self.storage
+            .values()
+            .filter(|c| c.status == *status)
+            .cloned()
+            .collect()
+    }
+
+    fn list_all(&self) -> Vec<PendingCorrection> {
+        self.storage.values().cloned().collect()
+    }
+}
+
+#[derive(Default)]
+pub struct InMemoryAuditLogRepository {
+    records: Vec<AuditRecord>,
+    /// Position of each record in `records`
+    by_id: HashMap<Uuid, usize>,
+}
+
+impl InMemoryAuditLogRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AuditLogRepository for InMemoryAuditLogRepository {
+    fn append(&mut self, record: AuditRecord) -> Result<(), String> {
+        self.by_id.insert(record.id, self.records.len());
+        self.records.push(record);
+        Ok(())
+    }
+
+    fn list_all(&self) -> Vec<AuditRecord> {
+        self.records.clone()
+    }
+
+    fn find_by_id(&self, id: &Uuid) -> Option<AuditRecord> {
+        self.by_id.get(id).map(|&index| self.records[index].clone())
+    }
+
+    fn find_by_entity(&self, entity_id: &Uuid) -> Vec<AuditRecord> {
+        self.records
+            .iter()
+            .filter(|r| r.entity_id == *entity_id)
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub struct InMemoryRoleGrantRepository {
+    storage: HashMap<Uuid, RoleGrant>,
+}
+
+impl InMemoryRoleGrantRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RoleGrantRepository for InMemoryRoleGrantRepository {
+    fn save(&mut self, grant: RoleGrant) -> Result<(), String> {
+        self.storage.insert(grant.id, grant);
+        Ok(())
+    }
+
+    fn find_by_id(&self, id: &Uuid) -> Option<RoleGrant> {
+        self.storage.get(id).cloned()
+    }
+
+    fn find_by_actor(&self, actor: &ActorId) -> Vec<RoleGrant> {
+        self.storage
+            .values()
+            .filter(|g| !g.revoked && g.actor == *actor)
+            .cloned()
+            .collect()
+    }
+
+    fn list_all(&self) -> Vec<RoleGrant> {
+        self.storage.values().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entity::SectionType;
+
+    #[test]
+    fn test_child_saved_before_its_parent_gets_its_path() {
+        let mut repo = InMemorySectionRepository::new();
+        let parent = Section::new(
+            Uuid::new_v4(),
+            "East".to_string(),
+            SectionType::Division,
+            None,
+        )
+        .unwrap();
+        let child = Section::new(
+            Uuid::new_v4(),
+            "Tokyo".to_string(),
+            SectionType::Section,
+            Some(parent.id),
+        )
+        .unwrap();
+        let (parent_id, child_id) = (parent.id, child.id);
+
+        repo.save(child).unwrap();
+        assert_eq!(repo.find_path(&child_id), vec![child_id]);
+        repo.save(parent).unwrap();
+        assert_eq!(repo.find_path(&child_id), vec![parent_id, child_id]);
+        assert_eq!(repo.find_children(&parent_id)[0].id, child_id);
+
+        // Moving the child away leaves the parent without children
+        let mut moved = repo.find_by_id(&child_id).unwrap();
+        moved.parent_id = None;
+        repo.save(moved).unwrap();
+        assert!(repo.find_children(&parent_id).is_empty());
+        assert!(repo.find_descendants(&parent_id).is_empty());
+        assert_eq!(repo.find_path(&child_id), vec![child_id]);
+    }
 }
@@ -1 +1,2 @@
+pub mod file_store;
 pub mod in_memory;
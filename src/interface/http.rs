@@ -0,0 +1,960 @@
+use crate::application::approval::CorrectionWorkflow;
+use crate::application::audit::AuditQuery;
+use crate::application::checklist::CloseError;
+use crate::application::export::ExportScope;
+use crate::application::reference;
+use crate::application::report::{Interval, Structure};
+use crate::application::service::{AccountingService, ServiceError};
+use crate::domain::entity::{CorrectionRequest, CorrectionStatus, Section, SectionType, Term};
+use crate::domain::provider::Clock;
+use crate::domain::repository::{
+    AuditLogRepository, PendingCorrectionRepository, SalesRepository, SectionRepository,
+    TermRepository,
+};
+use crate::domain::value_object::{ActorId, AuditInfo, IdempotencyKey, Money, SectionCode};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tiny_http::{Header, Method, Request, Response, Server};
+use uuid::Uuid;
+
+/// Header carrying the client-supplied idempotency key on POST requests.
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+/// Header identifying who makes a change; required on POST requests. It is
+/// taken on trust, not authenticated, so the API is only fit for a local or
+/// otherwise trusted network.
+pub const ACTOR_HEADER: &str = "X-Actor";
+/// Optional header tying a change to a wider operation.
+pub const CORRELATION_HEADER: &str = "X-Correlation-Id";
+
+/// Source recorded in the audit info of changes made over HTTP.
+const AUDIT_SOURCE: &str = "http";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApiError {
+    #[serde(skip)]
+    pub status: u16,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::new(404, "not_found", message)
+    }
+
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(400, "bad_request", message)
+    }
+
+    /// Maps a service error to a status code: a missing subject is 404, a
+    /// policy denial 403, a reused idempotency key 409 and anything else the
+    /// service refused 422.
+    fn from_service(error: ServiceError) -> Self {
+        match error {
+            ServiceError::NotFound(message) => Self::not_found(message),
+            ServiceError::Forbidden(message) => Self::new(403, "forbidden", message),
+            ServiceError::IdempotencyConflict(message) => {
+                Self::new(409, "idempotency_conflict", message)
+            }
+            ServiceError::Rejected(message) => Self::new(422, "rejected", message),
+        }
+    }
+
+    fn from_close(error: CloseError) -> Self {
+        match error {
+            CloseError::Blocked(_) => Self::new(409, "close_blocked", error.to_string()),
+            CloseError::Rejected(error) => Self::from_service(error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl ApiResponse {
+    fn ok<B: Serialize>(body: B) -> Result<Self, ApiError> {
+        Self::with_status(200, body)
+    }
+
+    fn created(id: Uuid) -> Result<Self, ApiError> {
+        Self::with_status(201, json!({ "id": id }))
+    }
+
+    fn with_status<B: Serialize>(status: u16, body: B) -> Result<Self, ApiError> {
+        let body = serde_json::to_value(body)
+            .map_err(|e| ApiError::new(500, "internal", e.to_string()))?;
+        Ok(Self { status, body })
+    }
+}
+
+impl From<ApiError> for ApiResponse {
+    fn from(error: ApiError) -> Self {
+        Self {
+            status: error.status,
+            body: json!({ "error": error }),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateSection {
+    #[serde(default)]
+    code: Option<String>,
+    name: String,
+    section_type: SectionType,
+    parent_id: Option<Uuid>,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RenameSection {
+    name: String,
+    effective: NaiveDate,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MoveSection {
+    parent_id: Option<Uuid>,
+    effective: NaiveDate,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeactivateSection {
+    effective: NaiveDate,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MergeSections {
+    target_section_id: Uuid,
+    effective: NaiveDate,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateTerm {
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RegisterSales {
+    amount: Money,
+    date: NaiveDateTime,
+    section_id: Uuid,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TransferSales {
+    target_section_id: Uuid,
+    date: NaiveDateTime,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CloseTerm {
+    #[serde(default)]
+    override_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SubmitSection {
+    section_id: Uuid,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CorrectTerm {
+    section_id: Uuid,
+    original_amount: Money,
+    correct_amount: Money,
+    date: NaiveDateTime,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RebalanceTerm {
+    source_section_id: Uuid,
+    target_section_id: Uuid,
+    amount: Money,
+    date: NaiveDateTime,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RejectCorrection {
+    reason: String,
+}
+
+/// A request reduced to what the router needs, independent of the transport.
+#[derive(Debug, Clone)]
+pub struct ApiRequest<'a> {
+    pub method: &'a str,
+    pub path: &'a str,
+    pub query: Option<&'a str>,
+    pub idempotency_key: Option<&'a str>,
+    pub actor: Option<&'a str>,
+    pub correlation_id: Option<&'a str>,
+    pub body: &'a str,
+}
+
+impl ApiRequest<'_> {
+    fn audit(&self, clock: &dyn Clock, reason: Option<String>) -> Result<AuditInfo, ApiError> {
+        Ok(AuditInfo::new(self.actor()?, AUDIT_SOURCE, clock)
+            .with_reason(reason)
+            .with_correlation_id(self.correlation_id.map(str::to_string)))
+    }
+
+    fn actor(&self) -> Result<ActorId, ApiError> {
+        let actor = self.actor.ok_or_else(|| {
+            ApiError::bad_request(format!("The {} header is required", ACTOR_HEADER))
+        })?;
+        ActorId::new(actor.to_string()).map_err(ApiError::bad_request)
+    }
+
+    /// Drafts a pending correction made by the `X-Actor`. Approved requests
+    /// get their idempotency key on approval, so the header is refused.
+    fn draft<S, T, L, A, P>(
+        &self,
+        service: &AccountingService<S, T, L, A>,
+        workflow: &mut CorrectionWorkflow<P>,
+        request: CorrectionRequest,
+        reason: Option<String>,
+    ) -> Result<ApiResponse, ApiError>
+    where
+        S: SectionRepository,
+        T: TermRepository,
+        L: SalesRepository,
+        A: AuditLogRepository,
+        P: PendingCorrectionRepository,
+    {
+        if self.idempotency_key.is_some() {
+            return Err(ApiError::bad_request(format!(
+                "Corrections get their {} on approval",
+                IDEMPOTENCY_HEADER
+            )));
+        }
+        let id = workflow
+            .draft(service, request, self.actor()?, reason.unwrap_or_default())
+            .map_err(ApiError::from_service)?;
+        ApiResponse::with_status(201, workflow.find(&id))
+    }
+}
+
+/// Routes one request to the matching `AccountingService` operation.
+/// Corrections and rebalances are never written directly: they are filed with
+/// `workflow` and written when a different actor approves them.
+pub fn handle<S, T, L, A, P>(
+    service: &mut AccountingService<S, T, L, A>,
+    workflow: &mut CorrectionWorkflow<P>,
+    request: &ApiRequest<'_>,
+) -> ApiResponse
+where
+    S: SectionRepository,
+    T: TermRepository,
+    L: SalesRepository,
+    A: AuditLogRepository,
+    P: PendingCorrectionRepository,
+{
+    route(service, workflow, request).unwrap_or_else(ApiResponse::from)
+}
+
+fn route<S, T, L, A, P>(
+    service: &mut AccountingService<S, T, L, A>,
+    workflow: &mut CorrectionWorkflow<P>,
+    request: &ApiRequest<'_>,
+) -> Result<ApiResponse, ApiError>
+where
+    S: SectionRepository,
+    T: TermRepository,
+    L: SalesRepository,
+    A: AuditLogRepository,
+    P: PendingCorrectionRepository,
+{
+    let segments: Vec<&str> = request
+        .path
+        .trim_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+    let key = request
+        .idempotency_key
+        .map(|k| IdempotencyKey::new(k.to_string()).map_err(ApiError::bad_request))
+        .transpose()?;
+
+    match (request.method, segments.as_slice()) {
+        ("GET", ["sections"]) => match query_param(request.query, "code").as_deref() {
+            Some(code) => {
+                let code = SectionCode::new(code.to_string()).map_err(ApiError::bad_request)?;
+                ApiResponse::ok(
+                    service
+                        .find_section_by_code(&code)
+                        .into_iter()
+                        .collect::<Vec<_>>(),
+                )
+            }
+            None => ApiResponse::ok(service.list_sections()),
+        },
+        ("POST", ["sections"]) => {
+            let body: CreateSection = parse_body(request.body)?;
+            let mut section = Section::new(
+                service.ids().next_id(),
+                body.name,
+                body.section_type,
+                body.parent_id,
+            )
+            .map_err(|e| ApiError::new(422, "rejected", e))?;
+            if let Some(code) = body.code {
+                let code = SectionCode::new(code).map_err(ApiError::bad_request)?;
+                section = section.with_code(code);
+            }
+            let audit = request.audit(service.clock(), body.reason)?;
+            let id = service
+                .create_section(section, key, audit)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::created(id)
+        }
+        ("GET", ["sections", id]) => {
+            let id = parse_id(id)?;
+            let section = service
+                .find_section(&id)
+                .ok_or_else(|| ApiError::not_found("Section not found"))?;
+            ApiResponse::ok(section)
+        }
+        ("POST", ["sections", id, "rename"]) => {
+            let id = parse_id(id)?;
+            let body: RenameSection = parse_body(request.body)?;
+            let audit = request.audit(service.clock(), body.reason)?;
+            service
+                .rename_section(id, body.name, body.effective, key, audit)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::ok(service.find_section(&id))
+        }
+        ("POST", ["sections", id, "move"]) => {
+            let id = parse_id(id)?;
+            let body: MoveSection = parse_body(request.body)?;
+            let audit = request.audit(service.clock(), body.reason)?;
+            service
+                .move_section(id, body.parent_id, body.effective, key, audit)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::ok(service.find_section(&id))
+        }
+        ("POST", ["sections", id, "deactivate"]) => {
+            let id = parse_id(id)?;
+            let body: DeactivateSection = parse_body(request.body)?;
+            let audit = request.audit(service.clock(), body.reason)?;
+            service
+                .deactivate_section(id, body.effective, key, audit)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::ok(service.find_section(&id))
+        }
+        ("POST", ["sections", id, "merge"]) => {
+            let id = parse_id(id)?;
+            let body: MergeSections = parse_body(request.body)?;
+            let audit = request.audit(service.clock(), body.reason)?;
+            service
+                .merge_sections(id, body.target_section_id, body.effective, key, audit)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::ok(service.find_section(&id))
+        }
+        ("GET", ["terms"]) => ApiResponse::ok(service.list_terms()),
+        ("POST", ["terms"]) => {
+            let body: CreateTerm = parse_body(request.body)?;
+            let term = Term::new(service.ids().next_id(), body.start_date, body.end_date)
+                .map_err(|e| ApiError::new(422, "rejected", e))?;
+            let audit = request.audit(service.clock(), body.reason)?;
+            let id = service
+                .create_term(term, key, audit)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::created(id)
+        }
+        ("GET", ["terms", id]) => {
+            let id = parse_id(id)?;
+            let term = service
+                .find_term(&id)
+                .ok_or_else(|| ApiError::not_found("Term not found"))?;
+            ApiResponse::ok(term)
+        }
+        ("POST", ["terms", id, "close"]) => {
+            let id = parse_id(id)?;
+            // The body is optional; an empty one closes only if every check passes
+            let body: CloseTerm = if request.body.trim().is_empty() {
+                CloseTerm::default()
+            } else {
+                parse_body(request.body)?
+            };
+            service
+                .close_term(
+                    id,
+                    body.override_reason,
+                    request.audit(service.clock(), None)?,
+                )
+                .map_err(ApiError::from_close)?;
+            ApiResponse::ok(service.find_term(&id))
+        }
+        ("POST", ["terms", id, "submissions"]) => {
+            let term_id = parse_id(id)?;
+            let body: SubmitSection = parse_body(request.body)?;
+            let audit = request.audit(service.clock(), body.reason)?;
+            service
+                .submit_section(term_id, body.section_id, key, audit)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::ok(service.find_term(&term_id))
+        }
+        ("POST", ["terms", id, "corrections"]) => {
+            let term_id = parse_id(id)?;
+            let body: CorrectTerm = parse_body(request.body)?;
+            let correction = CorrectionRequest::Correct {
+                term_id,
+                section_id: body.section_id,
+                original_amount: body.original_amount,
+                correct_amount: body.correct_amount,
+                date: body.date,
+            };
+            request.draft(service, workflow, correction, body.reason)
+        }
+        ("POST", ["terms", id, "rebalance"]) => {
+            let term_id = parse_id(id)?;
+            let body: RebalanceTerm = parse_body(request.body)?;
+            let rebalance = CorrectionRequest::Rebalance {
+                term_id,
+                source_section_id: body.source_section_id,
+                target_section_id: body.target_section_id,
+                amount: body.amount,
+                date: body.date,
+            };
+            request.draft(service, workflow, rebalance, body.reason)
+        }
+        ("GET", ["corrections"]) => {
+            let status = match query_param(request.query, "status").as_deref() {
+                Some("draft") => Some(CorrectionStatus::Draft),
+                Some("submitted") => Some(CorrectionStatus::Submitted),
+                Some("approved") => Some(CorrectionStatus::Approved),
+                Some("rejected") => Some(CorrectionStatus::Rejected),
+                Some(other) => {
+                    return Err(ApiError::bad_request(format!("Invalid status: {}", other)))
+                }
+                None => None,
+            };
+            ApiResponse::ok(workflow.list(status))
+        }
+        ("GET", ["corrections", id]) => {
+            let id = parse_id(id)?;
+            let correction = workflow
+                .find(&id)
+                .ok_or_else(|| ApiError::not_found("Pending correction not found"))?;
+            ApiResponse::ok(correction)
+        }
+        ("GET", ["corrections", id, "preview"]) => {
+            let id = parse_id(id)?;
+            let preview = workflow
+                .preview(service, id)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::ok(preview)
+        }
+        ("POST", ["corrections", id, "submit"]) => {
+            let id = parse_id(id)?;
+            workflow
+                .submit(service, id, &request.actor()?)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::ok(workflow.find(&id))
+        }
+        ("POST", ["corrections", id, "approve"]) => {
+            let id = parse_id(id)?;
+            workflow
+                .approve(service, id, request.actor()?, AUDIT_SOURCE)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::ok(workflow.find(&id))
+        }
+        ("POST", ["corrections", id, "reject"]) => {
+            let id = parse_id(id)?;
+            let body: RejectCorrection = parse_body(request.body)?;
+            workflow
+                .reject(service, id, request.actor()?, body.reason)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::ok(workflow.find(&id))
+        }
+        ("GET", ["terms", id, "report"]) => {
+            let id = parse_id(id)?;
+            let summary = service
+                .reports()
+                .term_summary(id)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::ok(summary)
+        }
+        ("GET", ["terms", id, "rollup"]) => {
+            let id = parse_id(id)?;
+            let structure = match query_param(request.query, "restate").as_deref() {
+                Some("true") => Structure::Restated,
+                Some("false") | None => Structure::AsOf,
+                Some(other) => {
+                    return Err(ApiError::bad_request(format!("Invalid restate: {}", other)))
+                }
+            };
+            let rollup = service
+                .reports()
+                .term_rollup(id, structure)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::ok(rollup)
+        }
+        ("GET", ["terms", id, "integrity"]) => {
+            let id = parse_id(id)?;
+            let report = service
+                .reports()
+                .term_integrity(id)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::ok(report)
+        }
+        ("GET", ["terms", id, "movement"]) => {
+            let id = parse_id(id)?;
+            let movement = service
+                .reports()
+                .term_movement(id)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::ok(movement)
+        }
+        ("GET", ["terms", id, "compare"]) => {
+            let id = parse_id(id)?;
+            let base_id = match query_param(request.query, "base").as_deref() {
+                Some(base) => parse_id(base)?,
+                None => return Err(ApiError::bad_request("Missing base term".to_string())),
+            };
+            let comparison = service
+                .reports()
+                .term_comparison(base_id, id)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::ok(comparison)
+        }
+        ("GET", ["sales"]) => {
+            let term_id = match query_param(request.query, "term_id").as_deref() {
+                Some(id) => parse_id(id)?,
+                None => {
+                    service
+                        .find_open_term()
+                        .ok_or_else(|| ApiError::not_found("No open term found"))?
+                        .id
+                }
+            };
+            let mut sales = service.list_sales(&term_id);
+            sales.sort_by(|a, b| a.date.cmp(&b.date).then(a.id.cmp(&b.id)));
+            ApiResponse::ok(sales)
+        }
+        ("POST", ["sales"]) => {
+            let body: RegisterSales = parse_body(request.body)?;
+            let audit = request.audit(service.clock(), body.reason)?;
+            let id = service
+                .register_sales(body.amount, body.date, body.section_id, key, audit)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::created(id)
+        }
+        ("GET", ["sales", id]) => {
+            let id = parse_id(id)?;
+            let sales = service
+                .find_sales(&id)
+                .ok_or_else(|| ApiError::not_found("Sales not found"))?;
+            ApiResponse::ok(sales)
+        }
+        ("POST", ["sales", id, "transfer"]) => {
+            let sales_id = parse_id(id)?;
+            let body: TransferSales = parse_body(request.body)?;
+            let audit = request.audit(service.clock(), body.reason)?;
+            let id = service
+                .transform_sales(sales_id, body.target_section_id, body.date, key, audit)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::created(id)
+        }
+        ("GET", ["series"]) => {
+            let interval = match query_param(request.query, "interval").as_deref() {
+                Some("day") => Interval::Day,
+                Some("week") => Interval::Week,
+                Some("month") => Interval::Month,
+                Some(other) => {
+                    return Err(ApiError::bad_request(format!(
+                        "Invalid interval: {}",
+                        other
+                    )))
+                }
+                None => return Err(ApiError::bad_request("Missing interval".to_string())),
+            };
+            let from = query_param(request.query, "from")
+                .as_deref()
+                .map(parse_date)
+                .transpose()?;
+            let to = query_param(request.query, "to")
+                .as_deref()
+                .map(parse_date)
+                .transpose()?;
+            let scope = match (from, to) {
+                (Some(from), Some(to)) => ExportScope::DateRange { from, to },
+                (None, None) => match query_param(request.query, "term_id").as_deref() {
+                    Some(id) => ExportScope::Term(parse_id(id)?),
+                    None => ExportScope::Term(
+                        service
+                            .find_open_term()
+                            .ok_or_else(|| ApiError::not_found("No open term found"))?
+                            .id,
+                    ),
+                },
+                _ => {
+                    return Err(ApiError::bad_request(
+                        "Pass both from and to, or neither".to_string(),
+                    ))
+                }
+            };
+            let section_id = query_param(request.query, "section_id")
+                .as_deref()
+                .map(parse_id)
+                .transpose()?;
+            let subtree = query_param(request.query, "subtree").as_deref() == Some("true");
+            let series = service
+                .reports()
+                .sales_series(scope, section_id, subtree, interval)
+                .map_err(ApiError::from_service)?;
+            ApiResponse::ok(series)
+        }
+        ("GET", ["audit"]) => {
+            let query = AuditQuery {
+                actor: query_param(request.query, "actor")
+                    .as_deref()
+                    .map(|a| ActorId::new(a.to_string()).map_err(ApiError::bad_request))
+                    .transpose()?,
+                entity_id: query_param(request.query, "entity_id")
+                    .as_deref()
+                    .map(parse_id)
+                    .transpose()?,
+                correlation_id: query_param(request.query, "correlation_id"),
+                ..AuditQuery::default()
+            };
+            ApiResponse::ok(service.audit_log().query(&query))
+        }
+        (
+            _,
+            ["sections"] | ["sections", _] | ["terms"] | ["terms", _] | ["sales"] | ["sales", _],
+        )
+        | (_, ["audit"] | ["series"] | ["corrections"] | ["corrections", _])
+        | (_, ["corrections", _, "preview" | "submit" | "approve" | "reject"])
+        | (_, ["sections", _, "rename" | "move" | "deactivate" | "merge"])
+        | (
+            _,
+            ["terms", _, "close" | "submissions" | "corrections" | "rebalance" | "report" | "rollup" | "compare"
+            | "movement" | "integrity"],
+        )
+        | (_, ["sales", _, "transfer"]) => Err(ApiError::new(
+            405,
+            "method_not_allowed",
+            format!("{} is not allowed on {}", request.method, request.path),
+        )),
+        _ => Err(ApiError::not_found(format!(
+            "No route for {}",
+            request.path
+        ))),
+    }
+}
+
+fn parse_body<B: DeserializeOwned>(body: &str) -> Result<B, ApiError> {
+    serde_json::from_str(body).map_err(|e| ApiError::bad_request(format!("Invalid body: {}", e)))
+}
+
+fn parse_id(value: &str) -> Result<Uuid, ApiError> {
+    Uuid::parse_str(value).map_err(|_| ApiError::bad_request(format!("Invalid ID: {}", value)))
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, ApiError> {
+    reference::parse_date(value)
+        .ok_or_else(|| ApiError::bad_request(format!("Invalid date: {}", value)))
+}
+
+/// Returns the decoded value of the first `name` parameter in the query
+/// string.
+fn query_param(query: Option<&str>, name: &str) -> Option<String> {
+    query?
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| percent_decode(key) == name)
+        .map(|(_, value)| percent_decode(value))
+}
+
+/// Decodes `+` and `%XX` escapes in a query string component. Malformed
+/// escapes are kept as written.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => match text
+                .get(i + 1..i + 3)
+                .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 2;
+                }
+                None => decoded.push(b'%'),
+            },
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Serves the JSON API over HTTP until `shutdown` is called.
+pub struct ApiServer {
+    server: Arc<Server>,
+}
+
+impl ApiServer {
+    pub fn bind(addr: &str) -> Result<Self, String> {
+        let server = Server::http(addr).map_err(|e| format!("Failed to bind {}: {}", addr, e))?;
+        Ok(Self {
+            server: Arc::new(server),
+        })
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    /// Returns a handle that stops `run` from another thread.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle {
+            server: Arc::clone(&self.server),
+        }
+    }
+
+    pub fn run<S, T, L, A, P>(
+        &self,
+        service: &mut AccountingService<S, T, L, A>,
+        workflow: &mut CorrectionWorkflow<P>,
+    ) where
+        S: SectionRepository,
+        T: TermRepository,
+        L: SalesRepository,
+        A: AuditLogRepository,
+        P: PendingCorrectionRepository,
+    {
+        for mut request in self.server.incoming_requests() {
+            let mut body = String::new();
+            let api_response = match request.as_reader().read_to_string(&mut body) {
+                Ok(_) => dispatch(service, workflow, &request, &body),
+                Err(e) => ApiError::bad_request(format!("Failed to read body: {}", e)).into(),
+            };
+            respond(request, api_response);
+        }
+    }
+
+    /// Like `run`, but takes the service and workflow from `open` for every
+    /// request, so each request reads what other processes (such as the CLI)
+    /// wrote to the store before it, and checks idempotency keys and term
+    /// state against that.
+    pub fn serve<S, T, L, A, P, F>(&self, mut open: F)
+    where
+        S: SectionRepository,
+        T: TermRepository,
+        L: SalesRepository,
+        A: AuditLogRepository,
+        P: PendingCorrectionRepository,
+        F: FnMut() -> Result<(AccountingService<S, T, L, A>, CorrectionWorkflow<P>), String>,
+    {
+        for mut request in self.server.incoming_requests() {
+            let mut body = String::new();
+            let api_response = match request.as_reader().read_to_string(&mut body) {
+                Ok(_) => match open() {
+                    Ok((mut service, mut workflow)) => {
+                        dispatch(&mut service, &mut workflow, &request, &body)
+                    }
+                    Err(e) => ApiError::new(500, "internal", e).into(),
+                },
+                Err(e) => ApiError::bad_request(format!("Failed to read body: {}", e)).into(),
+            };
+            respond(request, api_response);
+        }
+    }
+}
+
+fn respond(request: Request, api_response: ApiResponse) {
+    let content_type =
+        Header::from_bytes("Content-Type", "application/json").expect("static header is valid");
+    let response = Response::from_string(api_response.body.to_string())
+        .with_status_code(api_response.status)
+        .with_header(content_type);
+    if let Err(e) = request.respond(response) {
+        eprintln!("error: failed to send response: {}", e);
+    }
+}
+
+pub struct ShutdownHandle {
+    server: Arc<Server>,
+}
+
+impl ShutdownHandle {
+    pub fn shutdown(&self) {
+        self.server.unblock();
+    }
+}
+
+fn dispatch<S, T, L, A, P>(
+    service: &mut AccountingService<S, T, L, A>,
+    workflow: &mut CorrectionWorkflow<P>,
+    request: &Request,
+    body: &str,
+) -> ApiResponse
+where
+    S: SectionRepository,
+    T: TermRepository,
+    L: SalesRepository,
+    A: AuditLogRepository,
+    P: PendingCorrectionRepository,
+{
+    let (path, query) = match request.url().split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (request.url(), None),
+    };
+    let method = match request.method() {
+        Method::Get => "GET",
+        Method::Post => "POST",
+        Method::Put => "PUT",
+        Method::Delete => "DELETE",
+        Method::Patch => "PATCH",
+        _ => "OTHER",
+    };
+    let header = |name: &'static str| {
+        request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv(name))
+            .map(|h| h.value.as_str())
+    };
+
+    handle(
+        service,
+        workflow,
+        &ApiRequest {
+            method,
+            path,
+            query,
+            idempotency_key: header(IDEMPOTENCY_HEADER),
+            actor: header(ACTOR_HEADER),
+            correlation_id: header(CORRELATION_HEADER),
+            body,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::in_memory::InMemoryPendingCorrectionRepository;
+    use crate::test_support::service;
+
+    fn request<'a>(method: &'a str, path: &'a str, body: &'a str) -> ApiRequest<'a> {
+        ApiRequest {
+            method,
+            path,
+            query: None,
+            idempotency_key: None,
+            actor: Some("tester"),
+            correlation_id: None,
+            body,
+        }
+    }
+
+    #[test]
+    fn test_error_status_codes() {
+        let mut service = service();
+        let mut workflow = CorrectionWorkflow::new(InMemoryPendingCorrectionRepository::new());
+
+        let missing = format!("/terms/{}/close", Uuid::new_v4());
+        assert_eq!(
+            handle(&mut service, &mut workflow, &request("POST", &missing, "")).status,
+            404
+        );
+        assert_eq!(
+            handle(&mut service, &mut workflow, &request("POST", "/sales", "{")).status,
+            400
+        );
+        assert_eq!(
+            handle(
+                &mut service,
+                &mut workflow,
+                &request("DELETE", "/sales", "")
+            )
+            .status,
+            405
+        );
+        assert_eq!(
+            handle(&mut service, &mut workflow, &request("GET", "/nope", "")).status,
+            404
+        );
+
+        let response = handle(
+            &mut service,
+            &mut workflow,
+            &request(
+                "POST",
+                "/terms",
+                r#"{"start_date":"2025-12-31","end_date":"2025-01-01"}"#,
+            ),
+        );
+        assert_eq!(response.status, 422);
+        assert_eq!(response.body["error"]["code"], "rejected");
+
+        // A missing parent is part of the request, not the resource addressed
+        let body = format!(
+            r#"{{"name":"Orphan","section_type":"Section","parent_id":"{}"}}"#,
+            Uuid::new_v4()
+        );
+        let response = handle(
+            &mut service,
+            &mut workflow,
+            &request("POST", "/sections", &body),
+        );
+        assert_eq!(response.status, 422);
+        assert_eq!(response.body["error"]["code"], "rejected");
+    }
+
+    #[test]
+    fn test_query_param_decodes_escapes() {
+        let query = Some("actor=Ana+Lopez&correlation_id=batch%2F7%3A1&x=%zz%4");
+        assert_eq!(query_param(query, "actor").as_deref(), Some("Ana Lopez"));
+        assert_eq!(
+            query_param(query, "correlation_id").as_deref(),
+            Some("batch/7:1")
+        );
+        assert_eq!(query_param(query, "x").as_deref(), Some("%zz%4"));
+        assert_eq!(query_param(query, "missing"), None);
+    }
+}
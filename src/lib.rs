@@ -0,0 +1,7 @@
+pub mod application;
+pub mod domain;
+pub mod infrastructure;
+pub mod interface;
+
+#[cfg(test)]
+mod test_support;
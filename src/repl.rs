@@ -0,0 +1,758 @@
+use crate::cli::{
+    correct_request, execute, rebalance_request, section_label, Command, Context, Output,
+    SalesCommand, SectionCommand, Service, TermCommand,
+};
+use accounting_spec::application::approval::CorrectionWorkflow;
+use accounting_spec::application::service::AccountingService;
+use accounting_spec::domain::entity::{CorrectionRequest, Sales};
+use accounting_spec::domain::repository::{SalesRepository, SectionRepository, TermRepository};
+use accounting_spec::domain::value_object::Money;
+use accounting_spec::infrastructure::file_store::FilePendingCorrectionRepository;
+use accounting_spec::infrastructure::in_memory::{
+    InMemoryAuditLogRepository, InMemorySalesRepository, InMemorySectionRepository,
+    InMemoryTermRepository,
+};
+use clap::{Parser, Subcommand};
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Editor, Helper};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use uuid::Uuid;
+
+const HISTORY_FILE: &str = ".shell_history";
+
+const KEYWORDS: &[&str] = &[
+    "section",
+    "term",
+    "sales",
+    "correct",
+    "rebalance",
+    "report",
+    "audit",
+    "approval",
+    "role",
+    "grant",
+    "revoke",
+    "draft",
+    "help",
+    "exit",
+    "add",
+    "list",
+    "close",
+    "register",
+    "transfer",
+    "rename",
+    "move",
+    "deactivate",
+    "merge",
+    "tree",
+    "begin",
+    "show",
+    "commit",
+    "discard",
+    "submit",
+    "approve",
+    "reject",
+    "--section",
+    "--type",
+    "--parent",
+    "--term",
+    "--amount",
+    "--date",
+    "--to",
+    "--from",
+    "--original",
+    "--correct",
+    "--key",
+    "--reason",
+    "--override",
+    "--status",
+    "--role",
+    "--effective",
+    "--into",
+    "--top-level",
+    "--rollup",
+    "--restate",
+    "--compare",
+    "--csv",
+    "--movement",
+    "series",
+    "--subtree",
+    "--format",
+    "check",
+];
+
+type DraftService = AccountingService<
+    InMemorySectionRepository,
+    InMemoryTermRepository,
+    InMemorySalesRepository,
+    InMemoryAuditLogRepository,
+>;
+
+#[derive(Debug, Parser)]
+#[command(no_binary_name = true, name = "", disable_version_flag = true)]
+struct ShellLine {
+    #[command(subcommand)]
+    command: ShellCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum ShellCommand {
+    /// Stage corrections and rebalances before filing them for approval
+    #[command(subcommand)]
+    Draft(DraftCommand),
+    /// Leave the shell
+    #[command(alias = "quit")]
+    Exit,
+    #[command(flatten)]
+    Ledger(Command),
+}
+
+#[derive(Debug, Subcommand)]
+enum DraftCommand {
+    /// Start a draft; `correct` and `rebalance` are staged until commit
+    Begin,
+    /// Show section balances with the staged operations applied
+    Show,
+    /// File the staged operations for approval
+    Commit,
+    /// Drop the staged operations
+    Discard,
+}
+
+#[derive(Debug, Clone)]
+struct DraftOperation {
+    request: CorrectionRequest,
+    reason: String,
+    /// What the operation writes to the draft's copy, once staged
+    entries: Vec<Sales>,
+}
+
+impl DraftOperation {
+    fn term_id(&self) -> Uuid {
+        self.request.term_id()
+    }
+
+    /// The entries the operation would write to the draft's copy of the
+    /// ledger. Only approval writes corrections, so the draft saves them
+    /// itself.
+    fn written_to(&self, service: &DraftService, context: &Context) -> Result<Vec<Sales>, String> {
+        let audit = context.audit(Some(self.reason.clone()))?;
+        match self.request.clone() {
+            CorrectionRequest::Correct {
+                term_id,
+                section_id,
+                original_amount,
+                correct_amount,
+                date,
+            } => service.preview_correct_term(
+                term_id,
+                section_id,
+                original_amount,
+                correct_amount,
+                date,
+                None,
+                audit,
+            ),
+            CorrectionRequest::Rebalance {
+                term_id,
+                source_section_id,
+                target_section_id,
+                amount,
+                date,
+            } => service.preview_rebalance_term(
+                term_id,
+                source_section_id,
+                target_section_id,
+                amount,
+                date,
+                None,
+                audit,
+            ),
+        }
+        .map(|preview| preview.sales)
+        .map_err(String::from)
+    }
+}
+
+/// Session-scoped copy of the ledger that staged operations are applied to.
+struct Draft {
+    service: DraftService,
+    operations: Vec<DraftOperation>,
+}
+
+impl Draft {
+    fn begin(service: &Service) -> Result<Self, String> {
+        Ok(Self {
+            service: copy(service, &[])?,
+            operations: Vec::new(),
+        })
+    }
+
+    /// Checks the operation against the draft's copy and rebuilds the copy
+    /// with its entries added.
+    fn stage(
+        &mut self,
+        service: &Service,
+        mut operation: DraftOperation,
+        context: &Context,
+    ) -> Result<(), String> {
+        operation.entries = operation.written_to(&self.service, context)?;
+        self.operations.push(operation);
+        self.service = copy(service, &self.staged_entries())?;
+        Ok(())
+    }
+
+    fn staged_entries(&self) -> Vec<Sales> {
+        self.operations
+            .iter()
+            .flat_map(|o| o.entries.clone())
+            .collect()
+    }
+
+    /// Checks every staged operation against the current ledger, each with
+    /// the ones before it applied as `draft show` has them, and returns the
+    /// copy each one is checked against.
+    fn check(&self, service: &Service, context: &Context) -> Result<Vec<DraftService>, String> {
+        let maker = context.actor()?;
+        let mut ledgers = Vec::new();
+        let mut entries = Vec::new();
+        for (index, operation) in self.operations.iter().enumerate() {
+            let ledger = copy(service, &entries)?;
+            service
+                .authorize_request(&operation.request, &maker)
+                .and_then(|_| Ok(operation.written_to(&ledger, context)?))
+                .map(|written| entries.extend(written))
+                .map_err(|e| format!("Operation {} no longer applies: {}", index + 1, e))?;
+            ledgers.push(ledger);
+        }
+        Ok(ledgers)
+    }
+
+    /// Drops the first `count` operations once they are filed and rebuilds
+    /// the copy from the ones still staged.
+    fn drop_filed(&mut self, service: &Service, count: usize) -> Result<(), String> {
+        self.operations.drain(..count);
+        self.service = copy(service, &self.staged_entries())?;
+        Ok(())
+    }
+}
+
+/// An in-memory copy of the ledger with `staged` entries added.
+fn copy(service: &Service, staged: &[Sales]) -> Result<DraftService, String> {
+    let mut sections = InMemorySectionRepository::new();
+    for section in service.list_sections() {
+        sections.save(section)?;
+    }
+    let mut terms = InMemoryTermRepository::new();
+    let mut sales = InMemorySalesRepository::new();
+    for term in service.list_terms() {
+        for entry in service.list_sales(&term.id) {
+            sales.save(entry)?;
+        }
+        terms.save(term)?;
+    }
+    sales.save_all(staged.to_vec())?;
+
+    Ok(AccountingService::new(
+        sections,
+        terms,
+        sales,
+        InMemoryAuditLogRepository::new(),
+    ))
+}
+
+#[derive(Debug, Serialize)]
+struct BalanceChange {
+    term_id: Uuid,
+    section_id: Uuid,
+    section_name: String,
+    current: Money,
+    draft: Money,
+    change: Money,
+}
+
+fn balance_changes(service: &Service, draft: &Draft) -> Result<Vec<BalanceChange>, String> {
+    let mut term_ids: Vec<Uuid> = Vec::new();
+    for operation in &draft.operations {
+        if !term_ids.contains(&operation.term_id()) {
+            term_ids.push(operation.term_id());
+        }
+    }
+
+    let mut changes = Vec::new();
+    for term_id in term_ids {
+        let mut totals: BTreeMap<Uuid, (Money, Money)> = BTreeMap::new();
+        for section in service.reports().term_summary(term_id)?.sections {
+            totals
+                .entry(section.section_id)
+                .or_insert((Money::zero(), Money::zero()))
+                .0 = section.total;
+        }
+        for section in draft.service.reports().term_summary(term_id)?.sections {
+            totals
+                .entry(section.section_id)
+                .or_insert((Money::zero(), Money::zero()))
+                .1 = section.total;
+        }
+        let mut rows: Vec<BalanceChange> = totals
+            .into_iter()
+            .map(|(section_id, (current, after))| BalanceChange {
+                term_id,
+                section_id,
+                section_name: section_label(service, Some(section_id)),
+                current,
+                draft: after,
+                change: after - current,
+            })
+            .collect();
+        rows.sort_by(|a, b| a.section_name.cmp(&b.section_name));
+        changes.extend(rows);
+    }
+    Ok(changes)
+}
+
+fn show_draft(service: &Service, draft: &Draft, output: &Output) -> Result<(), String> {
+    let changes = balance_changes(service, draft)?;
+    if !output.json {
+        println!("Draft with {} staged operation(s)", draft.operations.len());
+    }
+    let rows = changes
+        .iter()
+        .map(|c| {
+            vec![
+                c.term_id.to_string(),
+                c.section_name.clone(),
+                c.current.to_string(),
+                c.draft.to_string(),
+                c.change.to_string(),
+            ]
+        })
+        .collect();
+    output.table(
+        &changes,
+        &["Term", "Section", "Current", "Draft", "Change"],
+        rows,
+    );
+    Ok(())
+}
+
+struct ShellHelper {
+    candidates: Vec<String>,
+}
+
+impl ShellHelper {
+    fn refresh(&mut self, service: &Service) {
+        let mut candidates: Vec<String> = KEYWORDS.iter().map(|k| k.to_string()).collect();
+        for section in service.list_sections() {
+            if section.name.contains(char::is_whitespace) {
+                candidates.push(format!("\"{}\"", section.name));
+            } else {
+                candidates.push(section.name);
+            }
+        }
+        candidates.extend(service.list_terms().into_iter().map(|t| t.id.to_string()));
+        candidates.sort();
+        candidates.dedup();
+        self.candidates = candidates;
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let start = word_start(&line[..pos]);
+        let prefix = &line[start..pos];
+        let matches = self
+            .candidates
+            .iter()
+            .filter(|c| c.starts_with(prefix))
+            .map(|c| Pair {
+                display: c.clone(),
+                replacement: c.clone(),
+            })
+            .collect();
+        Ok((start, matches))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+/// Start of the word under the cursor; an open quote extends the word back
+/// to the quote so names with spaces complete as one word.
+fn word_start(before_cursor: &str) -> usize {
+    let quotes = before_cursor.matches('"').count();
+    if quotes % 2 == 1 {
+        return before_cursor.rfind('"').unwrap_or(0);
+    }
+    before_cursor
+        .rfind(char::is_whitespace)
+        .map(|i| i + 1)
+        .unwrap_or(0)
+}
+
+/// Splits a shell line into words, honouring single and double quotes.
+fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_word = true;
+            }
+            None if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err("Unterminated quote".to_string());
+    }
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+fn is_read_only(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::Section(SectionCommand::List)
+            | Command::Term(TermCommand::List)
+            | Command::Sales(SalesCommand::List { .. })
+            | Command::Report { .. }
+    )
+}
+
+/// Runs one shell line. Returns `Ok(false)` when the shell should exit.
+fn run_line(
+    service: &mut Service,
+    draft: &mut Option<Draft>,
+    words: Vec<String>,
+    context: &Context,
+) -> Result<bool, Box<dyn Error>> {
+    let output = &context.output;
+    let line = match ShellLine::try_parse_from(words) {
+        Ok(line) => line,
+        Err(e) => {
+            e.print()?;
+            return Ok(true);
+        }
+    };
+
+    match (line.command, draft.as_mut()) {
+        (ShellCommand::Exit, _) => {
+            if draft.is_some() {
+                println!("Discarding the open draft");
+            }
+            return Ok(false);
+        }
+        (ShellCommand::Draft(DraftCommand::Begin), None) => {
+            *draft = Some(Draft::begin(service)?);
+            println!("Draft started; correct and rebalance are staged until `draft commit`");
+        }
+        (ShellCommand::Draft(DraftCommand::Begin), Some(_)) => {
+            return Err("A draft is already open".into());
+        }
+        (ShellCommand::Draft(_), None) => return Err("No open draft; use `draft begin`".into()),
+        (ShellCommand::Draft(DraftCommand::Show), Some(open)) => {
+            show_draft(service, open, output)?;
+        }
+        (ShellCommand::Draft(DraftCommand::Discard), Some(_)) => {
+            *draft = None;
+            println!("Draft discarded");
+        }
+        (ShellCommand::Draft(DraftCommand::Commit), Some(open)) => {
+            // Staged operations are filed for approval like `correct` and
+            // `rebalance` outside a draft; nothing is written to the ledger.
+            // All of them are checked before any is filed.
+            let ledgers = open.check(service, context)?;
+            let mut workflow =
+                CorrectionWorkflow::new(FilePendingCorrectionRepository::open(&context.store)?);
+            let maker = context.actor()?;
+            let mut filed = 0;
+            let mut failure = None;
+            for (operation, ledger) in open.operations.iter().zip(&ledgers) {
+                let result = workflow
+                    .draft_against(
+                        service,
+                        ledger,
+                        operation.request.clone(),
+                        maker.clone(),
+                        operation.reason.clone(),
+                    )
+                    .and_then(|id| workflow.submit(service, id, &maker).map(|_| id));
+                match result {
+                    Ok(id) => {
+                        output.id(id);
+                        filed += 1;
+                    }
+                    Err(e) => {
+                        failure = Some(e);
+                        break;
+                    }
+                }
+            }
+            if let Some(e) = failure {
+                // Filed operations leave the draft, so committing again does
+                // not file them twice
+                open.drop_filed(service, filed)?;
+                return Err(format!(
+                    "Commit stopped at operation {}: {}; {} filed operation(s) were removed from the draft",
+                    filed + 1,
+                    e,
+                    filed
+                )
+                .into());
+            }
+            output.done(&format!("Submitted {} operation(s) for approval", filed));
+            *draft = None;
+        }
+        (ShellCommand::Ledger(Command::Correct(args)), Some(open)) => {
+            let operation = DraftOperation {
+                request: correct_request(service, &args)?,
+                reason: args.reason,
+                entries: Vec::new(),
+            };
+            open.stage(service, operation, context)?;
+            show_draft(service, open, output)?;
+        }
+        (ShellCommand::Ledger(Command::Rebalance(args)), Some(open)) => {
+            let operation = DraftOperation {
+                request: rebalance_request(service, &args)?,
+                reason: args.reason,
+                entries: Vec::new(),
+            };
+            open.stage(service, operation, context)?;
+            show_draft(service, open, output)?;
+        }
+        (ShellCommand::Ledger(command), Some(_)) if !is_read_only(&command) => {
+            return Err("Commit or discard the open draft first".into());
+        }
+        (ShellCommand::Ledger(command), _) => execute(service, command, context)?,
+    }
+    Ok(true)
+}
+
+pub fn run(service: &mut Service, context: &Context) -> Result<(), Box<dyn Error>> {
+    let mut editor: Editor<ShellHelper, DefaultHistory> = Editor::new()?;
+    let mut helper = ShellHelper {
+        candidates: Vec::new(),
+    };
+    helper.refresh(service);
+    editor.set_helper(Some(helper));
+
+    let history = context.store.join(HISTORY_FILE);
+    // A missing history file just means a first session
+    let _ = editor.load_history(&history);
+
+    let mut draft: Option<Draft> = None;
+    loop {
+        let prompt = if draft.is_some() {
+            "ledger (draft)> "
+        } else {
+            "ledger> "
+        };
+        let line = match editor.readline(prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        editor.add_history_entry(line.as_str())?;
+
+        let result = split_words(&line)
+            .map_err(Into::into)
+            .and_then(|words| run_line(service, &mut draft, words, context));
+        match result {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => eprintln!("error: {}", e),
+        }
+        if let Some(helper) = editor.helper_mut() {
+            helper.refresh(service);
+        }
+    }
+
+    editor.save_history(&history)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_words_honours_quotes() {
+        assert_eq!(
+            split_words(r#"sales register --section "Sales Dept A" --amount 10"#).unwrap(),
+            vec![
+                "sales",
+                "register",
+                "--section",
+                "Sales Dept A",
+                "--amount",
+                "10"
+            ]
+        );
+        assert_eq!(split_words("a '' b").unwrap(), vec!["a", "", "b"]);
+        assert!(split_words("section add \"Tokyo").is_err());
+    }
+
+    #[test]
+    fn test_word_start() {
+        assert_eq!(word_start("sales li"), 6);
+        assert_eq!(word_start("correct --section \"Sales D"), 18);
+        assert_eq!(word_start(""), 0);
+    }
+
+    #[test]
+    fn test_parse_shell_lines() {
+        let line = ShellLine::try_parse_from(["draft", "begin"]).unwrap();
+        assert!(matches!(
+            line.command,
+            ShellCommand::Draft(DraftCommand::Begin)
+        ));
+        let line = ShellLine::try_parse_from(["quit"]).unwrap();
+        assert!(matches!(line.command, ShellCommand::Exit));
+        let line = ShellLine::try_parse_from(["term", "list"]).unwrap();
+        assert!(matches!(
+            line.command,
+            ShellCommand::Ledger(Command::Term(TermCommand::List))
+        ));
+    }
+
+    #[test]
+    fn test_commit_checks_every_operation_before_filing() {
+        use crate::cli::open_service;
+        use accounting_spec::application::render::RenderFormat;
+        use accounting_spec::domain::entity::{Section, SectionType, Term};
+        use accounting_spec::domain::provider::SystemClock;
+        use chrono::NaiveDate;
+        use std::sync::Arc;
+
+        let store = std::env::temp_dir().join(format!("accounting-draft-{}", Uuid::new_v4()));
+        let context = Context {
+            store: store.clone(),
+            output: Output {
+                json: true,
+                format: RenderFormat::Text,
+            },
+            dry_run: false,
+            actor: Some("alice".to_string()),
+            correlation_id: None,
+            source: "test",
+            today: NaiveDate::from_ymd_opt(2025, 6, 1).unwrap(),
+            clock: Arc::new(SystemClock),
+        };
+        let mut service = open_service(&store).unwrap();
+        let mut section_ids = Vec::new();
+        for name in ["East", "West"] {
+            let section = Section::new(
+                service.ids().next_id(),
+                name.to_string(),
+                SectionType::Section,
+                None,
+            )
+            .unwrap();
+            section_ids.push(
+                service
+                    .create_section(section, None, context.audit(None).unwrap())
+                    .unwrap(),
+            );
+        }
+        let term = Term::new(
+            service.ids().next_id(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+        )
+        .unwrap();
+        let term_id = service
+            .create_term(term, None, context.audit(None).unwrap())
+            .unwrap();
+
+        let mut draft = None;
+        let mut run = |service: &mut Service, line: &str| {
+            run_line(service, &mut draft, split_words(line).unwrap(), &context)
+                .map_err(|e| e.to_string())
+        };
+        run(&mut service, "draft begin").unwrap();
+        for (from, to, date) in [
+            ("East", "West", "2025-05-01"),
+            ("West", "East", "2025-08-01"),
+        ] {
+            run(
+                &mut service,
+                &format!(
+                    "rebalance --term {} --from {} --to {} --amount 10 --date {} --reason moved",
+                    term_id, from, to, date
+                ),
+            )
+            .unwrap();
+        }
+
+        // West stops taking postings after the second operation was staged
+        service
+            .deactivate_section(
+                section_ids[1],
+                NaiveDate::from_ymd_opt(2025, 7, 1).unwrap(),
+                None,
+                context.audit(None).unwrap(),
+            )
+            .unwrap();
+        let error = run(&mut service, "draft commit").unwrap_err();
+        assert!(
+            error.starts_with("Operation 2 no longer applies"),
+            "{}",
+            error
+        );
+        let workflow =
+            CorrectionWorkflow::new(FilePendingCorrectionRepository::open(&store).unwrap());
+        assert!(workflow.list(None).is_empty());
+        let open = draft.as_mut().unwrap();
+        assert_eq!(open.operations.len(), 2);
+
+        // Once filed, operations leave the draft
+        open.drop_filed(&service, 1).unwrap();
+        assert_eq!(open.operations.len(), 1);
+        assert_eq!(open.service.list_sales(&term_id).len(), 2);
+
+        std::fs::remove_dir_all(store).unwrap();
+    }
+}
@@ -0,0 +1,39 @@
+//! Fixtures shared by the unit tests.
+
+use crate::application::service::AccountingService;
+use crate::domain::provider::SystemClock;
+use crate::domain::value_object::{ActorId, AuditInfo};
+use crate::infrastructure::in_memory::{
+    InMemoryAuditLogRepository, InMemorySalesRepository, InMemorySectionRepository,
+    InMemoryTermRepository,
+};
+
+pub(crate) type MemoryService = AccountingService<
+    InMemorySectionRepository,
+    InMemoryTermRepository,
+    InMemorySalesRepository,
+    InMemoryAuditLogRepository,
+>;
+
+/// An empty service over in-memory repositories, without a policy.
+pub(crate) fn service() -> MemoryService {
+    AccountingService::new(
+        InMemorySectionRepository::new(),
+        InMemoryTermRepository::new(),
+        InMemorySalesRepository::new(),
+        InMemoryAuditLogRepository::new(),
+    )
+}
+
+pub(crate) fn audit_as(actor: &str) -> AuditInfo {
+    AuditInfo::new(
+        ActorId::new(actor.to_string()).unwrap(),
+        "test",
+        &SystemClock,
+    )
+}
+
+/// Audit info with a reason, so corrections are accepted too.
+pub(crate) fn audit() -> AuditInfo {
+    audit_as("tester").with_reason(Some("test".to_string()))
+}
@@ -0,0 +1,384 @@
+use accounting_spec::application::approval::CorrectionWorkflow;
+use accounting_spec::application::service::AccountingService;
+use accounting_spec::domain::entity::{Section, SectionType};
+use accounting_spec::domain::provider::SystemClock;
+use accounting_spec::domain::value_object::{ActorId, AuditInfo};
+use accounting_spec::infrastructure::file_store::{
+    FileAuditLogRepository, FilePendingCorrectionRepository, FileSalesRepository,
+    FileSectionRepository, FileTermRepository,
+};
+use accounting_spec::infrastructure::in_memory::{
+    InMemoryAuditLogRepository, InMemoryPendingCorrectionRepository, InMemorySalesRepository,
+    InMemorySectionRepository, InMemoryTermRepository,
+};
+use accounting_spec::interface::http::{ApiServer, ShutdownHandle};
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::thread::{self, JoinHandle};
+
+struct TestServer {
+    addr: SocketAddr,
+    shutdown: ShutdownHandle,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl TestServer {
+    fn start() -> Self {
+        Self::spawn(|server| {
+            let mut service = AccountingService::new(
+                InMemorySectionRepository::new(),
+                InMemoryTermRepository::new(),
+                InMemorySalesRepository::new(),
+                InMemoryAuditLogRepository::new(),
+            );
+            let mut workflow = CorrectionWorkflow::new(InMemoryPendingCorrectionRepository::new());
+            server.run(&mut service, &mut workflow);
+        })
+    }
+
+    /// Serves `store`, reopening it for every request as the server binary
+    /// does.
+    fn start_on(store: &Path) -> Self {
+        let store = store.to_path_buf();
+        Self::spawn(move |server| server.serve(|| open_store(&store)))
+    }
+
+    fn spawn(serve: impl FnOnce(ApiServer) + Send + 'static) -> Self {
+        let server = ApiServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let shutdown = server.shutdown_handle();
+        let thread = thread::spawn(move || serve(server));
+        Self {
+            addr,
+            shutdown,
+            thread: Some(thread),
+        }
+    }
+
+    fn get(&self, path: &str) -> (u16, Value) {
+        self.send("GET", path, None, None, "tester")
+    }
+
+    fn post(&self, path: &str, body: Value) -> (u16, Value) {
+        self.send("POST", path, Some(body), None, "tester")
+    }
+
+    fn post_as(&self, actor: &str, path: &str, body: Value) -> (u16, Value) {
+        self.send("POST", path, Some(body), None, actor)
+    }
+
+    fn post_with_key(&self, path: &str, body: Value, key: &str) -> (u16, Value) {
+        self.send("POST", path, Some(body), Some(key), "tester")
+    }
+
+    /// Minimal HTTP/1.1 client: one request per connection.
+    fn send(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<Value>,
+        key: Option<&str>,
+        actor: &str,
+    ) -> (u16, Value) {
+        let body = body.map(|b| b.to_string()).unwrap_or_default();
+        let mut request = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Type: application/json\r\nContent-Length: {}\r\nX-Actor: {}\r\n",
+            method,
+            path,
+            self.addr,
+            body.len(),
+            actor
+        );
+        if let Some(key) = key {
+            request.push_str(&format!("Idempotency-Key: {}\r\n", key));
+        }
+        request.push_str("\r\n");
+        request.push_str(&body);
+
+        let mut stream = TcpStream::connect(self.addr).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse().ok())
+            .unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.shutdown.shutdown();
+        if let Some(thread) = self.thread.take() {
+            thread.join().unwrap();
+        }
+    }
+}
+
+type FileService = AccountingService<
+    FileSectionRepository,
+    FileTermRepository,
+    FileSalesRepository,
+    FileAuditLogRepository,
+>;
+
+fn open_store(
+    store: &Path,
+) -> Result<
+    (
+        FileService,
+        CorrectionWorkflow<FilePendingCorrectionRepository>,
+    ),
+    String,
+> {
+    let service = AccountingService::new(
+        FileSectionRepository::open(store)?,
+        FileTermRepository::open(store)?,
+        FileSalesRepository::open(store)?,
+        FileAuditLogRepository::open(store)?,
+    );
+    let workflow = CorrectionWorkflow::new(FilePendingCorrectionRepository::open(store)?);
+    Ok((service, workflow))
+}
+
+fn temp_store(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("accounting-http-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn setup(server: &TestServer) -> (String, String, String) {
+    let (status, a) = server.post(
+        "/sections",
+        json!({ "name": "Section A", "section_type": "Section", "parent_id": null }),
+    );
+    assert_eq!(status, 201);
+    let (_, b) = server.post(
+        "/sections",
+        json!({ "name": "Section B", "section_type": "Section", "parent_id": null }),
+    );
+    let (status, term) = server.post(
+        "/terms",
+        json!({ "start_date": "2025-01-01", "end_date": "2025-12-31" }),
+    );
+    assert_eq!(status, 201);
+
+    let id = |v: &Value| v["id"].as_str().unwrap().to_string();
+    (id(&a), id(&b), id(&term))
+}
+
+#[test]
+fn test_register_transfer_and_report() {
+    let server = TestServer::start();
+    let (a, b, term) = setup(&server);
+
+    let (status, sale) = server.post(
+        "/sales",
+        json!({ "amount": "1000.00", "date": "2025-06-15T10:00:00", "section_id": a }),
+    );
+    assert_eq!(status, 201);
+    let sale_id = sale["id"].as_str().unwrap();
+
+    let (status, moved) = server.post(
+        &format!("/sales/{}/transfer", sale_id),
+        json!({ "target_section_id": b, "date": "2025-06-20T14:00:00" }),
+    );
+    assert_eq!(status, 201);
+    let (status, entry) = server.get(&format!("/sales/{}", moved["id"].as_str().unwrap()));
+    assert_eq!(status, 200);
+    assert_eq!(entry["sales_type"], "Adjustment");
+    assert_eq!(entry["related_sales_id"], sale["id"]);
+
+    let (status, listed) = server.get(&format!("/sales?term_id={}", term));
+    assert_eq!(status, 200);
+    assert_eq!(listed.as_array().unwrap().len(), 3);
+
+    let (status, closed) = server.post(&format!("/terms/{}/close", term), json!({}));
+    assert_eq!(status, 200);
+    assert_eq!(closed["status"], "Closed");
+
+    // Rebalances are filed for approval, not written
+    let (status, pending) = server.post(
+        &format!("/terms/{}/rebalance", term),
+        json!({
+            "source_section_id": b,
+            "target_section_id": a,
+            "amount": "100.00",
+            "date": "2025-12-31T23:59:59",
+            "reason": "Year-end reallocation"
+        }),
+    );
+    assert_eq!(status, 201);
+    assert_eq!(pending["status"], "Draft");
+    let pending = format!("/corrections/{}", pending["id"].as_str().unwrap());
+    let (status, _) = server.post(&format!("{}/submit", pending), json!({}));
+    assert_eq!(status, 200);
+    let (_, listed) = server.get(&format!("/sales?term_id={}", term));
+    assert_eq!(listed.as_array().unwrap().len(), 3);
+
+    // The maker cannot approve their own request
+    let (status, _) = server.post(&format!("{}/approve", pending), json!({}));
+    assert_eq!(status, 422);
+    let (status, approved) = server.post_as("checker", &format!("{}/approve", pending), json!({}));
+    assert_eq!(status, 200);
+    assert_eq!(approved["status"], "Approved");
+    assert_eq!(approved["checker"], "checker");
+
+    let (status, report) = server.get(&format!("/terms/{}/report", term));
+    assert_eq!(status, 200);
+    assert_eq!(report["total"], "1000.00");
+    assert_eq!(report["sections"][0]["section_name"], "Section A");
+    assert_eq!(report["sections"][0]["total"], "100.00");
+    assert_eq!(report["sections"][1]["total"], "900.00");
+}
+
+#[test]
+fn test_idempotency_key_header() {
+    let server = TestServer::start();
+    let (a, _, term) = setup(&server);
+
+    let body = json!({ "amount": "10.00", "date": "2025-06-15T10:00:00", "section_id": a });
+    let (_, first) = server.post_with_key("/sales", body.clone(), "pos-1");
+    let (status, second) = server.post_with_key("/sales", body, "pos-1");
+    assert_eq!(status, 201);
+    assert_eq!(first, second);
+
+    let (status, error) = server.post_with_key(
+        "/sales",
+        json!({ "amount": "20.00", "date": "2025-06-15T10:00:00", "section_id": a }),
+        "pos-1",
+    );
+    assert_eq!(status, 409);
+    assert_eq!(error["error"]["code"], "idempotency_conflict");
+
+    let (_, listed) = server.get(&format!("/sales?term_id={}", term));
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_typed_errors() {
+    let server = TestServer::start();
+    let (a, _, term) = setup(&server);
+
+    let (status, error) = server.post(
+        "/sales",
+        json!({ "amount": "10.00", "date": "2024-01-01T00:00:00", "section_id": a }),
+    );
+    assert_eq!(status, 422);
+    assert_eq!(error["error"]["code"], "rejected");
+    assert_eq!(
+        error["error"]["message"],
+        "Date is outside of the current term"
+    );
+
+    let (status, error) = server.get("/sections/00000000-0000-0000-0000-000000000000");
+    assert_eq!(status, 404);
+    assert_eq!(error["error"]["code"], "not_found");
+
+    let (status, error) = server.post(&format!("/terms/{}/corrections", term), json!({}));
+    assert_eq!(status, 400);
+    assert_eq!(error["error"]["code"], "bad_request");
+
+    let (status, _) = server.get("/sections/not-a-uuid");
+    assert_eq!(status, 400);
+
+    // Reports on a term that does not exist are not found, not rejected
+    let missing = "00000000-0000-0000-0000-000000000000";
+    let (status, error) = server.get(&format!("/terms/{}/report", missing));
+    assert_eq!(status, 404);
+    assert_eq!(error["error"]["code"], "not_found");
+    let (status, error) = server.get(&format!("/terms/{}/compare?base={}", term, missing));
+    assert_eq!(status, 404);
+    assert_eq!(error["error"]["message"], "Base term not found");
+    let (status, _) = server.get(&format!("/series?interval=month&term_id={}", missing));
+    assert_eq!(status, 404);
+    let (status, error) = server.get(&format!(
+        "/series?interval=month&term_id={}&section_id={}",
+        term, missing
+    ));
+    assert_eq!(status, 404);
+    assert_eq!(error["error"]["message"], "Section not found");
+}
+
+#[test]
+fn test_audit_log() {
+    let server = TestServer::start();
+    let (a, b, term) = setup(&server);
+
+    let (status, _) = server.post(&format!("/terms/{}/close", term), json!({}));
+    assert_eq!(status, 200);
+
+    // Corrections are refused without a reason
+    let rebalance = json!({
+        "source_section_id": a,
+        "target_section_id": b,
+        "amount": "10.00",
+        "date": "2025-12-31T00:00:00"
+    });
+    let (status, error) = server.post(&format!("/terms/{}/rebalance", term), rebalance);
+    assert_eq!(status, 422);
+    assert_eq!(
+        error["error"]["message"],
+        "A reason is required for corrections"
+    );
+
+    let (status, history) = server.get(&format!("/audit?entity_id={}", term));
+    assert_eq!(status, 200);
+    let history = history.as_array().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0]["action"], "Created");
+    assert_eq!(history[1]["action"], "Closed");
+    assert_eq!(history[1]["audit"]["actor"], "tester");
+    assert_eq!(history[1]["audit"]["source"], "http");
+
+    let (_, by_actor) = server.get("/audit?actor=tester");
+    assert_eq!(by_actor.as_array().unwrap().len(), 4);
+}
+
+#[test]
+fn test_server_sees_writes_from_other_processes() {
+    let store = temp_store("shared");
+    let server = TestServer::start_on(&store);
+    let (status, term) = server.post(
+        "/terms",
+        json!({ "start_date": "2025-01-01", "end_date": "2025-12-31" }),
+    );
+    assert_eq!(status, 201);
+    let term_id = term["id"].as_str().unwrap().parse().unwrap();
+
+    // Another process, such as the CLI, closes the term while the server runs
+    let (mut cli, _) = open_store(&store).unwrap();
+    let audit = AuditInfo::new(
+        ActorId::new("cli".to_string()).unwrap(),
+        "cli",
+        &SystemClock,
+    );
+    cli.close_term(term_id, None, audit.clone()).unwrap();
+
+    let (status, term) = server.get(&format!("/terms/{}", term_id));
+    assert_eq!(status, 200);
+    assert_eq!(term["status"], "Closed");
+
+    let section = Section::new(
+        cli.ids().next_id(),
+        "Section C".to_string(),
+        SectionType::Section,
+        None,
+    )
+    .unwrap();
+    let section_id = cli.create_section(section, None, audit).unwrap();
+    let (status, _) = server.post(
+        &format!("/sections/{}/rename", section_id),
+        json!({ "name": "Section D", "effective": "2025-06-01" }),
+    );
+    assert_eq!(status, 200);
+
+    drop(server);
+    let _ = std::fs::remove_dir_all(&store);
+}
@@ -0,0 +1,481 @@
+//! Model-based property test of the ledger invariants.
+//!
+//! Random sequences of service calls are applied to an `AccountingService`
+//! alongside a small model of what the ledger should contain. After every
+//! step the ledger is checked for conservation of money, term integrity and a
+//! consistent section hierarchy.
+//!
+//! The model uses the service the way the ledger is meant to be used: it keeps
+//! at most one open term, posts only to sections without children, and only
+//! adds children below sections that have no entries. Within those limits any
+//! call the service rejects must leave the ledger untouched.
+
+use accounting_spec::application::approval::CorrectionWorkflow;
+use accounting_spec::application::report::Structure;
+use accounting_spec::application::service::{AccountingService, ServiceError};
+use accounting_spec::domain::entity::{
+    CorrectionRequest, SalesType, Section, SectionType, Term, TermStatus,
+};
+use accounting_spec::domain::provider::SystemClock;
+use accounting_spec::domain::value_object::{ActorId, AuditInfo, Money};
+use accounting_spec::infrastructure::in_memory::{
+    InMemoryAuditLogRepository, InMemoryPendingCorrectionRepository, InMemorySalesRepository,
+    InMemorySectionRepository, InMemoryTermRepository,
+};
+use chrono::{Duration, Months, NaiveDate, NaiveDateTime};
+use proptest::prelude::*;
+use proptest::sample::Index;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+type Service = AccountingService<
+    InMemorySectionRepository,
+    InMemoryTermRepository,
+    InMemorySalesRepository,
+    InMemoryAuditLogRepository,
+>;
+
+#[derive(Debug, Clone)]
+enum Op {
+    CreateSection {
+        parent: Option<Index>,
+    },
+    CreateTerm,
+    Register {
+        section: Index,
+        cents: i64,
+        day: i64,
+    },
+    Transfer {
+        sale: Index,
+        target: Index,
+        day: i64,
+    },
+    Close,
+    Correct {
+        term: Index,
+        section: Index,
+        original: i64,
+        correct: i64,
+        day: i64,
+    },
+    Rebalance {
+        term: Index,
+        source: Index,
+        target: Index,
+        cents: i64,
+        day: i64,
+    },
+}
+
+fn amount() -> impl Strategy<Value = i64> {
+    prop_oneof![-100_000i64..=-1, 1i64..=100_000]
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        2 => proptest::option::of(any::<Index>()).prop_map(|parent| Op::CreateSection { parent }),
+        1 => Just(Op::CreateTerm),
+        4 => (any::<Index>(), amount(), 0i64..31)
+            .prop_map(|(section, cents, day)| Op::Register { section, cents, day }),
+        2 => (any::<Index>(), any::<Index>(), 0i64..31)
+            .prop_map(|(sale, target, day)| Op::Transfer { sale, target, day }),
+        1 => Just(Op::Close),
+        1 => (any::<Index>(), any::<Index>(), amount(), amount(), 0i64..31).prop_map(
+            |(term, section, original, correct, day)| Op::Correct {
+                term,
+                section,
+                original,
+                correct,
+                day,
+            }
+        ),
+        1 => (any::<Index>(), any::<Index>(), any::<Index>(), 1i64..=100_000, 0i64..31).prop_map(
+            |(term, source, target, cents, day)| Op::Rebalance {
+                term,
+                source,
+                target,
+                cents,
+                day,
+            }
+        ),
+    ]
+}
+
+struct ModelTerm {
+    id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+    open: bool,
+}
+
+impl ModelTerm {
+    /// A time on `day` days into the term, wrapped to stay within it.
+    fn date(&self, day: i64) -> NaiveDateTime {
+        let length = (self.end - self.start).num_days() + 1;
+        (self.start + Duration::days(day % length))
+            .and_hms_opt(10, 0, 0)
+            .unwrap()
+    }
+}
+
+#[derive(Default)]
+struct Model {
+    /// Section IDs with the index of their parent, in creation order
+    sections: Vec<(Uuid, Option<usize>)>,
+    /// Sections with entries, which must never gain children
+    posted: HashSet<Uuid>,
+    terms: Vec<ModelTerm>,
+    /// Normal sales with their section and the index of their term
+    sales: Vec<(Uuid, Uuid, usize)>,
+    /// What each term's normal entries must add up to
+    registered: HashMap<Uuid, Decimal>,
+    /// What all of each term's entries must add up to
+    totals: HashMap<Uuid, Decimal>,
+}
+
+impl Model {
+    fn has_children(&self, index: usize) -> bool {
+        self.sections
+            .iter()
+            .any(|(_, parent)| *parent == Some(index))
+    }
+
+    fn leaves(&self) -> Vec<Uuid> {
+        (0..self.sections.len())
+            .filter(|i| !self.has_children(*i))
+            .map(|i| self.sections[i].0)
+            .collect()
+    }
+
+    fn open_term(&self) -> Option<usize> {
+        self.terms.iter().position(|t| t.open)
+    }
+
+    fn ancestors(&self, index: usize) -> Vec<Uuid> {
+        let mut ancestors = Vec::new();
+        let mut current = self.sections[index].1;
+        while let Some(parent) = current {
+            ancestors.push(self.sections[parent].0);
+            current = self.sections[parent].1;
+        }
+        ancestors
+    }
+}
+
+fn audit() -> AuditInfo {
+    AuditInfo::new(
+        ActorId::new("model".to_string()).unwrap(),
+        "proptest",
+        &SystemClock,
+    )
+    .with_reason(Some("model test".to_string()))
+}
+
+/// Files `request` as the model's actor and approves it as another, the only
+/// way corrections reach the ledger.
+fn approved(service: &mut Service, request: CorrectionRequest) -> Result<(), ServiceError> {
+    let mut workflow = CorrectionWorkflow::new(InMemoryPendingCorrectionRepository::new());
+    let maker = audit().actor;
+    let checker = ActorId::new("checker".to_string()).unwrap();
+    let id = workflow.draft(service, request, maker.clone(), "model test".to_string())?;
+    workflow.submit(service, id, &maker)?;
+    workflow.approve(service, id, checker, "proptest")
+}
+
+fn money(cents: i64) -> Money {
+    Money::new(Decimal::new(cents, 2))
+}
+
+/// Everything a rejected call must leave unchanged.
+fn snapshot(service: &Service) -> (usize, Vec<(Uuid, TermStatus)>, usize) {
+    let mut terms: Vec<_> = service
+        .list_terms()
+        .into_iter()
+        .map(|t| (t.id, t.status))
+        .collect();
+    terms.sort_by_key(|(id, _)| *id);
+    let entries = terms
+        .iter()
+        .map(|(id, _)| service.list_sales(id).len())
+        .sum();
+    (service.list_sections().len(), terms, entries)
+}
+
+/// Applies `op` to the service and the model. Ops the model cannot place,
+/// such as a transfer before any sale exists, are skipped.
+fn apply(service: &mut Service, model: &mut Model, op: &Op) -> Result<(), TestCaseError> {
+    let before = snapshot(service);
+    let expect_err = |result: Result<(), ServiceError>, service: &Service| {
+        prop_assert!(result.is_err(), "{:?} should have been rejected", op);
+        prop_assert_eq!(snapshot(service), before.clone(), "rejected {:?} wrote", op);
+        Ok(())
+    };
+
+    match op {
+        Op::CreateSection { parent } => {
+            let candidates: Vec<usize> = (0..model.sections.len())
+                .filter(|i| !model.posted.contains(&model.sections[*i].0))
+                .collect();
+            let parent = parent
+                .filter(|_| !candidates.is_empty())
+                .map(|p| candidates[p.index(candidates.len())]);
+            let name = format!("Section {}", model.sections.len());
+            let section_type = match parent {
+                Some(_) => SectionType::Section,
+                None => SectionType::Department,
+            };
+            let section = Section::new(
+                service.ids().next_id(),
+                name,
+                section_type,
+                parent.map(|p| model.sections[p].0),
+            )
+            .unwrap();
+            let id = service
+                .create_section(section, None, audit())
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            model.sections.push((id, parent));
+        }
+        Op::CreateTerm => {
+            if model.open_term().is_some() {
+                return Ok(());
+            }
+            let months = model.terms.len() as u32;
+            let first = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+            let start = first + Months::new(months);
+            let end = (first + Months::new(months + 1)).pred_opt().unwrap();
+            let term = Term::new(service.ids().next_id(), start, end).unwrap();
+            let id = service
+                .create_term(term, None, audit())
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            model.terms.push(ModelTerm {
+                id,
+                start,
+                end,
+                open: true,
+            });
+            model.registered.insert(id, Decimal::ZERO);
+            model.totals.insert(id, Decimal::ZERO);
+        }
+        Op::Register {
+            section,
+            cents,
+            day,
+        } => {
+            let leaves = model.leaves();
+            if leaves.is_empty() {
+                return Ok(());
+            }
+            let section_id = leaves[section.index(leaves.len())];
+            let Some(term) = model.open_term() else {
+                let date = NaiveDate::from_ymd_opt(2025, 1, 1)
+                    .unwrap()
+                    .and_hms_opt(10, 0, 0)
+                    .unwrap();
+                let result = service
+                    .register_sales(money(*cents), date, section_id, None, audit())
+                    .map(|_| ());
+                return expect_err(result, service);
+            };
+            let date = model.terms[term].date(*day);
+            let id = service
+                .register_sales(money(*cents), date, section_id, None, audit())
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            model.sales.push((id, section_id, term));
+            model.posted.insert(section_id);
+            let term_id = model.terms[term].id;
+            *model.registered.get_mut(&term_id).unwrap() += Decimal::new(*cents, 2);
+            *model.totals.get_mut(&term_id).unwrap() += Decimal::new(*cents, 2);
+        }
+        Op::Transfer { sale, target, day } => {
+            let leaves = model.leaves();
+            if model.sales.is_empty() {
+                return Ok(());
+            }
+            let (sales_id, section_id, term) = model.sales[sale.index(model.sales.len())];
+            let target_id = leaves[target.index(leaves.len())];
+            let date = model.terms[term].date(*day);
+            let result = service
+                .transform_sales(sales_id, target_id, date, None, audit())
+                .map(|_| ());
+            if target_id == section_id {
+                return expect_err(result, service);
+            }
+            result.map_err(|e| TestCaseError::fail(e.to_string()))?;
+            model.posted.insert(target_id);
+        }
+        Op::Close => {
+            let Some(term) = model.open_term() else {
+                return Ok(());
+            };
+            service
+                .close_term(model.terms[term].id, None, audit())
+                .map_err(|e| TestCaseError::fail(e.to_string()))?;
+            model.terms[term].open = false;
+        }
+        Op::Correct {
+            term,
+            section,
+            original,
+            correct,
+            day,
+        } => {
+            let leaves = model.leaves();
+            if model.terms.is_empty() || leaves.is_empty() {
+                return Ok(());
+            }
+            let term = &model.terms[term.index(model.terms.len())];
+            let section_id = leaves[section.index(leaves.len())];
+            let (term_id, date) = (term.id, term.date(*day));
+            let request = CorrectionRequest::Correct {
+                term_id,
+                section_id,
+                original_amount: money(*original),
+                correct_amount: money(*correct),
+                date,
+            };
+            approved(service, request).map_err(|e| TestCaseError::fail(e.to_string()))?;
+            model.posted.insert(section_id);
+            *model.totals.get_mut(&term_id).unwrap() +=
+                Decimal::new(*correct, 2) - Decimal::new(*original, 2);
+        }
+        Op::Rebalance {
+            term,
+            source,
+            target,
+            cents,
+            day,
+        } => {
+            let leaves = model.leaves();
+            if model.terms.is_empty() || leaves.is_empty() {
+                return Ok(());
+            }
+            let term = &model.terms[term.index(model.terms.len())];
+            let source_id = leaves[source.index(leaves.len())];
+            let target_id = leaves[target.index(leaves.len())];
+            let request = CorrectionRequest::Rebalance {
+                term_id: term.id,
+                source_section_id: source_id,
+                target_section_id: target_id,
+                amount: money(*cents),
+                date: term.date(*day),
+            };
+            let result = approved(service, request);
+            if source_id == target_id {
+                return expect_err(result, service);
+            }
+            result.map_err(|e| TestCaseError::fail(e.to_string()))?;
+            model.posted.insert(source_id);
+            model.posted.insert(target_id);
+        }
+    }
+    Ok(())
+}
+
+/// Money is neither created nor destroyed: normal entries add up to what was
+/// registered, transfers net to zero, and only corrections change the total.
+fn check_conservation(service: &Service, model: &Model) -> Result<(), TestCaseError> {
+    for term in &model.terms {
+        let entries = service.list_sales(&term.id);
+        let sum = |sales_type: SalesType| -> Decimal {
+            entries
+                .iter()
+                .filter(|s| s.sales_type == sales_type)
+                .map(|s| s.amount.amount())
+                .sum()
+        };
+        prop_assert_eq!(sum(SalesType::Normal), model.registered[&term.id]);
+        prop_assert_eq!(sum(SalesType::Adjustment), Decimal::ZERO);
+        let total: Decimal = entries.iter().map(|s| s.amount.amount()).sum();
+        prop_assert_eq!(total, model.totals[&term.id]);
+    }
+    Ok(())
+}
+
+/// Every term passes its integrity check and agrees with the model on status.
+fn check_terms(service: &Service, model: &Model) -> Result<(), TestCaseError> {
+    let open = service
+        .list_terms()
+        .iter()
+        .filter(|t| t.status == TermStatus::Open)
+        .count();
+    prop_assert!(open <= 1);
+    for term in &model.terms {
+        let stored = service.find_term(&term.id).unwrap();
+        prop_assert_eq!(stored.status == TermStatus::Open, term.open);
+        let report = service
+            .reports()
+            .term_integrity(term.id)
+            .map_err(|e| TestCaseError::fail(e.to_string()))?;
+        prop_assert!(
+            report.is_consistent(),
+            "integrity violations: {:?}",
+            report.violations
+        );
+    }
+    Ok(())
+}
+
+/// The hierarchy matches the model, and every rollup line is its own entries
+/// plus the totals of its children.
+fn check_hierarchy(service: &Service, model: &Model) -> Result<(), TestCaseError> {
+    let hierarchy = service.hierarchy();
+    let mut covered = 0;
+    for (index, (id, parent)) in model.sections.iter().enumerate() {
+        let ancestors: Vec<Uuid> = hierarchy
+            .ancestors(*id)
+            .map_err(|e| TestCaseError::fail(e.to_string()))?
+            .iter()
+            .map(|s| s.id)
+            .collect();
+        prop_assert_eq!(&ancestors, &model.ancestors(index));
+        prop_assert_eq!(hierarchy.depth(*id).unwrap(), ancestors.len());
+        if parent.is_none() {
+            covered += hierarchy
+                .subtree(*id)
+                .map_err(|e| TestCaseError::fail(e.to_string()))?
+                .len();
+        }
+    }
+    prop_assert_eq!(covered, model.sections.len());
+
+    for term in &model.terms {
+        let rollup = service
+            .reports()
+            .term_rollup(term.id, Structure::Restated)
+            .map_err(|e| TestCaseError::fail(e.to_string()))?;
+        for line in &rollup.lines {
+            let children = rollup
+                .lines
+                .iter()
+                .filter(|l| l.parent_id == Some(line.section_id))
+                .fold(Money::zero(), |acc, l| acc + l.total);
+            prop_assert_eq!(line.total, line.own + children);
+        }
+        prop_assert_eq!(rollup.total.amount(), model.totals[&term.id]);
+    }
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn ledger_invariants_hold_after_every_step(ops in prop::collection::vec(op(), 1..40)) {
+        let mut service = AccountingService::new(
+            InMemorySectionRepository::new(),
+            InMemoryTermRepository::new(),
+            InMemorySalesRepository::new(),
+            InMemoryAuditLogRepository::new(),
+        );
+        let mut model = Model::default();
+        for op in &ops {
+            apply(&mut service, &mut model, op)?;
+            check_conservation(&service, &model)?;
+            check_terms(&service, &model)?;
+            check_hierarchy(&service, &model)?;
+        }
+    }
+}
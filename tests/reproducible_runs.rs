@@ -0,0 +1,163 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn run(store: &Path, seed: u64, actor: &str, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_accounting_spec"))
+        .arg("--store")
+        .arg(store)
+        .args(["--seed", &seed.to_string()])
+        .args(["--now", "2025-07-01T09:00:00"])
+        .args(["--actor", actor])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Runs the CLI against `store` with a fixed clock and returns what it printed.
+fn cli(store: &Path, seed: u64, actor: &str, args: &[&str]) -> String {
+    let output = run(store, seed, actor, args);
+    assert!(
+        output.status.success(),
+        "{:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+/// The same session: sections, a term, a sale, a transfer and an approved
+/// rebalance. Each command gets its own seed.
+fn session(store: &Path) {
+    cli(store, 1, "alice", &["section", "add", "Sales Dept A"]);
+    cli(store, 2, "alice", &["section", "add", "Sales Dept B"]);
+    let term = cli(
+        store,
+        3,
+        "alice",
+        &["term", "add", "2025-01-01", "2025-12-31"],
+    );
+    let sale = cli(
+        store,
+        4,
+        "alice",
+        &[
+            "sales",
+            "register",
+            "--section",
+            "Sales Dept A",
+            "--amount",
+            "1000.00",
+            "--date",
+            "2025-06-15",
+        ],
+    );
+    cli(
+        store,
+        5,
+        "alice",
+        &[
+            "sales",
+            "transfer",
+            &sale,
+            "--to",
+            "Sales Dept B",
+            "--date",
+            "2025-06-20",
+        ],
+    );
+    let request = cli(
+        store,
+        6,
+        "alice",
+        &[
+            "rebalance",
+            "--term",
+            &term,
+            "--from",
+            "Sales Dept B",
+            "--to",
+            "Sales Dept A",
+            "--amount",
+            "100",
+            "--date",
+            "2025-06-30",
+            "--reason",
+            "Misallocated",
+        ],
+    );
+    cli(store, 7, "bob", &["approval", "approve", &request]);
+}
+
+fn temp_store(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "accounting-reproducible-{}-{}",
+        std::process::id(),
+        name
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn test_seeded_runs_write_identical_files() {
+    let (first, second) = (temp_store("first"), temp_store("second"));
+    session(&first);
+    session(&second);
+
+    let mut names: Vec<_> = fs::read_dir(&first)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    names.sort();
+    assert!(names.iter().any(|n| n == "audit.jsonl"));
+    assert!(names.iter().any(|n| n == "corrections.jsonl"));
+    for name in &names {
+        let expected = fs::read(first.join(name)).unwrap();
+        let actual = fs::read(second.join(name)).unwrap();
+        assert!(expected == actual, "{:?} differs between runs", name);
+    }
+    assert_eq!(fs::read_dir(&second).unwrap().count(), names.len());
+
+    fs::remove_dir_all(&first).unwrap();
+    fs::remove_dir_all(&second).unwrap();
+}
+
+#[test]
+fn test_reused_seed_never_overwrites_entries() {
+    let store = temp_store("reused");
+    cli(&store, 1, "alice", &["section", "add", "Sales Dept A"]);
+    cli(
+        &store,
+        2,
+        "alice",
+        &["term", "add", "2025-01-01", "2025-12-31"],
+    );
+    let register = |amount| {
+        run(
+            &store,
+            3,
+            "alice",
+            &[
+                "sales",
+                "register",
+                "--section",
+                "Sales Dept A",
+                "--amount",
+                amount,
+                "--date",
+                "2025-06-15",
+            ],
+        )
+    };
+    assert!(register("100").status.success());
+    let second = register("250");
+    assert!(!second.status.success());
+    assert!(String::from_utf8_lossy(&second.stderr).contains("already exists"));
+
+    let sales = fs::read_to_string(store.join("sales.jsonl")).unwrap();
+    assert_eq!(sales.lines().count(), 1);
+    assert!(sales.contains("\"100\""));
+
+    fs::remove_dir_all(&store).unwrap();
+}